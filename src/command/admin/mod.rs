@@ -1,5 +1,7 @@
 mod autorole;
 mod quote_channel;
+mod rps_settings;
 
 pub use autorole::autorole;
 pub use quote_channel::quote_channel;
+pub use rps_settings::rps_settings;
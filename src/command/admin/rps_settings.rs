@@ -0,0 +1,490 @@
+use std::{fmt::Write, time::Duration};
+
+use poise::{
+	serenity_prelude::{CreateEmbed, GuildChannel, Mentionable, Role},
+	ChoiceParameter, CreateReply,
+};
+
+use crate::{
+	command::{format_duration, parent_command, rps::rank_roles::sync_rank_roles, ExpectGuildOnly},
+	data::{DepartedPlayers, RankRequirement, RatingSystem, RpsSettings},
+	Context, Error, Reply,
+};
+
+parent_command! {
+	let rps_settings = poise::command(
+		prefix_command,
+		slash_command,
+		guild_only,
+		required_permissions = "MANAGE_GUILD",
+		subcommands(
+			"timeouts",
+			"rating_system",
+			"global_ladder",
+			"rank_role",
+			"announcements",
+			"threads",
+			"moderation",
+			"departed_players",
+			"check"
+		)
+	)
+}
+
+parent_command! {
+	let rank_role = poise::command(
+		prefix_command,
+		slash_command,
+		guild_only,
+		required_permissions = "MANAGE_GUILD",
+		required_bot_permissions = "MANAGE_ROLES",
+		subcommands("add_rank_role", "remove_rank_role")
+	)
+}
+
+/// Change how long players have to respond during Rock, Paper, Scissors matches
+#[poise::command(
+	prefix_command,
+	slash_command,
+	guild_only,
+	required_permissions = "MANAGE_GUILD"
+)]
+pub async fn timeouts(
+	ctx: Context<'_>,
+	#[description = "Minutes a challenged player has to accept"]
+	#[min = 1]
+	accept: Option<u32>,
+	#[description = "Minutes players have to make their selection each round"]
+	#[min = 1]
+	round: Option<u32>,
+) -> Result<(), Error>
+{
+	if accept.is_none() && round.is_none()
+	{
+		ctx.reply_error("Specify at least one timeout to change!")
+			.await?;
+		return Ok(());
+	}
+
+	let settings = {
+		let mut data = ctx.data().acquire_lock().await;
+		let settings = data
+			.guild_data_mut(ctx.guild_id().expect_guild_only())
+			.rps_settings_mut();
+
+		if let Some(accept) = accept
+		{
+			settings.set_accept_timeout(Duration::from_secs(u64::from(accept) * 60));
+		}
+		if let Some(round) = round
+		{
+			settings.set_round_timeout(Duration::from_secs(u64::from(round) * 60));
+		}
+
+		settings.clone()
+	};
+
+	ctx.send(
+		CreateReply::default()
+			.embed(settings_embed(&settings))
+			.ephemeral(true),
+	)
+	.await?;
+
+	Ok(())
+}
+
+/// Change which rating system the Rock, Paper, Scissors leaderboard uses
+#[poise::command(
+	prefix_command,
+	slash_command,
+	guild_only,
+	required_permissions = "MANAGE_GUILD"
+)]
+pub async fn rating_system(
+	ctx: Context<'_>,
+	#[description = "The rating system to use"] system: RatingSystem,
+) -> Result<(), Error>
+{
+	let settings = {
+		let mut data = ctx.data().acquire_lock().await;
+		let guild_data = data.guild_data_mut(ctx.guild_id().expect_guild_only());
+
+		if guild_data.rps_settings().rating_system() == system
+		{
+			drop(data);
+			ctx.reply_error(format!("This server already uses {}!", system.name()))
+				.await?;
+			return Ok(());
+		}
+
+		guild_data.leaderboard_mut().convert_ratings(system);
+		guild_data.rps_settings_mut().set_rating_system(system);
+		guild_data.rps_settings().clone()
+	};
+
+	ctx.send(
+		CreateReply::default()
+			.embed(settings_embed(&settings))
+			.ephemeral(true),
+	)
+	.await?;
+
+	Ok(())
+}
+
+/// Choose whether ranked matches in this server count towards the global leaderboard
+#[poise::command(
+	prefix_command,
+	slash_command,
+	guild_only,
+	required_permissions = "MANAGE_GUILD"
+)]
+pub async fn global_ladder(
+	ctx: Context<'_>,
+	#[description = "Whether this server's ranked matches count towards the global leaderboard"]
+	enabled: bool,
+) -> Result<(), Error>
+{
+	let settings = {
+		let mut data = ctx.data().acquire_lock().await;
+		let settings = data
+			.guild_data_mut(ctx.guild_id().expect_guild_only())
+			.rps_settings_mut();
+		settings.set_global_ladder_enabled(enabled);
+		settings.clone()
+	};
+
+	ctx.send(
+		CreateReply::default()
+			.embed(settings_embed(&settings))
+			.ephemeral(true),
+	)
+	.await?;
+
+	Ok(())
+}
+
+/// Choose whether Rock, Paper, Scissors challenges are played in their own threads by default
+#[poise::command(
+	prefix_command,
+	slash_command,
+	guild_only,
+	required_permissions = "MANAGE_GUILD"
+)]
+pub async fn threads(
+	ctx: Context<'_>,
+	#[description = "Whether challenges are played in a thread off of the challenge message"]
+	enabled: bool,
+) -> Result<(), Error>
+{
+	let settings = {
+		let mut data = ctx.data().acquire_lock().await;
+		let settings = data
+			.guild_data_mut(ctx.guild_id().expect_guild_only())
+			.rps_settings_mut();
+		settings.set_match_threads(enabled);
+		settings.clone()
+	};
+
+	ctx.send(
+		CreateReply::default()
+			.embed(settings_embed(&settings))
+			.ephemeral(true),
+	)
+	.await?;
+
+	Ok(())
+}
+
+/// Give a role to players who reach a leaderboard position or rating
+#[poise::command(
+	prefix_command,
+	slash_command,
+	guild_only,
+	required_permissions = "MANAGE_GUILD",
+	required_bot_permissions = "MANAGE_ROLES",
+	rename = "add"
+)]
+pub async fn add_rank_role(
+	ctx: Context<'_>,
+	#[description = "The role to give"] role: Role,
+	#[description = "Give the role to players ranked this high or higher"]
+	#[min = 1]
+	top: Option<u32>,
+	#[description = "Give the role to players rated at least this much"] min_rating: Option<i32>,
+) -> Result<(), Error>
+{
+	let requirement = match (top, min_rating)
+	{
+		(Some(top), None) => RankRequirement::Top(top),
+		(None, Some(min_rating)) => RankRequirement::Rating(min_rating),
+		_ =>
+		{
+			ctx.reply_error("Specify either a position or a rating, but not both!")
+				.await?;
+			return Ok(());
+		}
+	};
+
+	let guild_id = ctx.guild_id().expect_guild_only();
+	let settings = {
+		let mut data = ctx.data().acquire_lock().await;
+		let settings = data.guild_data_mut(guild_id).rps_settings_mut();
+		settings.rank_roles_mut().set(role.id, requirement);
+		settings.clone()
+	};
+
+	ctx.send(
+		CreateReply::default()
+			.embed(settings_embed(&settings))
+			.ephemeral(true),
+	)
+	.await?;
+
+	sync_rank_roles(ctx, guild_id, &[]).await?;
+
+	Ok(())
+}
+
+/// Stop giving a role for leaderboard placement, taking it back from everyone who has it
+#[poise::command(
+	prefix_command,
+	slash_command,
+	guild_only,
+	required_permissions = "MANAGE_GUILD",
+	required_bot_permissions = "MANAGE_ROLES",
+	rename = "remove"
+)]
+pub async fn remove_rank_role(
+	ctx: Context<'_>,
+	#[description = "The rank role to remove"] role: Role,
+) -> Result<(), Error>
+{
+	let guild_id = ctx.guild_id().expect_guild_only();
+	let settings = {
+		let mut data = ctx.data().acquire_lock().await;
+		let settings = data.guild_data_mut(guild_id).rps_settings_mut();
+		settings
+			.rank_roles_mut()
+			.remove(role.id)
+			.then(|| settings.clone())
+	};
+
+	let Some(settings) = settings
+	else
+	{
+		ctx.reply_error(format!("{} isn't a rank role!", role.mention()))
+			.await?;
+		return Ok(());
+	};
+
+	ctx.send(
+		CreateReply::default()
+			.embed(settings_embed(&settings))
+			.ephemeral(true),
+	)
+	.await?;
+
+	sync_rank_roles(ctx, guild_id, &[role.id]).await?;
+
+	Ok(())
+}
+
+/// Choose where to announce new leaders and players reaching new rank roles
+#[poise::command(
+	prefix_command,
+	slash_command,
+	guild_only,
+	required_permissions = "MANAGE_GUILD"
+)]
+pub async fn announcements(
+	ctx: Context<'_>,
+	#[description = "The channel to announce in (leave empty to turn announcements off)"]
+	channel: Option<GuildChannel>,
+) -> Result<(), Error>
+{
+	let settings = {
+		let mut data = ctx.data().acquire_lock().await;
+		let settings = data
+			.guild_data_mut(ctx.guild_id().expect_guild_only())
+			.rps_settings_mut();
+		settings.set_announcement_channel(channel.map(|channel| channel.id));
+		settings.clone()
+	};
+
+	ctx.send(
+		CreateReply::default()
+			.embed(settings_embed(&settings))
+			.ephemeral(true),
+	)
+	.await?;
+
+	Ok(())
+}
+
+/// Choose where to report Rock, Paper, Scissors matches that look like win trading
+#[poise::command(
+	prefix_command,
+	slash_command,
+	guild_only,
+	required_permissions = "MANAGE_GUILD"
+)]
+pub async fn moderation(
+	ctx: Context<'_>,
+	#[description = "The channel to report suspicious matches in (leave empty to stop reporting)"]
+	channel: Option<GuildChannel>,
+) -> Result<(), Error>
+{
+	let settings = {
+		let mut data = ctx.data().acquire_lock().await;
+		let settings = data
+			.guild_data_mut(ctx.guild_id().expect_guild_only())
+			.rps_settings_mut();
+		settings.set_moderation_channel(channel.map(|channel| channel.id));
+		settings.clone()
+	};
+
+	ctx.send(
+		CreateReply::default()
+			.embed(settings_embed(&settings))
+			.ephemeral(true),
+	)
+	.await?;
+
+	Ok(())
+}
+
+/// Choose what happens to players on the Rock, Paper, Scissors leaderboard after they leave
+#[poise::command(
+	prefix_command,
+	slash_command,
+	guild_only,
+	required_permissions = "MANAGE_GUILD"
+)]
+pub async fn departed_players(
+	ctx: Context<'_>,
+	#[description = "Whether players who left are marked, hidden, or removed"]
+	handling: DepartedPlayers,
+) -> Result<(), Error>
+{
+	let settings = {
+		let mut data = ctx.data().acquire_lock().await;
+		let settings = data
+			.guild_data_mut(ctx.guild_id().expect_guild_only())
+			.rps_settings_mut();
+		settings.set_departed_players(handling);
+		settings.clone()
+	};
+
+	ctx.send(
+		CreateReply::default()
+			.embed(settings_embed(&settings))
+			.ephemeral(true),
+	)
+	.await?;
+
+	Ok(())
+}
+
+/// Show the current Rock, Paper, Scissors settings for this server
+#[poise::command(
+	prefix_command,
+	slash_command,
+	guild_only,
+	required_permissions = "MANAGE_GUILD"
+)]
+pub async fn check(ctx: Context<'_>) -> Result<(), Error>
+{
+	let settings = ctx
+		.data()
+		.acquire_lock()
+		.await
+		.guild_data(ctx.guild_id().expect_guild_only())
+		.map(|dat| dat.rps_settings().clone())
+		.unwrap_or_default();
+
+	ctx.send(
+		CreateReply::default()
+			.embed(settings_embed(&settings))
+			.ephemeral(true),
+	)
+	.await?;
+
+	Ok(())
+}
+
+fn settings_embed(settings: &RpsSettings) -> CreateEmbed
+{
+	CreateEmbed::new()
+		.title("Rock Paper Scissors Settings")
+		.field(
+			"Accept timeout",
+			format_duration(settings.accept_timeout()),
+			true,
+		)
+		.field(
+			"Round timeout",
+			format_duration(settings.round_timeout()),
+			true,
+		)
+		.field("Rating system", settings.rating_system().name(), true)
+		.field(
+			"Global leaderboard",
+			if settings.global_ladder_enabled() { "Included" } else { "Opted out" },
+			true,
+		)
+		.field(
+			"Announcements",
+			settings
+				.announcement_channel()
+				.map_or_else(|| String::from("Off"), |channel| channel.mention().to_string()),
+			true,
+		)
+		.field(
+			"Match threads",
+			if settings.match_threads() { "On" } else { "Off" },
+			true,
+		)
+		.field(
+			"Suspicious matches",
+			settings.moderation_channel().map_or_else(
+				|| String::from("Not reported"),
+				|channel| channel.mention().to_string(),
+			),
+			true,
+		)
+		.field(
+			"Departed players",
+			settings.departed_players().name(),
+			true,
+		)
+		.field("Rank roles", rank_roles_string(settings), false)
+		.color(crate::DEFAULT_COLOR)
+}
+
+fn rank_roles_string(settings: &RpsSettings) -> String
+{
+	if settings.rank_roles().is_empty()
+	{
+		return String::from("None");
+	}
+
+	let mut rank_roles = String::new();
+	for rank_role in settings.rank_roles().iter()
+	{
+		let _ = match rank_role.requirement
+		{
+			RankRequirement::Top(1) => writeln!(rank_roles, "{} - #1", rank_role.role.mention()),
+			RankRequirement::Top(top) =>
+			{
+				writeln!(rank_roles, "{} - Top {top}", rank_role.role.mention())
+			}
+			RankRequirement::Rating(min_rating) =>
+			{
+				writeln!(rank_roles, "{} - {min_rating}+ rating", rank_role.role.mention())
+			}
+		};
+	}
+	rank_roles
+}
@@ -5,6 +5,10 @@ mod random_user;
 mod roll;
 mod rps;
 
+pub use rps::remove_departed;
+
+use std::time::Duration;
+
 use crate::{data::Data, Error};
 use poise::{
 	serenity_prelude::{self as serenity, GuildId},
@@ -21,6 +25,7 @@ pub fn vec() -> Vec<Command<Data, Error>>
 		quote::quote(),
 		admin::autorole(),
 		admin::quote_channel(),
+		admin::rps_settings(),
 	]
 }
 
@@ -102,3 +107,23 @@ fn type_name(s: &str) -> &str
 
 	&s[start..end]
 }
+
+/// Formats a duration the way it'd be said out loud, using only its largest whole unit
+pub fn format_duration(duration: Duration) -> String
+{
+	let seconds = duration.as_secs();
+	let (amount, unit) = match seconds
+	{
+		0..=59 => (seconds, "second"),
+		60..=3599 => (seconds / 60, "minute"),
+		3600..=86399 => (seconds / 3600, "hour"),
+		_ => (seconds / 86400, "day"),
+	};
+
+	match amount
+	{
+		1 if unit == "hour" => String::from("an hour"),
+		1 => format!("a {unit}"),
+		_ => format!("{amount} {unit}s"),
+	}
+}
@@ -0,0 +1,982 @@
+use std::{collections::HashSet, fmt::Write};
+
+use futures::TryStreamExt;
+use poise::{
+	serenity_prelude::{
+		self as serenity, CreateAllowedMentions, CreateEmbed, CreateMessage, GuildId, Mentionable,
+		User, UserId,
+	},
+	CreateReply,
+};
+
+use super::rank_roles::RankChanges;
+use crate::{
+	command::{parent_command, ExpectGuildOnly},
+	data::{
+		AuditAction, Data, DepartedPlayers, FlagReason, FlagStatus, GuildData, MatchRecord,
+		Outcome, ScoreStat,
+	},
+	Context, Error, Reply,
+};
+
+/// how many flagged matches are listed at once
+const FLAGGED_LIST_LENGTH: usize = 15;
+/// how many audit log entries are listed at once
+const AUDIT_LIST_LENGTH: usize = 15;
+
+parent_command! {
+	let admin = poise::command(
+		prefix_command,
+		slash_command,
+		guild_only,
+		subcommands(
+			"flagged", "void", "dismiss", "reset", "set", "adjust", "merge", "prune", "audit"
+		)
+	)
+}
+
+/// A match that was just flagged for looking like win trading
+pub struct FlaggedMatch
+{
+	pub id: usize,
+	pub winner: UserId,
+	pub loser: UserId,
+	pub reason: FlagReason,
+}
+
+/// Reports a flagged match in the server's moderation channel, if it has one
+pub async fn report_flag(
+	ctx: Context<'_>,
+	guild_id: GuildId,
+	flag: &FlaggedMatch,
+) -> Result<(), Error>
+{
+	let moderation_channel = ctx
+		.data()
+		.acquire_lock()
+		.await
+		.guild_data(guild_id)
+		.and_then(|guild_data| guild_data.rps_settings().moderation_channel());
+
+	let Some(channel) = moderation_channel
+	else
+	{
+		return Ok(());
+	};
+
+	channel
+		.send_message(
+			ctx,
+			CreateMessage::new()
+				.embed(
+					CreateEmbed::new()
+						.title(format!("Suspicious Match #{}", flag.id))
+						.description(format!(
+							"{} beat {}\n{}",
+							flag.winner.mention(),
+							flag.loser.mention(),
+							flag.reason
+						))
+						.field(
+							"Review",
+							format!(
+								"`/rps admin void id:{0}` takes back the match's result, or \
+								 `/rps admin dismiss id:{0}` leaves it as is",
+								flag.id
+							),
+							false,
+						)
+						.color(crate::DEFAULT_COLOR),
+				)
+				.allowed_mentions(CreateAllowedMentions::new()),
+		)
+		.await?;
+
+	Ok(())
+}
+
+/// List the Rock, Paper, Scissors matches flagged for looking like win trading
+#[poise::command(
+	prefix_command,
+	slash_command,
+	guild_only,
+	required_permissions = "MANAGE_GUILD"
+)]
+pub async fn flagged(
+	ctx: Context<'_>,
+	#[description = "Include matches that were already reviewed (default: false)"]
+	reviewed: Option<bool>,
+) -> Result<(), Error>
+{
+	let reviewed = reviewed.unwrap_or(false);
+
+	let flagged_list = {
+		let data = ctx.data().acquire_lock().await;
+		let mut flagged_list = String::new();
+		if let Some(guild_data) = data.guild_data(ctx.guild_id().expect_guild_only())
+		{
+			for (id, record) in guild_data
+				.match_history()
+				.flagged()
+				.rev()
+				.filter(|(_, record)| {
+					reviewed || record.flag.is_some_and(|flag| flag.status == FlagStatus::Open)
+				})
+				.take(FLAGGED_LIST_LENGTH)
+			{
+				let _ = writeln!(flagged_list, "{}", flag_string(id, record));
+			}
+		}
+		flagged_list
+	};
+
+	ctx.send(
+		CreateReply::default()
+			.embed(
+				CreateEmbed::new()
+					.title("Flagged Matches")
+					.description(if flagged_list.is_empty()
+					{
+						String::from("No matches need reviewing")
+					}
+					else
+					{
+						flagged_list
+					})
+					.color(crate::DEFAULT_COLOR),
+			)
+			.ephemeral(true)
+			.allowed_mentions(CreateAllowedMentions::new()),
+	)
+	.await?;
+
+	Ok(())
+}
+
+/// Void a Rock, Paper, Scissors match, taking back the wins, losses, and rating it gave
+#[poise::command(
+	prefix_command,
+	slash_command,
+	guild_only,
+	required_permissions = "MANAGE_GUILD"
+)]
+pub async fn void(
+	ctx: Context<'_>,
+	#[description = "The id of the match"]
+	#[min = 1]
+	id: u32,
+	#[description = "Why the match is being voided"] reason: Option<String>,
+) -> Result<(), Error>
+{
+	let guild_id = ctx.guild_id().expect_guild_only();
+	let id = id as usize;
+
+	let voided = {
+		let mut data = ctx.data().acquire_lock().await;
+		let guild_data = data.guild_data_mut(guild_id);
+		let voided = void_match(guild_data, id);
+		if voided.is_ok()
+		{
+			guild_data
+				.audit_log_mut()
+				.record(ctx.author().id, AuditAction::VoidMatch { id }, reason);
+		}
+		voided
+	};
+
+	let (records, rank_changes) = match voided
+	{
+		Ok(voided) => voided,
+		Err(error) =>
+		{
+			ctx.reply_error(error).await?;
+			return Ok(());
+		}
+	};
+
+	let mut players = records
+		.iter()
+		.flat_map(|record| [record.challenger, record.opponent])
+		.collect::<Vec<_>>();
+	players.sort_unstable();
+	players.dedup();
+
+	let mut embed = CreateEmbed::new()
+		.title(format!("Match #{id} Voided"))
+		.description(match &records[..]
+		{
+			[record] => format!(
+				"{}'s win over {} no longer counts",
+				record.winner.mention(),
+				record.opponent_of(record.winner).mention()
+			),
+			_ => format!(
+				"The whole game it was part of no longer counts for {}",
+				super::mention_list(&players)
+			),
+		})
+		.color(crate::DEFAULT_COLOR);
+	let results = game_results(&records);
+	if !results.is_empty()
+	{
+		let mut ratings = String::new();
+		for (player, _, change) in results
+		{
+			let _ = writeln!(ratings, "{} {:+}", player.mention(), -change);
+		}
+		embed = embed.field("Ratings", ratings, false);
+	}
+
+	ctx.send(
+		CreateReply::default()
+			.embed(embed)
+			.ephemeral(true)
+			.allowed_mentions(CreateAllowedMentions::new()),
+	)
+	.await?;
+
+	rank_changes.apply(ctx, guild_id, &players).await;
+
+	Ok(())
+}
+
+/// Leave a flagged Rock, Paper, Scissors match as it is, marking it as reviewed
+#[poise::command(
+	prefix_command,
+	slash_command,
+	guild_only,
+	required_permissions = "MANAGE_GUILD"
+)]
+pub async fn dismiss(
+	ctx: Context<'_>,
+	#[description = "The id of the flagged match"]
+	#[min = 1]
+	id: u32,
+	#[description = "Why the match is fine"] reason: Option<String>,
+) -> Result<(), Error>
+{
+	let id = id as usize;
+
+	let error = {
+		let mut data = ctx.data().acquire_lock().await;
+		let guild_data = data.guild_data_mut(ctx.guild_id().expect_guild_only());
+		let flag = guild_data
+			.match_history_mut()
+			.get_mut(id)
+			.map(|record| &mut record.flag);
+
+		let error = match flag
+		{
+			None => Some(format!("There's no match #{id}!")),
+			Some(None) => Some(format!("Match #{id} wasn't flagged!")),
+			Some(Some(flag)) if flag.status != FlagStatus::Open =>
+			{
+				Some(format!("Match #{id} was already reviewed!"))
+			}
+			Some(Some(flag)) =>
+			{
+				flag.status = FlagStatus::Dismissed;
+				None
+			}
+		};
+		if error.is_none()
+		{
+			guild_data
+				.audit_log_mut()
+				.record(ctx.author().id, AuditAction::DismissFlag { id }, reason);
+		}
+		error
+	};
+
+	if let Some(error) = error
+	{
+		ctx.reply_error(error).await?;
+		return Ok(());
+	}
+
+	ctx.send(
+		CreateReply::default()
+			.embed(
+				CreateEmbed::new()
+					.title(format!("Match #{id} Dismissed"))
+					.description("The match's result stands")
+					.color(crate::DEFAULT_COLOR),
+			)
+			.ephemeral(true),
+	)
+	.await?;
+
+	Ok(())
+}
+
+/// Wipe a player's Rock, Paper, Scissors score from the leaderboard
+#[poise::command(
+	prefix_command,
+	slash_command,
+	guild_only,
+	required_permissions = "MANAGE_GUILD"
+)]
+pub async fn reset(
+	ctx: Context<'_>,
+	#[description = "The player to reset"] player: User,
+	#[description = "Why the player is being reset"] reason: Option<String>,
+) -> Result<(), Error>
+{
+	let guild_id = ctx.guild_id().expect_guild_only();
+
+	let rank_changes = {
+		let mut data = ctx.data().acquire_lock().await;
+		let guild_data = data.guild_data_mut(guild_id);
+		let (removed, rank_changes) = RankChanges::track(guild_data, |guild_data| {
+			guild_data.leaderboard_mut().remove(player.id)
+		});
+		if removed.is_some()
+		{
+			guild_data.rating_history_mut().remove(player.id);
+			guild_data.audit_log_mut().record(
+				ctx.author().id,
+				AuditAction::ResetPlayer { player: player.id },
+				reason,
+			);
+		}
+		removed.map(|_| rank_changes)
+	};
+
+	let Some(rank_changes) = rank_changes
+	else
+	{
+		ctx.reply_error(format!("{} isn't on the leaderboard!", player.mention()))
+			.await?;
+		return Ok(());
+	};
+
+	ctx.send(
+		CreateReply::default()
+			.embed(
+				CreateEmbed::new()
+					.title("Player Reset")
+					.description(format!(
+						"{}'s wins, losses, and rating have been wiped from the leaderboard",
+						player.mention()
+					))
+					.color(crate::DEFAULT_COLOR),
+			)
+			.ephemeral(true)
+			.allowed_mentions(CreateAllowedMentions::new()),
+	)
+	.await?;
+
+	rank_changes.apply(ctx, guild_id, &[player.id]).await;
+
+	Ok(())
+}
+
+/// Set a player's Rock, Paper, Scissors rating, wins, or losses
+#[poise::command(
+	prefix_command,
+	slash_command,
+	guild_only,
+	required_permissions = "MANAGE_GUILD"
+)]
+pub async fn set(
+	ctx: Context<'_>,
+	#[description = "The player to change"] player: User,
+	#[description = "What to change"] stat: ScoreStat,
+	#[description = "The new value"] value: i32,
+	#[description = "Why the change is being made"] reason: String,
+) -> Result<(), Error>
+{
+	change_stat(ctx, &player, stat, reason, |_| value).await
+}
+
+/// Raise or lower a player's Rock, Paper, Scissors rating, wins, or losses
+#[poise::command(
+	prefix_command,
+	slash_command,
+	guild_only,
+	required_permissions = "MANAGE_GUILD"
+)]
+pub async fn adjust(
+	ctx: Context<'_>,
+	#[description = "The player to change"] player: User,
+	#[description = "What to change"] stat: ScoreStat,
+	#[description = "How much to add (negative to take away)"] amount: i32,
+	#[description = "Why the change is being made"] reason: String,
+) -> Result<(), Error>
+{
+	change_stat(ctx, &player, stat, reason, |old| old.saturating_add(amount)).await
+}
+
+/// Fold an alt account's Rock, Paper, Scissors score and matches into a player's main account
+#[poise::command(
+	prefix_command,
+	slash_command,
+	guild_only,
+	required_permissions = "MANAGE_GUILD"
+)]
+pub async fn merge(
+	ctx: Context<'_>,
+	#[description = "The alt account to merge"] alt: User,
+	#[description = "The account to merge it into"] main: User,
+	#[description = "Why the accounts are being merged"] reason: Option<String>,
+) -> Result<(), Error>
+{
+	if alt.id == main.id
+	{
+		ctx.reply_error("An account can't be merged into itself!")
+			.await?;
+		return Ok(());
+	}
+
+	let guild_id = ctx.guild_id().expect_guild_only();
+	let rank_changes = {
+		let mut data = ctx.data().acquire_lock().await;
+		let guild_data = data.guild_data_mut(guild_id);
+		let (merged, rank_changes) = RankChanges::track(guild_data, |guild_data| {
+			merge_accounts(guild_data, alt.id, main.id)
+		});
+		if merged
+		{
+			guild_data.rating_history_mut().merge(alt.id, main.id);
+			guild_data.audit_log_mut().record(
+				ctx.author().id,
+				AuditAction::MergeAccounts {
+					alt: alt.id,
+					main: main.id,
+				},
+				reason,
+			);
+		}
+		merged.then_some(rank_changes)
+	};
+
+	let Some(rank_changes) = rank_changes
+	else
+	{
+		ctx.reply_error(format!("{} isn't on the leaderboard!", alt.mention()))
+			.await?;
+		return Ok(());
+	};
+
+	ctx.send(
+		CreateReply::default()
+			.embed(
+				CreateEmbed::new()
+					.title("Accounts Merged")
+					.description(format!(
+						"{}'s score and matches now belong to {}",
+						alt.mention(),
+						main.mention()
+					))
+					.color(crate::DEFAULT_COLOR),
+			)
+			.ephemeral(true)
+			.allowed_mentions(CreateAllowedMentions::new()),
+	)
+	.await?;
+
+	rank_changes.apply(ctx, guild_id, &[alt.id, main.id]).await;
+
+	Ok(())
+}
+
+/// Take everyone who has left the server off of the Rock, Paper, Scissors leaderboard
+#[poise::command(
+	prefix_command,
+	slash_command,
+	guild_only,
+	required_permissions = "MANAGE_GUILD"
+)]
+pub async fn prune(
+	ctx: Context<'_>,
+	#[description = "Why the leaderboard is being pruned"] reason: Option<String>,
+) -> Result<(), Error>
+{
+	ctx.defer_ephemeral().await?;
+
+	let guild_id = ctx.guild_id().expect_guild_only();
+	let members = guild_id
+		.members_iter(ctx.http())
+		.map_ok(|member| member.user.id)
+		.try_collect::<HashSet<_>>()
+		.await?;
+
+	let (departed, rank_changes) = {
+		let mut data = ctx.data().acquire_lock().await;
+		let guild_data = data.guild_data_mut(guild_id);
+		let departed = guild_data
+			.leaderboard()
+			.players()
+			.filter(|player| !members.contains(player))
+			.collect::<Vec<_>>();
+
+		let ((), rank_changes) = RankChanges::track(guild_data, |guild_data| {
+			for player in &departed
+			{
+				guild_data.leaderboard_mut().remove(*player);
+				guild_data.rating_history_mut().remove(*player);
+			}
+		});
+		if !departed.is_empty()
+		{
+			guild_data.audit_log_mut().record(
+				ctx.author().id,
+				AuditAction::PruneDeparted {
+					players: departed.clone(),
+				},
+				reason,
+			);
+		}
+		(departed, rank_changes)
+	};
+
+	let description = if departed.is_empty()
+	{
+		String::from("Everyone on the leaderboard is still in the server")
+	}
+	else
+	{
+		let mut description = format!("Removed {} players:\n", departed.len());
+		for player in &departed
+		{
+			let _ = writeln!(description, "{}", player.mention());
+		}
+		description
+	};
+
+	ctx.send(
+		CreateReply::default()
+			.embed(
+				CreateEmbed::new()
+					.title("Leaderboard Pruned")
+					.description(description)
+					.color(crate::DEFAULT_COLOR),
+			)
+			.ephemeral(true)
+			.allowed_mentions(CreateAllowedMentions::new()),
+	)
+	.await?;
+
+	rank_changes.apply(ctx, guild_id, &[]).await;
+
+	Ok(())
+}
+
+/// Takes `player` off the leaderboard after they leave, if the server has auto-pruning turned on.
+/// The removal is logged under the bot's name so moderators can still see where they went
+pub async fn remove_departed(
+	ctx: &serenity::Context,
+	data: &Data,
+	guild_id: GuildId,
+	player: UserId,
+) -> Result<(), Error>
+{
+	let rank_changes = {
+		let mut data = data.acquire_lock().await;
+		if !data
+			.guild_data(guild_id)
+			.is_some_and(|dat| dat.rps_settings().departed_players() == DepartedPlayers::Removed)
+		{
+			return Ok(());
+		}
+
+		let guild_data = data.guild_data_mut(guild_id);
+		let (removed, rank_changes) = RankChanges::track(guild_data, |guild_data| {
+			guild_data.leaderboard_mut().remove(player).is_some()
+		});
+		if !removed
+		{
+			return Ok(());
+		}
+
+		guild_data.rating_history_mut().remove(player);
+		guild_data.audit_log_mut().record(
+			ctx.cache.current_user().id,
+			AuditAction::PruneDeparted {
+				players: vec![player],
+			},
+			Some(String::from("Left the server")),
+		);
+		rank_changes
+	};
+
+	rank_changes.apply(ctx, guild_id, &[]).await;
+
+	Ok(())
+}
+
+/// Show the latest changes moderators have made to the Rock, Paper, Scissors records
+#[poise::command(
+	prefix_command,
+	slash_command,
+	guild_only,
+	required_permissions = "MANAGE_GUILD"
+)]
+pub async fn audit(ctx: Context<'_>) -> Result<(), Error>
+{
+	let audit_list = {
+		let data = ctx.data().acquire_lock().await;
+		let mut audit_list = String::new();
+		if let Some(guild_data) = data.guild_data(ctx.guild_id().expect_guild_only())
+		{
+			for entry in guild_data
+				.audit_log()
+				.entries()
+				.iter()
+				.rev()
+				.take(AUDIT_LIST_LENGTH)
+			{
+				let _ = write!(
+					audit_list,
+					"<t:{}:R> {}: {}",
+					entry.at.unix_timestamp(),
+					entry.moderator.mention(),
+					entry.action
+				);
+				let _ = match &entry.reason
+				{
+					Some(reason) => writeln!(audit_list, "\n-# {reason}"),
+					None => writeln!(audit_list),
+				};
+			}
+		}
+		audit_list
+	};
+
+	ctx.send(
+		CreateReply::default()
+			.embed(
+				CreateEmbed::new()
+					.title("Audit Log")
+					.description(if audit_list.is_empty()
+					{
+						String::from("Nobody has changed the records yet")
+					}
+					else
+					{
+						audit_list
+					})
+					.color(crate::DEFAULT_COLOR),
+			)
+			.ephemeral(true)
+			.allowed_mentions(CreateAllowedMentions::new()),
+	)
+	.await?;
+
+	Ok(())
+}
+
+/// Sets one part of `player`'s score to whatever `new_value` makes of its current value
+async fn change_stat(
+	ctx: Context<'_>,
+	player: &User,
+	stat: ScoreStat,
+	reason: String,
+	new_value: impl FnOnce(i32) -> i32,
+) -> Result<(), Error>
+{
+	let guild_id = ctx.guild_id().expect_guild_only();
+
+	let change = set_stat(
+		ctx.data().acquire_lock().await.guild_data_mut(guild_id),
+		ctx.author().id,
+		player,
+		stat,
+		reason.clone(),
+		new_value,
+	);
+
+	let (description, rank_changes) = match change
+	{
+		Ok(change) => change,
+		Err(error) =>
+		{
+			ctx.reply_error(error).await?;
+			return Ok(());
+		}
+	};
+
+	ctx.send(
+		CreateReply::default()
+			.embed(
+				CreateEmbed::new()
+					.title("Score Changed")
+					.description(description)
+					.field("Reason", reason, false)
+					.color(crate::DEFAULT_COLOR),
+			)
+			.ephemeral(true)
+			.allowed_mentions(CreateAllowedMentions::new()),
+	)
+	.await?;
+
+	rank_changes.apply(ctx, guild_id, &[player.id]).await;
+
+	Ok(())
+}
+
+/// Sets one part of `player`'s score, recording the change in the audit log. Returns a
+/// description of the change and how rank roles changed, or why the change can't be made
+fn set_stat(
+	guild_data: &mut GuildData,
+	moderator: UserId,
+	player: &User,
+	stat: ScoreStat,
+	reason: String,
+	new_value: impl FnOnce(i32) -> i32,
+) -> Result<(String, RankChanges), String>
+{
+	let Some(old) = guild_data
+		.leaderboard()
+		.score(player.id)
+		.map(|score| score.stat(stat))
+	else
+	{
+		return Err(format!("{} isn't on the leaderboard!", player.mention()));
+	};
+
+	let new = new_value(old);
+	if stat != ScoreStat::Elo && new < 0
+	{
+		return Err(format!("{} can't have negative {stat}!", player.mention()));
+	}
+
+	let ((), rank_changes) = RankChanges::track(guild_data, |guild_data| {
+		guild_data
+			.leaderboard_mut()
+			.score_mut(player.id)
+			.set_stat(stat, new);
+	});
+	let action = AuditAction::SetStat {
+		player: player.id,
+		stat,
+		old,
+		new,
+	};
+	let description = action.to_string();
+	guild_data
+		.audit_log_mut()
+		.record(moderator, action, Some(reason));
+
+	Ok((description, rank_changes))
+}
+
+/// Takes back everything match `id` did to the server's leaderboard and leaves it out of
+/// everyone's stats, returning the match and how rank roles changed, or why it can't be voided.
+/// A pairing from a free-for-all or team game can't be taken back on its own, so the whole game is
+/// voided along with it, returning all of its pairings. The global ladder is left alone, since a
+/// match's share of it isn't recorded
+fn void_match(
+	guild_data: &mut GuildData,
+	id: usize,
+) -> Result<(Vec<MatchRecord>, RankChanges), String>
+{
+	let record = guild_data.match_history().get(id).cloned();
+
+	match record
+	{
+		None => Err(format!("There's no match #{id}!")),
+		Some(record) if record.voided => Err(format!("Match #{id} was already voided!")),
+		Some(record)
+			if record.ranked
+				&& guild_data
+					.season_started_at()
+					.is_some_and(|started_at| record.played_at < started_at) =>
+		{
+			Err(format!(
+				"Match #{id} was played last season, so it can't be voided anymore!"
+			))
+		}
+		Some(_) =>
+		{
+			let ids = guild_data.match_history().game_of(id);
+			let records = ids
+				.iter()
+				.filter_map(|id| guild_data.match_history().get(*id).cloned())
+				.collect::<Vec<_>>();
+
+			let ((), rank_changes) = RankChanges::track(guild_data, |guild_data| {
+				let leaderboard = guild_data.leaderboard_mut();
+				for (player, outcome, rating_change) in game_results(&records)
+				{
+					// players who have been reset or removed since have nothing left to revert
+					if leaderboard.score(player).is_some()
+					{
+						leaderboard
+							.score_mut(player)
+							.revert_match(outcome, rating_change);
+					}
+				}
+			});
+
+			for id in ids
+			{
+				if let Some(voided) = guild_data.match_history_mut().get_mut(id)
+				{
+					voided.voided = true;
+					if let Some(flag) = &mut voided.flag
+					{
+						flag.status = FlagStatus::Voided;
+					}
+				}
+			}
+			Ok((records, rank_changes))
+		}
+	}
+}
+
+/// Folds `alt`'s score and matches into `main`'s, returning whether `alt` had a score to merge.
+/// Games the two accounts played against each other are voided first, since the merged account
+/// can't have played itself and would otherwise keep both the win and the loss from them
+fn merge_accounts(guild_data: &mut GuildData, alt: UserId, main: UserId) -> bool
+{
+	if guild_data.leaderboard().score(alt).is_none()
+	{
+		return false;
+	}
+
+	let head_to_head = guild_data
+		.match_history()
+		.matches()
+		.iter()
+		.enumerate()
+		.filter(|(_, record)| !record.voided && record.involves(alt) && record.involves(main))
+		.map(|(i, _)| i + 1)
+		.collect::<Vec<_>>();
+	for id in head_to_head
+	{
+		// games from last season were already wiped from the leaderboard, so there's nothing to
+		// take back, and the pairings from a game voided earlier in the loop already are
+		if void_match(guild_data, id).is_ok()
+		{
+			continue;
+		}
+		if let Some(record) = guild_data.match_history_mut().get_mut(id)
+		{
+			record.voided = true;
+		}
+	}
+
+	guild_data.leaderboard_mut().merge(alt, main);
+	guild_data.match_history_mut().replace_player(alt, main);
+	true
+}
+
+/// How a ranked game went for everyone in it, going by its pairings: whether they won, and how
+/// much their rating moved. A free-for-all or team game only counts as a win for players who won
+/// every one of their pairings, since that's who the leaderboard gave the win to
+fn game_results(records: &[MatchRecord]) -> Vec<(UserId, Outcome, i32)>
+{
+	let mut results = Vec::<(UserId, Outcome, i32)>::new();
+	for record in records
+	{
+		for player in [record.challenger, record.opponent]
+		{
+			let Some(change) = record.rating_change_of(player)
+			else
+			{
+				continue;
+			};
+
+			match results.iter_mut().find(|(id, _, _)| *id == player)
+			{
+				Some((_, outcome, total_change)) =>
+				{
+					*total_change += change;
+					if record.winner != player
+					{
+						*outcome = Outcome::Loss;
+					}
+				}
+				None => results.push((player, record.outcome_for(player), change)),
+			}
+		}
+	}
+	results
+}
+
+fn flag_string(id: usize, record: &MatchRecord) -> String
+{
+	let mut flag_string = format!(
+		"`#{id}` <t:{}:R> {} beat {}",
+		record.played_at.unix_timestamp(),
+		record.winner.mention(),
+		record.opponent_of(record.winner).mention()
+	);
+	if let Some(flag) = record.flag
+	{
+		let _ = write!(flag_string, "\n-# {}", flag.reason);
+		match flag.status
+		{
+			FlagStatus::Open => (),
+			FlagStatus::Dismissed => flag_string.push_str(" (dismissed)"),
+			FlagStatus::Voided => flag_string.push_str(" (voided)"),
+		}
+	}
+	flag_string
+}
+
+#[cfg(test)]
+mod tests
+{
+	use poise::serenity_prelude::{Timestamp, UserId};
+
+	use super::merge_accounts;
+	use crate::data::{GuildData, MatchRecord, Outcome};
+
+	const MAIN: UserId = UserId::new(1);
+	const ALT: UserId = UserId::new(2);
+	const OTHER: UserId = UserId::new(3);
+
+	/// Records a ranked one on one match, updating both players' scores
+	fn play(guild_data: &mut GuildData, winner: UserId, loser: UserId)
+	{
+		let leaderboard = guild_data.leaderboard_mut();
+		let (winner_elo, loser_elo) = (
+			leaderboard.score_mut(winner).elo,
+			leaderboard.score_mut(loser).elo,
+		);
+		leaderboard.score_mut(winner).increment_wins();
+		let new_winner_elo = leaderboard
+			.score_mut(winner)
+			.update_elo(loser_elo, Outcome::Win);
+		leaderboard.score_mut(loser).increment_losses();
+		let new_loser_elo = leaderboard
+			.score_mut(loser)
+			.update_elo(winner_elo, Outcome::Loss);
+
+		guild_data.match_history_mut().record(MatchRecord {
+			challenger: winner,
+			opponent: loser,
+			winner,
+			forfeited: false,
+			ranked: true,
+			played_at: Timestamp::now(),
+			rounds: Vec::new(),
+			rating_changes: Some((new_winner_elo - winner_elo, new_loser_elo - loser_elo)),
+			flag: None,
+			voided: false,
+			game_id: None,
+		});
+	}
+
+	#[test]
+	fn merging_voids_games_between_the_two_accounts()
+	{
+		let mut guild_data = GuildData::default();
+		play(&mut guild_data, MAIN, ALT);
+		play(&mut guild_data, ALT, OTHER);
+
+		assert!(merge_accounts(&mut guild_data, ALT, MAIN));
+
+		let main = guild_data.leaderboard().score(MAIN).unwrap();
+		assert_eq!((main.wins, main.losses), (1, 0));
+		assert!(guild_data.leaderboard().score(ALT).is_none());
+
+		let history = guild_data.match_history();
+		assert!(history.get(1).unwrap().voided);
+		let won = history.get(2).unwrap();
+		assert!(!won.voided);
+		assert_eq!((won.winner, won.opponent), (MAIN, OTHER));
+	}
+}
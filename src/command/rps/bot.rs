@@ -0,0 +1,58 @@
+use std::collections::HashMap;
+
+use rand::Rng;
+
+use super::model::{ChallengerOpponentPair, Selection};
+use crate::data::BotDifficulty;
+
+/// Picks the bot's selection for the next round. The bot always plays as the opponent, so only
+/// the challenger's past selections are used to try and predict what they'll do next
+pub fn choose_selection(
+	difficulty: BotDifficulty,
+	history: &[ChallengerOpponentPair<Selection>],
+) -> Selection
+{
+	let mut rng = rand::thread_rng();
+	let player_history = history
+		.iter()
+		.map(|round| round.challenger)
+		.collect::<Vec<_>>();
+
+	let prediction = match difficulty
+	{
+		BotDifficulty::Easy => None,
+		BotDifficulty::Normal if rng.gen_bool(0.6) => most_common(player_history.iter().copied()),
+		BotDifficulty::Hard if rng.gen_bool(0.8) => predict_next(&player_history)
+			.or_else(|| most_common(player_history.iter().copied())),
+		_ => None,
+	};
+
+	prediction.map_or_else(|| rng.gen(), Selection::counter)
+}
+
+/// Looks at what the player has previously thrown right after their most recent selection and
+/// guesses they'll do the same again
+fn predict_next(player_history: &[Selection]) -> Option<Selection>
+{
+	let last = *player_history.last()?;
+	most_common(
+		player_history
+			.windows(2)
+			.filter(|pair| pair[0] == last)
+			.map(|pair| pair[1]),
+	)
+}
+
+fn most_common(selections: impl Iterator<Item = Selection>) -> Option<Selection>
+{
+	let mut counts = HashMap::new();
+	for selection in selections
+	{
+		*counts.entry(selection).or_insert(0_u32) += 1;
+	}
+
+	counts
+		.into_iter()
+		.max_by_key(|(_, count)| *count)
+		.map(|(selection, _)| selection)
+}
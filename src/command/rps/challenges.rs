@@ -0,0 +1,245 @@
+use std::fmt::Write;
+
+use poise::{
+	serenity_prelude::{
+		ChannelId, CreateAllowedMentions, CreateEmbed, GuildId, Mentionable, MessageId, Timestamp,
+		UserId,
+	},
+	CreateReply,
+};
+
+use crate::{
+	command::ExpectGuildOnly,
+	data::PendingChallenge,
+	Context, Error, Reply,
+};
+
+/// Withdraw the Rock, Paper, Scissors challenge you've issued
+#[poise::command(slash_command, prefix_command, guild_only)]
+pub async fn cancel(ctx: Context<'_>) -> Result<(), Error>
+{
+	let withdrawn = ctx
+		.data()
+		.acquire_matchmaking_lock()
+		.await
+		.activity_mut(ctx.guild_id().expect_guild_only())
+		.withdraw(ctx.author().id);
+
+	let Some(challenge) = withdrawn
+	else
+	{
+		ctx.reply_error("You don't have an open challenge!").await?;
+		return Ok(());
+	};
+
+	ctx.send(
+		CreateReply::default()
+			.embed(
+				CreateEmbed::new()
+					.title("Challenge withdrawn")
+					.description(format!(
+						"Your {} has been withdrawn",
+						challenge_string(&challenge, ctx.guild_id())
+					))
+					.color(crate::DEFAULT_COLOR),
+			)
+			.reply(true)
+			.allowed_mentions(CreateAllowedMentions::new()),
+	)
+	.await?;
+
+	Ok(())
+}
+
+/// List the open Rock, Paper, Scissors challenges involving you
+#[poise::command(slash_command, prefix_command, guild_only)]
+pub async fn pending(ctx: Context<'_>) -> Result<(), Error>
+{
+	let guild_id = ctx.guild_id().expect_guild_only();
+	let user = ctx.author().id;
+
+	let (challenges, game_channel) = {
+		let mut matchmaking = ctx.data().acquire_matchmaking_lock().await;
+		let activity = matchmaking.activity_mut(guild_id);
+		(activity.challenges().to_vec(), activity.game_of(user))
+	};
+
+	let mut outgoing = String::new();
+	let mut incoming = String::new();
+	let mut open = String::new();
+	for challenge in &challenges
+	{
+		let list = if challenge.challenger == user
+		{
+			&mut outgoing
+		}
+		else if challenge.involves(user)
+		{
+			&mut incoming
+		}
+		else if challenge.opponent.is_none()
+		{
+			&mut open
+		}
+		else
+		{
+			continue;
+		};
+		let _ = writeln!(list, "{}", challenge_string(challenge, Some(guild_id)));
+	}
+
+	let mut embed = CreateEmbed::new()
+		.title("Pending Challenges")
+		.description(game_channel.map_or_else(
+			|| String::from("You aren't in a game right now"),
+			|channel| format!("You're in a game in {}", channel.mention()),
+		))
+		.color(crate::DEFAULT_COLOR);
+	for (name, list) in [
+		("Your challenge", outgoing),
+		("Challenges for you", incoming),
+		("Open challenges", open),
+	]
+	{
+		if !list.is_empty()
+		{
+			embed = embed.field(name, list, false);
+		}
+	}
+
+	ctx.send(
+		CreateReply::default()
+			.embed(embed)
+			.ephemeral(true)
+			.allowed_mentions(CreateAllowedMentions::new()),
+	)
+	.await?;
+
+	Ok(())
+}
+
+fn challenge_string(challenge: &PendingChallenge, guild_id: Option<GuildId>) -> String
+{
+	format!(
+		"[challenge]({}) from {} to {}, issued <t:{}:R>",
+		challenge.message.link(challenge.channel, guild_id),
+		challenge.challenger.mention(),
+		challenge
+			.opponent
+			.map_or_else(|| String::from("anyone"), |opponent| opponent.mention().to_string()),
+		challenge.issued_at.unix_timestamp()
+	)
+}
+
+/// Replies with an error if any of `players` is already in a game or the matchmaking queue.
+/// Returns whether they're all free to play
+pub async fn check_available(ctx: Context<'_>, players: &[UserId]) -> Result<bool, Error>
+{
+	let busy = ctx
+		.data()
+		.acquire_matchmaking_lock()
+		.await
+		.first_busy(ctx.guild_id().expect_guild_only(), players);
+
+	if let Some(player) = busy
+	{
+		ctx.reply_error(busy_message(ctx, player, "")).await?;
+	}
+	Ok(busy.is_none())
+}
+
+/// Whether `player` is in a game or the matchmaking queue
+pub async fn is_busy(ctx: Context<'_>, player: UserId) -> bool
+{
+	ctx.data()
+		.acquire_matchmaking_lock()
+		.await
+		.first_busy(ctx.guild_id().expect_guild_only(), &[player])
+		.is_some()
+}
+
+/// Marks `players` as being in a game in `channel`, checking they're all free under the same
+/// lock so nobody can end up in two games at once. If anyone is busy, nobody is marked and the
+/// game is called off with an error naming them. Returns whether the game can start
+pub async fn try_start_playing(
+	ctx: Context<'_>,
+	players: &[UserId],
+	channel: ChannelId,
+) -> Result<bool, Error>
+{
+	let started = ctx
+		.data()
+		.acquire_matchmaking_lock()
+		.await
+		.try_start_playing(ctx.guild_id().expect_guild_only(), players, channel);
+
+	if let Err(player) = started
+	{
+		ctx.reply_error(busy_message(ctx, player, ", so the game has been called off"))
+			.await?;
+	}
+	Ok(started.is_ok())
+}
+
+fn busy_message(ctx: Context<'_>, player: UserId, consequence: &str) -> String
+{
+	if player == ctx.author().id
+	{
+		format!("You're already in a game or the matchmaking queue{consequence}!")
+	}
+	else
+	{
+		format!(
+			"{} is already in a game or the matchmaking queue{consequence}!",
+			player.mention()
+		)
+	}
+}
+
+pub async fn issue(
+	ctx: Context<'_>,
+	opponent: Option<UserId>,
+	channel: ChannelId,
+	message: MessageId,
+)
+{
+	ctx.data()
+		.acquire_matchmaking_lock()
+		.await
+		.activity_mut(ctx.guild_id().expect_guild_only())
+		.issue(PendingChallenge {
+			challenger: ctx.author().id,
+			opponent,
+			channel,
+			message,
+			issued_at: Timestamp::now(),
+		});
+}
+
+/// Whether the challenge on `message` is still waiting on a response, rather than withdrawn
+pub async fn is_pending(ctx: Context<'_>, message: MessageId) -> bool
+{
+	ctx.data()
+		.acquire_matchmaking_lock()
+		.await
+		.activity_mut(ctx.guild_id().expect_guild_only())
+		.is_pending(message)
+}
+
+pub async fn resolve(ctx: Context<'_>, message: MessageId)
+{
+	ctx.data()
+		.acquire_matchmaking_lock()
+		.await
+		.activity_mut(ctx.guild_id().expect_guild_only())
+		.resolve(message);
+}
+
+pub async fn stop_playing(ctx: Context<'_>, players: &[UserId])
+{
+	ctx.data()
+		.acquire_matchmaking_lock()
+		.await
+		.activity_mut(ctx.guild_id().expect_guild_only())
+		.stop_playing(players);
+}
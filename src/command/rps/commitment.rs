@@ -0,0 +1,138 @@
+use std::fmt::Write;
+
+use poise::{
+	serenity_prelude::{CreateAllowedMentions, CreateEmbed},
+	CreateReply,
+};
+use rand::Rng;
+use sha2::{Digest, Sha256};
+
+use crate::{data::Selection, Context, Error};
+
+/// The bot's selection for a round, locked in ahead of time. The hash is shown before the player
+/// picks and the selection and salt afterwards, so anyone can check the bot didn't change its
+/// mind after seeing what the player chose
+pub struct Commitment
+{
+	selection: Selection,
+	salt: String,
+}
+impl Commitment
+{
+	pub fn new(selection: Selection) -> Self
+	{
+		let salt = rand::thread_rng()
+			.gen::<[u8; 16]>()
+			.iter()
+			.fold(String::new(), |mut salt, byte| {
+				let _ = write!(salt, "{byte:02x}");
+				salt
+			});
+
+		Self { selection, salt }
+	}
+
+	pub fn hash(&self) -> String
+	{
+		commitment_hash(self.selection, &self.salt)
+	}
+
+	pub fn reveal(&self) -> String
+	{
+		format!(
+			"Selection: `{}`\nSalt: `{}`\nHash: `{}`\nCheck it with `/rps verify`",
+			self.selection.as_str(),
+			self.salt,
+			self.hash()
+		)
+	}
+}
+
+/// The SHA-256 hash of `<salt>:<selection>`, as lowercase hex
+pub fn commitment_hash(selection: Selection, salt: &str) -> String
+{
+	Sha256::digest(format!("{salt}:{}", selection.as_str()))
+		.iter()
+		.fold(String::new(), |mut hash, byte| {
+			let _ = write!(hash, "{byte:02x}");
+			hash
+		})
+}
+
+/// Check that the bot really committed to its selection before you picked yours
+#[poise::command(slash_command, prefix_command)]
+pub async fn verify(
+	ctx: Context<'_>,
+	#[description = "The selection the bot revealed"] selection: Selection,
+	#[description = "The salt the bot revealed"] salt: String,
+	#[description = "The hash the bot posted before you picked"] hash: String,
+) -> Result<(), Error>
+{
+	let expected = commitment_hash(selection, salt.trim());
+	let matches = expected.eq_ignore_ascii_case(hash.trim());
+
+	ctx.send(
+		CreateReply::default()
+			.embed(
+				CreateEmbed::new()
+					.title(if matches { "Verified!" } else { "Mismatch!" })
+					.description(if matches
+					{
+						format!(
+							"The hash matches. The bot committed to **{selection}** before you \
+							 picked"
+						)
+					}
+					else
+					{
+						String::from(
+							"The hash doesn't match that selection and salt. Make sure they were \
+							 copied exactly",
+						)
+					})
+					.field("Computed hash", format!("`{expected}`"), false)
+					.color(if matches { crate::DEFAULT_COLOR } else { crate::ERROR_COLOR }),
+			)
+			.reply(true)
+			.allowed_mentions(CreateAllowedMentions::new()),
+	)
+	.await?;
+
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests
+{
+	use super::{commitment_hash, Commitment};
+	use crate::data::Selection;
+
+	#[test]
+	fn hash_is_sha256_of_salt_and_selection()
+	{
+		assert_eq!(
+			commitment_hash(Selection::Rock, "abc"),
+			"c574784082c466cb799331d03de11787eb8a8a0e1bd28c3cb4aa7c6de05e369d"
+		);
+	}
+
+	#[test]
+	fn hash_depends_on_selection_and_salt()
+	{
+		let hash = commitment_hash(Selection::Rock, "abc");
+		assert_ne!(hash, commitment_hash(Selection::Paper, "abc"));
+		assert_ne!(hash, commitment_hash(Selection::Rock, "abd"));
+	}
+
+	#[test]
+	fn revealed_commitment_verifies()
+	{
+		let commitment = Commitment::new(Selection::Scissors);
+		assert_eq!(commitment.salt.len(), 32);
+		assert_eq!(
+			commitment.hash(),
+			commitment_hash(Selection::Scissors, &commitment.salt)
+		);
+		assert!(commitment.reveal().contains(&commitment.hash()));
+	}
+}
@@ -0,0 +1,1185 @@
+use std::{collections::HashMap, fmt::Write, time::Duration};
+
+use poise::{
+	serenity_prelude::{
+		ButtonStyle, CreateActionRow, CreateAllowedMentions, CreateButton, CreateEmbed,
+		CreateInteractionResponse, CreateInteractionResponseMessage, CreateMessage, EditMessage,
+		GuildChannel, GuildId, Mentionable, Message, Timestamp, UserId,
+	},
+	ChoiceParameter, CreateReply,
+};
+
+use super::{
+	model::{self, ChallengerOpponentPair, Selection},
+	rank_roles::RankChanges,
+	scoreboard::{self, Board},
+	RecordedMatch,
+};
+use crate::{
+	command::ExpectGuildOnly,
+	data::{DataManager, Leaderboard, Outcome, RatingSystem},
+	Context, Error, Respond,
+};
+
+const MIN_PLAYERS: usize = 3;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, poise::ChoiceParameter)]
+pub enum FreeForAllMode
+{
+	/// everyone who throws a losing move is knocked out, until one player is left
+	#[default]
+	Elimination,
+	/// everyone who throws a winning move scores a point, until someone reaches the target
+	Points,
+}
+
+/// Start a free-for-all game of Rock, Paper, Scissors that anyone can join
+#[poise::command(slash_command, prefix_command, guild_only)]
+pub async fn ffa(
+	ctx: Context<'_>,
+	#[description = "How the winner is decided (default: Elimination)"]
+	mode: Option<FreeForAllMode>,
+	#[description = "Points needed to win in Points mode (default: 3)"]
+	#[min = 1]
+	points: Option<u32>,
+	#[description = "Whether the match affects ratings (default: true)"] ranked: Option<bool>,
+	#[description = "How many players can join (default: 6)"]
+	#[min = 3]
+	#[max = 10]
+	max_players: Option<u32>,
+) -> Result<(), Error>
+{
+	let mode = mode.unwrap_or_default();
+	let points = points.unwrap_or(3);
+	let ranked = ranked.unwrap_or(true);
+	let max_players = max_players.map_or(6, |max| max as usize);
+	if !super::challenges::check_available(ctx, &[ctx.author().id]).await?
+	{
+		return Ok(());
+	}
+
+	let settings = super::rps_settings(ctx).await;
+	let Some(players) = run_lobby(ctx, mode, ranked, max_players, settings.accept_timeout()).await?
+	else
+	{
+		return Ok(());
+	};
+
+	// anyone could have started another game while waiting in the lobby
+	let channel = ctx.guild_channel().await.expect_guild_only();
+	if !super::challenges::try_start_playing(ctx, &players, channel.id).await?
+	{
+		return Ok(());
+	}
+	let mut game = FreeForAll::start(players.clone(), mode, points);
+	let finished = play_rounds(ctx, &mut game, &channel, settings.round_timeout()).await;
+	super::challenges::stop_playing(ctx, &players).await;
+	if !finished?
+	{
+		return Ok(());
+	}
+
+	let placements = game.placements();
+	let players = placements.iter().map(|(player, _)| *player).collect::<Vec<_>>();
+	let guild_id = ctx.guild_id().expect_guild_only();
+	let RecordedMatch {
+		rating_changes,
+		rank_changes,
+		flags,
+	} = record_game(
+		&mut *ctx.data().acquire_lock().await,
+		guild_id,
+		&game,
+		&placements,
+		ranked,
+	);
+
+	let mut embed = results_embed(&placements, rating_changes.as_deref());
+	if !ranked
+	{
+		embed = embed.footer(super::casual_footer());
+	}
+	channel
+		.send_message(
+			ctx,
+			CreateMessage::new()
+				.embed(embed)
+				.allowed_mentions(CreateAllowedMentions::new()),
+		)
+		.await?;
+
+	if let Some(rank_changes) = rank_changes
+	{
+		rank_changes.apply(ctx, guild_id, &players).await;
+	}
+	for flag in flags
+	{
+		super::admin::report_flag(ctx, guild_id, &flag).await?;
+	}
+
+	Ok(())
+}
+
+/// Saves a finished game to the server's history as a pairing between every two players who
+/// placed differently, updating the leaderboards if it was ranked. Rating changes are given as
+/// everyone's old and new ratings, in the same order as `placements`
+fn record_game(
+	data: &mut DataManager,
+	guild_id: GuildId,
+	game: &FreeForAll,
+	placements: &[(UserId, u32)],
+	ranked: bool,
+) -> RecordedMatch<Vec<(i32, i32)>>
+{
+	let guild_data = data.guild_data_mut(guild_id);
+	let rating_system = guild_data.rps_settings().rating_system();
+	let counts_globally = ranked && guild_data.rps_settings().global_ladder_enabled();
+	// playing the same opponent over and over counts for less each time, just like it does in
+	// one on one matches
+	let now = Timestamp::now();
+	let weights = pairings(placements)
+		.flat_map(|(player, opponent)| {
+			let weight = guild_data
+				.match_history()
+				.repeat_weight(player, opponent, now);
+			[((player, opponent), weight), ((opponent, player), weight)]
+		})
+		.collect::<Pairings<_>>();
+
+	let (rated, rank_changes) = ranked
+		.then(|| {
+			RankChanges::track(guild_data, |guild_data| {
+				update_leaderboard(
+					guild_data.leaderboard_mut(),
+					placements,
+					rating_system,
+					&weights,
+				)
+			})
+		})
+		.unzip();
+	let (rating_changes, pairing_changes) = rated.unzip();
+	if let Some(rating_changes) = &rating_changes
+	{
+		guild_data.rating_history_mut().record(
+			placements
+				.iter()
+				.zip(rating_changes)
+				.map(|((player, _), (_, new_rating))| (*player, *new_rating)),
+		);
+	}
+
+	let records = pairings(placements).map(|(winner, loser)| {
+		super::pairing_record(
+			ChallengerOpponentPair::new(winner, loser),
+			winner,
+			game.forfeited(loser),
+			game.pairing_rounds(winner, loser),
+			pairing_changes
+				.as_ref()
+				.map(|changes| (changes[&(winner, loser)], changes[&(loser, winner)])),
+		)
+	});
+	let flags = super::record_pairings(guild_data, records);
+
+	// the global ladder always uses ELO, since servers can each pick their own system
+	if counts_globally
+	{
+		update_leaderboard(
+			data.global_leaderboard_mut(),
+			placements,
+			RatingSystem::Elo,
+			&weights,
+		);
+	}
+
+	RecordedMatch {
+		rating_changes,
+		rank_changes,
+		flags,
+	}
+}
+
+/// Posts a lobby for players to join and waits for the host to start it. Returns everyone who
+/// joined, or `None` if the lobby was cancelled or never filled up enough to start
+async fn run_lobby(
+	ctx: Context<'_>,
+	mode: FreeForAllMode,
+	ranked: bool,
+	max_players: usize,
+	accept_timeout: Duration,
+) -> Result<Option<Vec<UserId>>, Error>
+{
+	let host = ctx.author().id;
+	let mut players = vec![host];
+
+	let mut lobby_message = ctx
+		.send(
+			CreateReply::default()
+				.embed(lobby_embed(&players, mode, ranked, max_players, accept_timeout))
+				.components(lobby_buttons())
+				.allowed_mentions(CreateAllowedMentions::new())
+				.reply(true),
+		)
+		.await?
+		.into_message()
+		.await?;
+
+	let started = loop
+	{
+		let Some(interaction) = lobby_message
+			.await_component_interaction(ctx)
+			.timeout(accept_timeout)
+			.await
+		else
+		{
+			lobby_message
+				.edit(
+					ctx,
+					EditMessage::new()
+						.embed(lobby_closed_embed(
+							"Nobody started the game in time. The lobby has been closed",
+						))
+						.components(vec![]),
+				)
+				.await?;
+			break false;
+		};
+
+		let user = interaction.user.id;
+		let error = match interaction.data.custom_id.as_str()
+		{
+			"rps-ffa-join" if players.contains(&user) => Some("You're already in the lobby!"),
+			"rps-ffa-join" if interaction.user.bot => Some("Bots can't join the lobby!"),
+			"rps-ffa-join" if super::challenges::is_busy(ctx, user).await =>
+			{
+				Some("You're already in a game or the matchmaking queue!")
+			}
+			"rps-ffa-join" =>
+			{
+				players.push(user);
+				None
+			}
+			"rps-ffa-leave" if !players.contains(&user) => Some("You aren't in the lobby!"),
+			"rps-ffa-leave" if user == host =>
+			{
+				interaction
+					.create_response(
+						ctx,
+						lobby_update(
+							lobby_closed_embed("The host left, so the lobby was closed"),
+							vec![],
+						),
+					)
+					.await?;
+				break false;
+			}
+			"rps-ffa-leave" =>
+			{
+				players.retain(|player| *player != user);
+				None
+			}
+			"rps-ffa-start" if user != host => Some("Only the host can start the game!"),
+			"rps-ffa-start" if players.len() < MIN_PLAYERS => Some("Not enough players yet!"),
+			"rps-ffa-start" => None,
+			_ => continue,
+		};
+
+		if let Some(error) = error
+		{
+			interaction
+				.respond_ephemeral(ctx, crate::error_embed(error))
+				.await?;
+			continue;
+		}
+
+		// the lobby starts on its own once it's full, so nobody is left waiting on the host
+		if interaction.data.custom_id == "rps-ffa-start" || players.len() >= max_players
+		{
+			interaction
+				.create_response(ctx, lobby_update(started_embed(&players), vec![]))
+				.await?;
+			break true;
+		}
+
+		interaction
+			.create_response(
+				ctx,
+				lobby_update(
+					lobby_embed(&players, mode, ranked, max_players, accept_timeout),
+					lobby_buttons(),
+				),
+			)
+			.await?;
+	};
+
+	Ok(started.then_some(players))
+}
+
+fn lobby_embed(
+	players: &[UserId],
+	mode: FreeForAllMode,
+	ranked: bool,
+	max_players: usize,
+	accept_timeout: Duration,
+) -> CreateEmbed
+{
+	CreateEmbed::new()
+		.title("Free-for-all lobby")
+		.description(format!(
+			"{} is hosting a{} **{}** free-for-all! Join in, and the host can start once there \
+			 are at least {MIN_PLAYERS} players",
+			players[0].mention(),
+			if ranked { " ranked" } else { " casual" },
+			mode.name()
+		))
+		.field(
+			format!("Players ({}/{max_players})", players.len()),
+			super::mention_list(players),
+			false,
+		)
+		.color(crate::DEFAULT_COLOR)
+		.footer(super::timeout_footer(accept_timeout))
+}
+
+fn started_embed(players: &[UserId]) -> CreateEmbed
+{
+	CreateEmbed::new()
+		.title("Free-for-all started!")
+		.description(super::mention_list(players))
+		.color(crate::DEFAULT_COLOR)
+}
+
+fn lobby_closed_embed(reason: &str) -> CreateEmbed
+{
+	CreateEmbed::new()
+		.title("Lobby closed")
+		.description(reason)
+		.color(crate::DEFAULT_COLOR)
+}
+
+fn lobby_buttons() -> Vec<CreateActionRow>
+{
+	vec![CreateActionRow::Buttons(vec![
+		CreateButton::new("rps-ffa-join")
+			.emoji('\u{2795}')
+			.label("Join")
+			.style(ButtonStyle::Success),
+		CreateButton::new("rps-ffa-leave")
+			.emoji('\u{1f6aa}')
+			.label("Leave")
+			.style(ButtonStyle::Secondary),
+		CreateButton::new("rps-ffa-start")
+			.emoji('\u{25b6}')
+			.label("Start")
+			.style(ButtonStyle::Primary),
+	])]
+}
+
+fn lobby_update(embed: CreateEmbed, buttons: Vec<CreateActionRow>) -> CreateInteractionResponse
+{
+	CreateInteractionResponse::UpdateMessage(
+		CreateInteractionResponseMessage::new()
+			.embed(embed)
+			.components(buttons)
+			.allowed_mentions(CreateAllowedMentions::new()),
+	)
+}
+
+/// Plays rounds until someone wins, all on one message that's edited as the game goes on.
+/// Returns `false` if the game was abandoned
+async fn play_rounds(
+	ctx: Context<'_>,
+	game: &mut FreeForAll,
+	channel: &GuildChannel,
+	round_timeout: Duration,
+) -> Result<bool, Error>
+{
+	let mut board = FreeForAllBoard::new(round_timeout);
+	let mut game_message = channel
+		.send_message(
+			ctx,
+			board
+				.message(game)
+				.allowed_mentions(CreateAllowedMentions::new()),
+		)
+		.await?;
+
+	loop
+	{
+		let played = await_selections(ctx, &game_message, game, &mut board).await?;
+		if let Some(winner) = game.winner()
+		{
+			board.conclude(&format!("{} wins the game!", winner.mention()));
+		}
+		game_message.edit(ctx, board.edit(game)).await?;
+
+		if !played
+		{
+			return Ok(false);
+		}
+		if game.winner().is_some()
+		{
+			return Ok(true);
+		}
+	}
+}
+
+/// Collects everyone's selection for the round, then plays it. Anyone who forfeits or doesn't
+/// select in time is knocked out. Returns `false` if the game was abandoned
+async fn await_selections(
+	ctx: Context<'_>,
+	game_message: &Message,
+	game: &mut FreeForAll,
+	board: &mut FreeForAllBoard,
+) -> Result<bool, Error>
+{
+	loop
+	{
+		// everyone else forfeiting decides the game without playing the round
+		if game.winner().is_some()
+		{
+			return Ok(true);
+		}
+		if game.all_selected()
+		{
+			let round = game.play_round();
+			board.status = round_summary(&round, game);
+			return Ok(true);
+		}
+
+		let Some(interaction) = game_message
+			.await_component_interaction(ctx)
+			.timeout(board.round_timeout)
+			.await
+		else
+		{
+			return Ok(time_up(game, board));
+		};
+
+		let user = interaction.user.id;
+		let Some(player) = game.active_player_mut(user)
+		else
+		{
+			interaction
+				.respond_ephemeral(
+					ctx,
+					crate::error_embed("Only players still in the game are allowed to respond!"),
+				)
+				.await?;
+			continue;
+		};
+
+		if interaction.data.custom_id == "rps-forfeit"
+		{
+			game.knock_out(user);
+			board.status = format!("{} forfeits and is out!", user.mention());
+			// the message is edited once the game is decided or the round is played
+			interaction
+				.create_response(
+					ctx,
+					if game.winner().is_some() || game.all_selected()
+					{
+						CreateInteractionResponse::Acknowledge
+					}
+					else
+					{
+						board.update(game)
+					},
+				)
+				.await?;
+			continue;
+		}
+
+		if player.selection.is_some()
+		{
+			interaction
+				.respond_ephemeral(ctx, crate::error_embed("You have already selected!"))
+				.await?;
+			continue;
+		}
+
+		let Some(selection) = model::selection_from_button(&interaction.data.custom_id)
+		else
+		{
+			continue;
+		};
+		player.selection = Some(selection);
+
+		let update = (!game.all_selected()).then(|| board.update(game));
+		super::confirm_selection(ctx, &interaction, update, selection).await?;
+	}
+}
+
+/// Knocks out everyone who didn't select in time, then plays the round with whoever did.
+/// Returns `false` if nobody did, abandoning the game
+fn time_up(game: &mut FreeForAll, board: &mut FreeForAllBoard) -> bool
+{
+	let stalling = game.knock_out_stalling();
+	if game.active_players().next().is_none()
+	{
+		board.conclude("Time's up! Nobody made a selection in time. The game has been abandoned");
+		return false;
+	}
+
+	board.status = format!(
+		"Time's up! {} didn't make a selection in time and {} out",
+		super::mention_list(&stalling),
+		if stalling.len() == 1 { "is" } else { "are" }
+	);
+	if game.winner().is_none()
+	{
+		let round = game.play_round();
+		board.status = format!("{}\n\n{}", board.status, round_summary(&round, game));
+	}
+	true
+}
+
+/// What everyone threw in the round and what came of it
+fn round_summary(round: &FreeForAllRound, game: &FreeForAll) -> String
+{
+	let mut summary = format!("**Round {}**\n", round.number);
+	for (player, selection) in &round.selections
+	{
+		let _ = writeln!(summary, "{} threw {selection}", player.mention());
+	}
+
+	let result = match round.winning_selection
+	{
+		None if round.selections.is_empty() => String::from("Nobody threw anything"),
+		None => String::from("It's a draw!"),
+		Some(winning_selection) => match game.mode
+		{
+			FreeForAllMode::Elimination => format!(
+				"**{winning_selection}** wins! {} {} out",
+				super::mention_list(&round.losers()),
+				if round.losers().len() == 1 { "is" } else { "are" }
+			),
+			FreeForAllMode::Points => format!(
+				"**{winning_selection}** wins! {} {} a point",
+				super::mention_list(&round.winners()),
+				if round.winners().len() == 1 { "scores" } else { "score" }
+			),
+		},
+	};
+	summary.push_str(&result);
+	summary
+}
+
+/// The board a free-for-all is played out on
+struct FreeForAllBoard
+{
+	round_timeout: Duration,
+	/// what happened last, shown above the standings
+	status: String,
+	concluded: bool,
+}
+impl FreeForAllBoard
+{
+	fn new(round_timeout: Duration) -> Self
+	{
+		Self {
+			round_timeout,
+			status: String::from("Pick rock, paper, or scissors!"),
+			concluded: false,
+		}
+	}
+
+	/// Ends the game with `result` under whatever happened last, disabling the buttons
+	fn conclude(&mut self, result: &str)
+	{
+		self.status = format!("{}\n\n**{result}**", self.status);
+		self.concluded = true;
+	}
+}
+impl Board<FreeForAll> for FreeForAllBoard
+{
+	fn embed(&self, game: &FreeForAll) -> CreateEmbed
+	{
+		let mut embed = CreateEmbed::new()
+			.title(if self.concluded
+			{
+				String::from("Game over")
+			}
+			else
+			{
+				format!("Round {}", game.round + 1)
+			})
+			.description(&self.status)
+			.color(crate::DEFAULT_COLOR)
+			.footer(scoreboard::round_timeout_footer(self.round_timeout));
+
+		match game.mode
+		{
+			FreeForAllMode::Elimination =>
+			{
+				embed = embed.field(
+					"Still standing",
+					super::mention_list(
+						&game.active_players().map(|player| player.id).collect::<Vec<_>>(),
+					),
+					false,
+				);
+			}
+			FreeForAllMode::Points =>
+			{
+				let mut standings = String::new();
+				for player in game.active_players()
+				{
+					let _ = writeln!(standings, "{}: {}", player.id.mention(), player.points);
+				}
+				embed =
+					embed.field(format!("Standings (first to {})", game.target), standings, false);
+			}
+		}
+
+		if !self.concluded
+		{
+			embed = embed.field(
+				"Waiting on",
+				scoreboard::waiting_on(
+					game.active_players()
+						.map(|player| (player.id, player.selection.is_some())),
+				),
+				false,
+			);
+		}
+
+		embed
+	}
+
+	fn concluded(&self) -> bool
+	{
+		self.concluded
+	}
+}
+
+fn results_embed(
+	placements: &[(UserId, u32)],
+	rating_changes: Option<&[(i32, i32)]>,
+) -> CreateEmbed
+{
+	let mut standings = String::new();
+	for (i, (player, placement)) in placements.iter().enumerate()
+	{
+		let _ = write!(standings, "{placement}. {}", player.mention());
+		if let Some((old_elo, new_elo)) = rating_changes.and_then(|changes| changes.get(i))
+		{
+			let _ = write!(standings, " ({old_elo} → {new_elo}, {:+})", new_elo - old_elo);
+		}
+		standings.push('\n');
+	}
+
+	CreateEmbed::new()
+		.title("Free-for-all over")
+		.description(format!("# {} wins!", placements[0].0.mention()))
+		.field("Placements", standings, false)
+		.color(crate::DEFAULT_COLOR)
+}
+
+/// Every pair of players who placed differently, as the better placed player then the other
+fn pairings(placements: &[(UserId, u32)]) -> impl Iterator<Item = (UserId, UserId)> + '_
+{
+	placements.iter().flat_map(move |(player, placement)| {
+		placements
+			.iter()
+			.filter(move |(_, other_placement)| other_placement > placement)
+			.map(move |(other, _)| (*player, *other))
+	})
+}
+
+/// something about each player's game against each other player, keyed by the player then the
+/// opponent
+type Pairings<T> = HashMap<(UserId, UserId), T>;
+
+/// Treats the free-for-all as a game against every other player, won against whoever placed lower
+/// and lost to whoever placed higher, rated all at once with each pairing scaled by its weight.
+/// Returns everyone's old and new ratings, in the same order as `placements`, and how much each
+/// pairing moved their ratings
+fn update_leaderboard(
+	leaderboard: &mut Leaderboard,
+	placements: &[(UserId, u32)],
+	rating_system: RatingSystem,
+	weights: &Pairings<f64>,
+) -> (Vec<(i32, i32)>, Pairings<i32>)
+{
+	let now = Timestamp::now();
+	let old_scores = placements
+		.iter()
+		.map(|(player, _)| leaderboard.score(*player).cloned().unwrap_or_default())
+		.collect::<Vec<_>>();
+	let mut pairing_changes = HashMap::new();
+
+	for (player, placement) in placements
+	{
+		let (opponents, opponent_scores): (Vec<_>, Vec<_>) = placements
+			.iter()
+			.zip(&old_scores)
+			.filter(|((_, other_placement), _)| other_placement != placement)
+			.map(|((other, other_placement), other_score)| {
+				(
+					*other,
+					(
+						other_score,
+						Outcome::from(placement < other_placement),
+						weights[&(*player, *other)],
+					),
+				)
+			})
+			.unzip();
+
+		let score = leaderboard.score_mut(*player);
+		let changes = score.update_rating_against_many(&opponent_scores, rating_system, now);
+		pairing_changes.extend(
+			opponents
+				.into_iter()
+				.zip(changes)
+				.map(|(opponent, change)| ((*player, opponent), change)),
+		);
+		if *placement == 1
+		{
+			score.increment_wins();
+		}
+		else
+		{
+			score.increment_losses();
+		}
+	}
+
+	let rating_changes = placements
+		.iter()
+		.zip(old_scores)
+		.map(|((player, _), old_score)| {
+			(
+				old_score.elo,
+				leaderboard
+					.score(*player)
+					.map_or(old_score.elo, |score| score.elo),
+			)
+		})
+		.collect();
+	(rating_changes, pairing_changes)
+}
+
+#[derive(Debug, Clone)]
+struct FreeForAllPlayer
+{
+	id: UserId,
+	selection: Option<Selection>,
+	points: u32,
+	/// the round the player was knocked out in, if they have been
+	out_in_round: Option<u32>,
+	/// whether the player forfeited or didn't select in time, rather than being eliminated
+	forfeited: bool,
+}
+
+/// A game of Rock, Paper, Scissors between any number of players, who all throw at once
+#[derive(Debug, Clone)]
+struct FreeForAll
+{
+	mode: FreeForAllMode,
+	/// the points needed to win, in points mode
+	target: u32,
+	players: Vec<FreeForAllPlayer>,
+	round: u32,
+	/// everyone's selection in every round played, for the players who threw one
+	history: Vec<Vec<(UserId, Selection)>>,
+}
+impl FreeForAll
+{
+	fn start(players: Vec<UserId>, mode: FreeForAllMode, target: u32) -> Self
+	{
+		Self {
+			mode,
+			target,
+			players: players
+				.into_iter()
+				.map(|id| FreeForAllPlayer {
+					id,
+					selection: None,
+					points: 0,
+					out_in_round: None,
+					forfeited: false,
+				})
+				.collect(),
+			round: 0,
+			history: Vec::new(),
+		}
+	}
+
+	fn active_players(&self) -> impl Iterator<Item = &FreeForAllPlayer>
+	{
+		self.players
+			.iter()
+			.filter(|player| player.out_in_round.is_none())
+	}
+
+	fn active_player_mut(&mut self, id: UserId) -> Option<&mut FreeForAllPlayer>
+	{
+		self.players
+			.iter_mut()
+			.find(|player| player.id == id && player.out_in_round.is_none())
+	}
+
+	fn all_selected(&self) -> bool
+	{
+		self.active_players()
+			.all(|player| player.selection.is_some())
+	}
+
+	/// Knocks out a player who forfeited or didn't select in time
+	fn knock_out(&mut self, id: UserId)
+	{
+		let round = self.round + 1;
+		if let Some(player) = self.active_player_mut(id)
+		{
+			player.selection = None;
+			player.out_in_round = Some(round);
+			player.forfeited = true;
+		}
+	}
+
+	fn forfeited(&self, id: UserId) -> bool
+	{
+		self.players
+			.iter()
+			.any(|player| player.id == id && player.forfeited)
+	}
+
+	/// Knocks out everyone still in who hasn't selected, returning who they were
+	fn knock_out_stalling(&mut self) -> Vec<UserId>
+	{
+		let stalling = self
+			.active_players()
+			.filter(|player| player.selection.is_none())
+			.map(|player| player.id)
+			.collect::<Vec<_>>();
+		for player in &stalling
+		{
+			self.knock_out(*player);
+		}
+		stalling
+	}
+
+	/// Resolves the round from everyone's selections. A round only has a winning selection if
+	/// exactly two different selections were thrown, so one selection or all three is a draw
+	fn play_round(&mut self) -> FreeForAllRound
+	{
+		self.round += 1;
+
+		let selections = self
+			.players
+			.iter_mut()
+			.filter(|player| player.out_in_round.is_none())
+			.filter_map(|player| Some((player.id, player.selection.take()?)))
+			.collect::<Vec<_>>();
+
+		let mut thrown = selections
+			.iter()
+			.map(|(_, selection)| *selection)
+			.collect::<Vec<_>>();
+		thrown.sort_unstable_by_key(|selection| selection.as_str());
+		thrown.dedup();
+
+		let winning_selection = match thrown[..]
+		{
+			[first, second] => Some(if first.counter() == second { second } else { first }),
+			_ => None,
+		};
+
+		self.history.push(selections.clone());
+		let round = FreeForAllRound {
+			number: self.round,
+			selections,
+			winning_selection,
+		};
+
+		let round_number = self.round;
+		let (losers, winners) = (round.losers(), round.winners());
+		for player in &mut self.players
+		{
+			match self.mode
+			{
+				FreeForAllMode::Elimination if losers.contains(&player.id) =>
+				{
+					player.out_in_round = Some(round_number);
+				}
+				FreeForAllMode::Points if winners.contains(&player.id) => player.points += 1,
+				_ => (),
+			}
+		}
+
+		round
+	}
+
+	/// The winner of the game, if it's been decided. The last player standing always wins, and in
+	/// points mode so does anyone alone in the lead with enough points
+	fn winner(&self) -> Option<UserId>
+	{
+		let mut active = self.active_players();
+		let first = active.next()?;
+		if active.next().is_none()
+		{
+			return Some(first.id);
+		}
+
+		match self.mode
+		{
+			FreeForAllMode::Elimination => None,
+			FreeForAllMode::Points =>
+			{
+				let leader = self.active_players().max_by_key(|player| player.points)?;
+				let tied = self
+					.active_players()
+					.filter(|player| player.points == leader.points)
+					.count() > 1;
+				(leader.points >= self.target && !tied).then_some(leader.id)
+			}
+		}
+	}
+
+	/// The rounds `player` and `opponent` both threw in, as `player`'s selection then
+	/// `opponent`'s
+	fn pairing_rounds(&self, player: UserId, opponent: UserId) -> Vec<(Selection, Selection)>
+	{
+		let selection_of = |round: &[(UserId, Selection)], id| {
+			round
+				.iter()
+				.find(|(thrower, _)| *thrower == id)
+				.map(|(_, selection)| *selection)
+		};
+		self.history
+			.iter()
+			.filter_map(|round| {
+				Some((selection_of(round, player)?, selection_of(round, opponent)?))
+			})
+			.collect()
+	}
+
+	/// Everyone's final placement, best first. Players still in are ranked by points, ahead of
+	/// everyone who was knocked out, who are ranked by how long they lasted. Players who can't be
+	/// separated share a placement
+	#[allow(clippy::cast_possible_truncation)]
+	fn placements(&self) -> Vec<(UserId, u32)>
+	{
+		let key = |player: &FreeForAllPlayer| {
+			(
+				player.out_in_round.is_none(),
+				player.out_in_round,
+				player.points,
+			)
+		};
+
+		let mut placements = self
+			.players
+			.iter()
+			.map(|player| {
+				let better = self
+					.players
+					.iter()
+					.filter(|other| key(other) > key(player))
+					.count();
+				(player.id, better as u32 + 1)
+			})
+			.collect::<Vec<_>>();
+		placements.sort_by_key(|(_, placement)| *placement);
+		placements
+	}
+}
+
+struct FreeForAllRound
+{
+	number: u32,
+	selections: Vec<(UserId, Selection)>,
+	winning_selection: Option<Selection>,
+}
+impl FreeForAllRound
+{
+	fn winners(&self) -> Vec<UserId>
+	{
+		self.players_where(|selection| Some(selection) == self.winning_selection)
+	}
+
+	fn losers(&self) -> Vec<UserId>
+	{
+		self.players_where(|selection| {
+			self.winning_selection
+				.is_some_and(|winning_selection| selection != winning_selection)
+		})
+	}
+
+	fn players_where(&self, predicate: impl Fn(Selection) -> bool) -> Vec<UserId>
+	{
+		self.selections
+			.iter()
+			.filter(|(_, selection)| predicate(*selection))
+			.map(|(player, _)| *player)
+			.collect()
+	}
+}
+
+#[cfg(test)]
+mod tests
+{
+	use poise::serenity_prelude::UserId;
+
+	use super::{FreeForAll, FreeForAllMode, Selection};
+
+	fn player(id: u64) -> UserId
+	{
+		UserId::new(id)
+	}
+
+	fn game(players: u64, mode: FreeForAllMode, target: u32) -> FreeForAll
+	{
+		FreeForAll::start((1..=players).map(player).collect(), mode, target)
+	}
+
+	fn select(game: &mut FreeForAll, selections: &[(u64, Selection)])
+	{
+		for (id, selection) in selections
+		{
+			game.active_player_mut(player(*id)).unwrap().selection = Some(*selection);
+		}
+	}
+
+	#[test]
+	fn elimination_knocks_out_losing_throws()
+	{
+		let mut game = game(3, FreeForAllMode::Elimination, 0);
+		select(
+			&mut game,
+			&[(1, Selection::Rock), (2, Selection::Rock), (3, Selection::Paper)],
+		);
+
+		let round = game.play_round();
+		assert_eq!(round.winning_selection, Some(Selection::Paper));
+		assert_eq!(round.losers(), vec![player(1), player(2)]);
+		assert_eq!(game.winner(), Some(player(3)));
+	}
+
+	#[test]
+	fn one_or_all_three_throws_draw()
+	{
+		let mut game = game(3, FreeForAllMode::Elimination, 0);
+		select(
+			&mut game,
+			&[(1, Selection::Rock), (2, Selection::Paper), (3, Selection::Scissors)],
+		);
+		assert_eq!(game.play_round().winning_selection, None);
+
+		select(
+			&mut game,
+			&[(1, Selection::Rock), (2, Selection::Rock), (3, Selection::Rock)],
+		);
+		assert_eq!(game.play_round().winning_selection, None);
+
+		assert_eq!(game.active_players().count(), 3);
+		assert_eq!(game.winner(), None);
+		assert!(!game.all_selected());
+	}
+
+	#[test]
+	fn points_go_to_winning_throws()
+	{
+		let mut game = game(3, FreeForAllMode::Points, 2);
+		select(
+			&mut game,
+			&[(1, Selection::Scissors), (2, Selection::Scissors), (3, Selection::Paper)],
+		);
+		game.play_round();
+		assert_eq!(game.active_players().count(), 3);
+		// tied leaders at the target aren't a winner yet
+		select(
+			&mut game,
+			&[(1, Selection::Scissors), (2, Selection::Scissors), (3, Selection::Paper)],
+		);
+		game.play_round();
+		assert_eq!(game.winner(), None);
+
+		select(
+			&mut game,
+			&[(1, Selection::Rock), (2, Selection::Scissors), (3, Selection::Scissors)],
+		);
+		game.play_round();
+		assert_eq!(game.winner(), Some(player(1)));
+	}
+
+	#[test]
+	fn elimination_placements_go_by_how_long_players_lasted()
+	{
+		let mut game = game(4, FreeForAllMode::Elimination, 0);
+		game.knock_out(player(4));
+		select(
+			&mut game,
+			&[(1, Selection::Rock), (2, Selection::Rock), (3, Selection::Rock)],
+		);
+		game.play_round();
+		select(
+			&mut game,
+			&[(1, Selection::Paper), (2, Selection::Rock), (3, Selection::Rock)],
+		);
+		game.play_round();
+
+		assert_eq!(
+			game.placements(),
+			vec![(player(1), 1), (player(2), 2), (player(3), 2), (player(4), 4)]
+		);
+	}
+
+	#[test]
+	fn points_placements_go_by_points()
+	{
+		let mut game = game(3, FreeForAllMode::Points, 1);
+		select(
+			&mut game,
+			&[(1, Selection::Paper), (2, Selection::Rock), (3, Selection::Rock)],
+		);
+		game.play_round();
+
+		assert_eq!(game.winner(), Some(player(1)));
+		assert_eq!(
+			game.placements(),
+			vec![(player(1), 1), (player(2), 2), (player(3), 2)]
+		);
+	}
+
+	#[test]
+	fn only_knocked_out_players_forfeit()
+	{
+		let mut game = game(3, FreeForAllMode::Elimination, 0);
+		game.knock_out(player(3));
+		select(&mut game, &[(1, Selection::Paper), (2, Selection::Rock)]);
+		game.play_round();
+
+		assert!(!game.forfeited(player(1)));
+		assert!(!game.forfeited(player(2)));
+		assert!(game.forfeited(player(3)));
+	}
+
+	#[test]
+	fn pairing_rounds_only_include_rounds_both_threw_in()
+	{
+		let mut game = game(3, FreeForAllMode::Points, 5);
+		select(
+			&mut game,
+			&[(1, Selection::Rock), (2, Selection::Paper), (3, Selection::Paper)],
+		);
+		game.play_round();
+		game.knock_out(player(3));
+		select(&mut game, &[(1, Selection::Scissors), (2, Selection::Rock)]);
+		game.play_round();
+
+		assert_eq!(
+			game.pairing_rounds(player(1), player(2)),
+			vec![
+				(Selection::Rock, Selection::Paper),
+				(Selection::Scissors, Selection::Rock),
+			]
+		);
+		assert_eq!(
+			game.pairing_rounds(player(1), player(3)),
+			vec![(Selection::Rock, Selection::Paper)]
+		);
+	}
+}
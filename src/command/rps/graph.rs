@@ -0,0 +1,147 @@
+use poise::{
+	serenity_prelude::{
+		CreateAllowedMentions, CreateAttachment, CreateEmbed, Member, Mentionable, Timestamp,
+	},
+	CreateReply,
+};
+
+use crate::{
+	command::ExpectGuildOnly,
+	data::RatingPoint,
+	render::{self, Fonts, GraphLine},
+	Context, Error, Reply,
+};
+
+/// Draw a graph of Rock, Paper, Scissors ratings over time, comparing up to four players
+#[poise::command(slash_command, prefix_command, guild_only)]
+pub async fn graph(
+	ctx: Context<'_>,
+	#[description = "The player to graph (default: you)"] user: Option<Member>,
+	#[description = "Another player to compare against"] compare_a: Option<Member>,
+	#[description = "Another player to compare against"] compare_b: Option<Member>,
+	#[description = "Another player to compare against"] compare_c: Option<Member>,
+) -> Result<(), Error>
+{
+	let Some(fonts) = ctx.data().fonts()
+	else
+	{
+		ctx.reply_error("Graphs can't be drawn without fonts set up in `config.toml`!")
+			.await?;
+		return Ok(());
+	};
+
+	let guild_id = ctx.guild_id().expect_guild_only();
+	let first = match user
+	{
+		Some(member) => member,
+		None => ctx.http().get_member(guild_id, ctx.author().id).await?,
+	};
+	let mut members = vec![first];
+	for member in [compare_a, compare_b, compare_c].into_iter().flatten()
+	{
+		if !members.iter().any(|other| other.user.id == member.user.id)
+		{
+			members.push(member);
+		}
+	}
+
+	let histories = {
+		let data = ctx.data().acquire_lock().await;
+		let guild_data = data.guild_data(guild_id);
+		members
+			.iter()
+			.map(|member| {
+				guild_data.map_or_else(Vec::new, |guild_data| {
+					guild_data.rating_history().of(member.user.id).to_vec()
+				})
+			})
+			.collect::<Vec<_>>()
+	};
+
+	let (lines, unplayed) = members.iter().zip(histories).fold(
+		(Vec::new(), Vec::new()),
+		|(mut lines, mut unplayed), (member, history)| {
+			if history.is_empty()
+			{
+				unplayed.push(member.mention().to_string());
+			}
+			else
+			{
+				lines.push((member, history));
+			}
+			(lines, unplayed)
+		},
+	);
+
+	if lines.is_empty()
+	{
+		ctx.reply_error(format!(
+			"{} {} played any ranked matches yet!",
+			unplayed.join(", "),
+			if unplayed.len() == 1 { "hasn't" } else { "haven't" }
+		))
+		.await?;
+		return Ok(());
+	}
+
+	let image = draw_graph(fonts, lines)?;
+
+	let mut embed = CreateEmbed::new()
+		.title("Rock Paper Scissors Rating History")
+		.image("attachment://graph.png")
+		.color(crate::DEFAULT_COLOR);
+	if !unplayed.is_empty()
+	{
+		embed = embed.description(format!(
+			"Left out {}, since they haven't played any ranked matches yet",
+			unplayed.join(", ")
+		));
+	}
+
+	ctx.send(
+		CreateReply::default()
+			.embed(embed)
+			.attachment(CreateAttachment::bytes(image, "graph.png"))
+			.reply(true)
+			.allowed_mentions(CreateAllowedMentions::new()),
+	)
+	.await?;
+
+	Ok(())
+}
+
+/// Draws each member's rating history in the next line color, dated from the earliest match
+/// anyone played to the latest
+fn draw_graph(fonts: &Fonts, histories: Vec<(&Member, Vec<RatingPoint>)>) -> Result<Vec<u8>, Error>
+{
+	let times = histories
+		.iter()
+		.flat_map(|(_, history)| history.iter().map(|point| point.at))
+		.collect::<Vec<_>>();
+	let start = times.iter().min().copied().unwrap_or_else(Timestamp::now);
+	let end = times.iter().max().copied().unwrap_or_else(Timestamp::now);
+
+	let lines = histories
+		.into_iter()
+		.zip(render::GRAPH_LINE_COLORS)
+		.map(|((member, history), color)| GraphLine {
+			name: member.display_name().to_owned(),
+			color,
+			points: history
+				.iter()
+				.map(|point| (point.at.unix_timestamp(), point.rating))
+				.collect(),
+		})
+		.collect::<Vec<_>>();
+
+	render::encode_png(&render::render_rating_graph(
+		fonts,
+		&lines,
+		(&date_label(start), &date_label(end)),
+	))
+}
+
+fn date_label(timestamp: Timestamp) -> String
+{
+	timestamp.format("%b %-d, %Y").to_string()
+}
@@ -1,31 +1,46 @@
-use std::fmt::Write;
+use std::{fmt::Write, ops::RangeInclusive, time::Duration};
 
 use poise::{
 	serenity_prelude::{
-		CreateAllowedMentions, CreateEmbed, Member, Mentionable, PartialGuild, UserId,
+		ButtonStyle, CreateActionRow, CreateAllowedMentions, CreateAttachment, CreateButton,
+		CreateEmbed, CreateEmbedFooter, CreateInteractionResponse,
+		CreateInteractionResponseMessage, EditMessage, Member, Mentionable, PartialGuild,
+		Timestamp, UserId,
 	},
-	CreateReply,
+	ChoiceParameter, CreateReply,
 };
+use unicode_width::UnicodeWidthStr;
 
+use super::members::MemberLookup;
 use crate::{
 	command::ExpectGuildOnly,
-	data::{GuildData, LeaderboardEntry, Score},
-	Context, Error, Reply,
+	data::{
+		BotDifficulty, GuildData, LeaderboardEntry, LeaderboardSort, RatingSystem, Score,
+	},
+	render::{self, Fonts, LeaderboardRow, ProfileCard},
+	Context, Error, Reply, Respond,
 };
 
+const PAGE_SIZE: usize = 15;
+/// how long the page buttons keep working after they were last used
+const PAGE_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+/// the most columns a name can take up in the text leaderboard before it gets cut off
+const NAME_MAX_WIDTH: usize = 32;
+/// added after the names of players who have left the server
+const DEPARTED_MARKER: &str = " (left)";
+
 macro_rules! write_lb_line {
 	($buffer:expr, $spacing:expr, $rank:expr, $user:expr, $elo:expr, $wins:expr, $losses:expr, $win_rate:expr) => {
 		writeln!(
 			$buffer,
-			"{:<rank_wid$} │ {:<user_wid$} │ {:>elo_wid$} │ {:>win_wid$} │ {:>loss_wid$} │ {:>rate_wid$}",
+			"{:<rank_wid$} │ {} │ {:>elo_wid$} │ {:>win_wid$} │ {:>loss_wid$} │ {:>rate_wid$}",
 			$rank,
-			$user,
+			pad_to_width($user, $spacing.name),
 			$elo,
 			$wins,
 			$losses,
 			$win_rate,
 			rank_wid = $spacing.rank,
-			user_wid = $spacing.name,
 			elo_wid = $spacing.elo,
 			win_wid = $spacing.wins,
 			loss_wid = $spacing.losses,
@@ -33,13 +48,13 @@ macro_rules! write_lb_line {
 		)
 	};
 
-	($buffer:expr, $spacing:expr, $entry:expr) => {
+	($buffer:expr, $spacing:expr, $entry:expr, $rating_system:expr) => {
 		write_lb_line!(
 			$buffer,
 			$spacing,
 			($entry).rank(),
 			($entry).user(),
-			($entry).score().elo,
+			($entry).score().rating_string($rating_system),
 			($entry).score().wins,
 			($entry).score().losses,
 			format!("{:.4}", ($entry).score().win_rate()).trim_start_matches('0')
@@ -61,61 +76,505 @@ macro_rules! repeat {
 pub async fn leaderboard(
 	ctx: Context<'_>,
 	#[description = "Specify a user to see their specific score"] user: Option<Member>,
+	#[description = "Show the rankings against the bot on a difficulty instead"]
+	vs_bot: Option<BotDifficulty>,
+	#[description = "Show the final standings of a past season instead"]
+	#[autocomplete = "super::season::autocomplete_season"]
+	season: Option<String>,
+	#[description = "How to rank the leaderboard (default: ELO)"] sort: Option<LeaderboardSort>,
+	#[description = "Draw the leaderboard as an image (default: false)"] image: Option<bool>,
+	#[description = "Show this server's leaderboard or the global one (default: Server)"]
+	scope: Option<LeaderboardScope>,
 ) -> Result<(), Error>
 {
 	let guild = ctx.partial_guild().await.expect_guild_only();
+	let sort = sort.unwrap_or_default();
+	let as_image = image.unwrap_or(false);
+	// the bot ladder is only kept per server
+	let scope = match vs_bot
+	{
+		Some(_) => LeaderboardScope::Server,
+		None => scope.unwrap_or_default(),
+	};
+
+	if let Some(season_name) = season
+	{
+		season_leaderboard(ctx, &guild, &season_name, sort, as_image).await?;
+		return Ok(());
+	}
 
 	if let Some(target_member) = user
 	{
-		user_score(ctx, &guild, target_member).await?;
+		if let Some(difficulty) = vs_bot
+		{
+			bot_record(ctx, &guild, target_member, difficulty).await?;
+		}
+		else
+		{
+			user_score(ctx, &guild, target_member, as_image).await?;
+		}
+		return Ok(());
 	}
-	else if let Some(sorted_leaderboard) = ctx
+
+	let leaderboard = ranked_scores(ctx, &guild, vs_bot, scope, sort).await;
+
+	if let Some((sorted_leaderboard, rating_system)) = leaderboard
+		&& !sorted_leaderboard.is_empty()
+	{
+		let title = match (vs_bot, scope)
+		{
+			(Some(difficulty), _) => format!(
+				"vs {} Leaderboard ({})",
+				ctx.cache().current_user().name,
+				difficulty.name()
+			),
+			(None, LeaderboardScope::Server) => String::from("Rock Paper Scissors Leaderboard"),
+			(None, LeaderboardScope::Global) =>
+			{
+				String::from("Global Rock Paper Scissors Leaderboard")
+			}
+		};
+		full_leaderboard(
+			ctx,
+			&guild,
+			sorted_leaderboard,
+			title,
+			LeaderboardView {
+				rating_system,
+				sort,
+				scope,
+				as_image,
+			},
+		)
+		.await?;
+	}
+	else if sort == LeaderboardSort::WinRate
+	{
+		ctx.reply_error(format!(
+			"Nobody has played the {} games needed to be ranked by win rate!",
+			LeaderboardSort::WIN_RATE_MIN_GAMES
+		))
+		.await?;
+	}
+	else if scope == LeaderboardScope::Global
+	{
+		ctx.reply_error("Nobody has played on the global leaderboard yet!")
+			.await?;
+	}
+	else
+	{
+		ctx.reply_error("No leaderboard exists for this server!")
+			.await?;
+	}
+
+	Ok(())
+}
+
+async fn season_leaderboard(
+	ctx: Context<'_>,
+	guild: &PartialGuild,
+	season_name: &str,
+	sort: LeaderboardSort,
+	as_image: bool,
+) -> Result<(), Error>
+{
+	let Some(season) = ctx
 		.data()
 		.acquire_lock()
 		.await
 		.guild_data(guild.id)
-		.map(|dat| dat.leaderboard().ordered_scores(Some(15)))
-		&& !sorted_leaderboard.is_empty()
+		.and_then(|dat| dat.season(season_name))
+		.cloned()
+	else
 	{
-		full_leaderboard(ctx, &guild, sorted_leaderboard).await?;
+		ctx.reply_error(format!("No season named `{season_name}` exists!"))
+			.await?;
+		return Ok(());
+	};
+
+	let scores = season
+		.standings
+		.iter()
+		.map(|standing| (standing.user, standing.score()))
+		.collect::<Vec<_>>();
+	let sorted_leaderboard = RankedScore::collect(crate::data::rank_scores(
+		scores.iter().map(|(user, score)| (*user, score)),
+		sort,
+		None,
+	));
+
+	if sorted_leaderboard.is_empty()
+	{
+		ctx.reply_error(format!(
+			"Nobody played the {} games needed to be ranked by win rate in {}!",
+			LeaderboardSort::WIN_RATE_MIN_GAMES,
+			season.name
+		))
+		.await?;
+		return Ok(());
+	}
+
+	full_leaderboard(
+		ctx,
+		guild,
+		sorted_leaderboard,
+		format!("Rock Paper Scissors Leaderboard ({})", season.name),
+		LeaderboardView {
+			rating_system: RatingSystem::Elo,
+			sort,
+			scope: LeaderboardScope::Server,
+			as_image,
+		},
+	)
+	.await
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, poise::ChoiceParameter)]
+pub enum LeaderboardScope
+{
+	#[default]
+	Server,
+	Global,
+}
+
+/// How a leaderboard should be shown, carried across every page
+#[derive(Debug, Clone, Copy)]
+struct LeaderboardView
+{
+	rating_system: RatingSystem,
+	sort: LeaderboardSort,
+	scope: LeaderboardScope,
+	as_image: bool,
+}
+
+/// The parts of a player needed to show them on a leaderboard. On the global leaderboard players
+/// usually aren't members of the server, so this can come from either a member or a user
+struct PlayerDisplay
+{
+	name: String,
+	avatar_url: String,
+	/// whether the player is known to have left the server
+	departed: bool,
+}
+impl PlayerDisplay
+{
+	/// Looks up everyone on a page at once. Players that can't be found at all still get a row,
+	/// one missing player shouldn't take the rest of the leaderboard down with them
+	async fn fetch_page(
+		ctx: Context<'_>,
+		guild: &PartialGuild,
+		players: &[UserId],
+		scope: LeaderboardScope,
+	) -> Vec<Self>
+	{
+		let lookup = match scope
+		{
+			LeaderboardScope::Server => MemberLookup::fetch(ctx, guild.id, players).await,
+			LeaderboardScope::Global => MemberLookup::default(),
+		};
+
+		futures::future::join_all(players.iter().map(|player| {
+			let lookup = &lookup;
+			async move {
+				if let Some(member) = lookup.member(*player)
+				{
+					return Self {
+						name: member.display_name().to_owned(),
+						avatar_url: member.face(),
+						departed: false,
+					};
+				}
+
+				match player.to_user(ctx).await
+				{
+					Ok(user) => Self {
+						name: user.global_name.clone().unwrap_or_else(|| user.name.clone()),
+						avatar_url: user.face(),
+						departed: lookup.has_left(*player),
+					},
+					Err(err) =>
+					{
+						log::warn!("Could not fetch leaderboard player {player}! {err}");
+						Self {
+							name: String::from("Unknown player"),
+							avatar_url: String::new(),
+							departed: lookup.has_left(*player),
+						}
+					}
+				}
+			}
+		}))
+		.await
+	}
+
+	/// The player's name cut down to `max_width`, marked if they've left the server
+	fn label(&self, max_width: usize) -> String
+	{
+		if self.departed
+		{
+			let name = fit_to_width(&self.name, max_width - DEPARTED_MARKER.width());
+			format!("{name}{DEPARTED_MARKER}")
+		}
+		else
+		{
+			fit_to_width(&self.name, max_width)
+		}
+	}
+}
+
+/// An owned copy of a leaderboard entry, so the data lock doesn't need to be held the whole time
+/// someone is flipping through pages
+struct RankedScore
+{
+	user: UserId,
+	rank: u32,
+	score: Score,
+}
+impl RankedScore
+{
+	fn collect(entries: Vec<LeaderboardEntry<'_>>) -> Vec<Self>
+	{
+		entries
+			.into_iter()
+			.map(|entry| Self {
+				user: *entry.user(),
+				rank: entry.rank(),
+				score: entry.score().clone(),
+			})
+			.collect()
+	}
+}
+
+/// Everyone on the leaderboard being shown in order, along with the rating system it's ranked by
+async fn ranked_scores(
+	ctx: Context<'_>,
+	guild: &PartialGuild,
+	vs_bot: Option<BotDifficulty>,
+	scope: LeaderboardScope,
+	sort: LeaderboardSort,
+) -> Option<(Vec<RankedScore>, RatingSystem)>
+{
+	// collected into its own statement so the data lock is released before the leaderboard is
+	// sent, rather than being held for as long as people are flipping through pages
+	let ((sorted_leaderboard, rating_system), hide_departed) = {
+		let data = ctx.data().acquire_lock().await;
+		let hide_departed = data
+			.guild_data(guild.id)
+			.is_some_and(|dat| dat.rps_settings().departed_players().hidden());
+		let leaderboard = match (vs_bot, scope)
+		{
+			(Some(difficulty), _) => data.guild_data(guild.id).map(|dat| {
+				(
+					RankedScore::collect(dat.bot_ladder().ordered_scores(difficulty, sort, None)),
+					RatingSystem::Elo,
+				)
+			}),
+			(None, LeaderboardScope::Server) => data.guild_data(guild.id).map(|dat| {
+				(
+					RankedScore::collect(dat.leaderboard().sorted_scores(sort, None)),
+					dat.rps_settings().rating_system(),
+				)
+			}),
+			(None, LeaderboardScope::Global) => Some((
+				RankedScore::collect(data.global_leaderboard().sorted_scores(sort, None)),
+				RatingSystem::Elo,
+			)),
+		}?;
+		(leaderboard, hide_departed)
+	};
+
+	if hide_departed && scope == LeaderboardScope::Server
+	{
+		Some((without_departed(ctx, guild, sorted_leaderboard, sort).await, rating_system))
 	}
 	else
 	{
-		ctx.reply_error("No leaderboard exists for this server!")
+		Some((sorted_leaderboard, rating_system))
+	}
+}
+
+/// Takes everyone who has left the server off of `sorted_leaderboard` and ranks the rest again, so
+/// there aren't any gaps. Players Discord didn't answer for are kept rather than guessed at
+async fn without_departed(
+	ctx: Context<'_>,
+	guild: &PartialGuild,
+	sorted_leaderboard: Vec<RankedScore>,
+	sort: LeaderboardSort,
+) -> Vec<RankedScore>
+{
+	let players = sorted_leaderboard
+		.iter()
+		.map(|ranked| ranked.user)
+		.collect::<Vec<_>>();
+	let lookup = MemberLookup::fetch(ctx, guild.id, &players).await;
+
+	RankedScore::collect(crate::data::rank_scores(
+		sorted_leaderboard
+			.iter()
+			.filter(|ranked| !lookup.has_left(ranked.user))
+			.map(|ranked| (ranked.user, &ranked.score)),
+		sort,
+		None,
+	))
+}
+
+async fn full_leaderboard(
+	ctx: Context<'_>,
+	guild: &PartialGuild,
+	sorted_leaderboard: Vec<RankedScore>,
+	title: String,
+	view: LeaderboardView,
+) -> Result<(), Error>
+{
+	let page_count = sorted_leaderboard.len().div_ceil(PAGE_SIZE);
+	let mut page = 0;
+
+	let (embed, image) =
+		leaderboard_page(ctx, guild, &sorted_leaderboard, page, view).await?;
+
+	let mut reply = CreateReply::default()
+		.embed(
+			embed
+				.title(&title)
+				.footer(page_footer(page, page_count, view.sort)),
+		)
+		.reply(true)
+		.allowed_mentions(CreateAllowedMentions::new());
+	if let Some(image) = image
+	{
+		reply = reply.attachment(image);
+	}
+
+	if page_count <= 1
+	{
+		ctx.send(reply).await?;
+		return Ok(());
+	}
+
+	let mut leaderboard_message = ctx
+		.send(reply.components(page_buttons(page, page_count)))
+		.await?
+		.into_message()
+		.await?;
+
+	while let Some(interaction) = leaderboard_message
+		.await_component_interaction(ctx)
+		.timeout(PAGE_TIMEOUT)
+		.await
+	{
+		page = match interaction.data.custom_id.as_str()
+		{
+			"rps-lb-previous" => page.saturating_sub(1),
+			"rps-lb-next" => usize::min(page + 1, page_count - 1),
+			"rps-lb-me" =>
+			{
+				if let Some(position) = sorted_leaderboard
+					.iter()
+					.position(|ranked| ranked.user == interaction.user.id)
+				{
+					position / PAGE_SIZE
+				}
+				else
+				{
+					interaction
+						.respond_ephemeral(
+							ctx,
+							crate::error_embed("You aren't on this leaderboard!"),
+						)
+						.await?;
+					continue;
+				}
+			}
+			_ => continue,
+		};
+
+		let (embed, image) =
+			leaderboard_page(ctx, guild, &sorted_leaderboard, page, view).await?;
+
+		interaction
+			.create_response(
+				ctx,
+				CreateInteractionResponse::UpdateMessage(
+					CreateInteractionResponseMessage::new()
+						.embed(
+							embed
+								.title(&title)
+								.footer(page_footer(page, page_count, view.sort)),
+						)
+						.files(image)
+						.components(page_buttons(page, page_count)),
+				),
+			)
 			.await?;
 	}
 
+	leaderboard_message
+		.edit(ctx, EditMessage::new().components(vec![]))
+		.await?;
+
 	Ok(())
 }
 
 // TODO: i still dont like this code very much but thats for a later time i think -morgan 2024-05-27
 // it's a litte better now i think. im still not a big fan of the `StringLengths` struct. either in
 // name or in purpose. but i think it'll do for now -morgan 2024-05-28
-async fn full_leaderboard(
+async fn leaderboard_page(
 	ctx: Context<'_>,
 	guild: &PartialGuild,
-	sorted_leaderboard: Vec<LeaderboardEntry<'_>>,
-) -> Result<(), Error>
+	sorted_leaderboard: &[RankedScore],
+	page: usize,
+	view: LeaderboardView,
+) -> Result<(CreateEmbed, Option<CreateAttachment>), Error>
 {
-	// so for some reason vscode inlay hints are really confused as to what the type of `entry` is
-	// despite rustc being perfectly able to deduce it? kinda weird -morgan 2024-05-28
-	let scores = futures::future::try_join_all(sorted_leaderboard.into_iter().map(
-		|entry: LeaderboardEntry<UserId>| async {
-			entry
-				.map_user(|id| async move {
-					guild
-						.member(ctx.http(), id)
-						.await
-						.map(|member| unidecode::unidecode(member.display_name()))
-				})
-				.await_user()
-				.await
-				.transpose()
-		},
-	))
-	.await?;
+	let rating_system = view.rating_system;
+
+	let page_entries = sorted_leaderboard
+		.iter()
+		.skip(page * PAGE_SIZE)
+		.take(PAGE_SIZE)
+		.collect::<Vec<_>>();
+	let displays = PlayerDisplay::fetch_page(
+		ctx,
+		guild,
+		&page_entries.iter().map(|ranked| ranked.user).collect::<Vec<_>>(),
+		view.scope,
+	)
+	.await;
+	let players = page_entries
+		.iter()
+		.zip(displays)
+		.map(|(ranked, display)| LeaderboardEntry::new(display, ranked.rank, &ranked.score))
+		.collect::<Vec<_>>();
+
+	let mut embed = CreateEmbed::new().color(crate::DEFAULT_COLOR);
+	match view.scope
+	{
+		LeaderboardScope::Server =>
+		{
+			if let Some(guild_icon) = guild.icon_url()
+			{
+				embed = embed.thumbnail(guild_icon);
+			}
+		}
+		LeaderboardScope::Global => embed = embed.thumbnail(ctx.cache().current_user().face()),
+	}
+
+	if view.as_image && let Some(fonts) = ctx.data().fonts()
+	{
+		let elo_range = elo_range(sorted_leaderboard.iter().map(|ranked| &ranked.score));
+		match leaderboard_image(fonts, &players, &elo_range, rating_system).await
+		{
+			Ok(image) => return Ok((embed.image("attachment://leaderboard.png"), Some(image))),
+			Err(err) => log::error!("Could not render leaderboard image! {err}"),
+		}
+	}
+
+	let scores = players
+		.into_iter()
+		.map(|entry| entry.map_user(|player| player.label(NAME_MAX_WIDTH)))
+		.collect::<Vec<_>>();
 
-	let string_lengths = get_max_lengths(&scores).cap_name_at(32);
+	let string_lengths = get_max_lengths(&scores, rating_system);
 
 	let mut leaderboard_string = String::from("```");
 
@@ -132,49 +591,144 @@ async fn full_leaderboard(
 
 	leaderboard_string.push_str(&string_lengths.draw_line('═', '╪'));
 
-	let mut top_3_line_drawn = false;
+	// later pages start below the top 3, so the line is only drawn right after someone in it
+	let mut previous_rank = None;
 	for entry in scores
 	{
-		if !top_3_line_drawn && entry.rank() > 3
+		if previous_rank.is_some_and(|rank| rank <= 3) && entry.rank() > 3
 		{
 			leaderboard_string.push_str(&string_lengths.draw_line('┄', '┼'));
-			top_3_line_drawn = true;
 		}
+		previous_rank = Some(entry.rank());
 
-		let _ = write_lb_line!(leaderboard_string, string_lengths, entry);
+		let _ = write_lb_line!(leaderboard_string, string_lengths, entry, rating_system);
 	}
 
 	leaderboard_string.push_str("```");
 
-	let mut embed = CreateEmbed::new()
-		.title("Rock Paper Scissors Leaderboard")
-		.description(leaderboard_string)
-		.color(crate::DEFAULT_COLOR);
+	Ok((embed.description(leaderboard_string), None))
+}
 
-	if let Some(guild_icon) = guild.icon_url()
+async fn leaderboard_image(
+	fonts: &Fonts,
+	players: &[LeaderboardEntry<'_, PlayerDisplay>],
+	elo_range: &RangeInclusive<i32>,
+	rating_system: RatingSystem,
+) -> Result<CreateAttachment, Error>
+{
+	// a missing avatar just leaves a gap, it's not worth failing the whole image over
+	let avatars = futures::future::join_all(players.iter().map(|entry| async move {
+		render::fetch_circle(&entry.user().avatar_url, render::LEADERBOARD_AVATAR_SIZE)
+			.await
+			.ok()
+	}))
+	.await;
+
+	let rows = players
+		.iter()
+		.zip(avatars)
+		.map(|(entry, avatar)| LeaderboardRow {
+			rank: entry.rank(),
+			name: entry.user().label(NAME_MAX_WIDTH),
+			avatar,
+			rating: entry.score().rating_string(rating_system),
+			elo: entry.score().elo,
+			wins: entry.score().wins,
+			losses: entry.score().losses,
+			win_rate: entry.score().win_rate(),
+		})
+		.collect::<Vec<_>>();
+
+	Ok(CreateAttachment::bytes(
+		render::encode_png(&render::render_leaderboard(fonts, &rows, elo_range))?,
+		"leaderboard.png",
+	))
+}
+
+/// The lowest and highest ratings out of `scores`, which rating bars are drawn relative to
+fn elo_range<'a>(scores: impl Iterator<Item = &'a Score>) -> RangeInclusive<i32>
+{
+	let elos = scores.map(|score| score.elo).collect::<Vec<_>>();
+	let lowest = elos.iter().min().copied().unwrap_or(Score::BASE_ELO);
+	let highest = elos.iter().max().copied().unwrap_or(Score::BASE_ELO);
+	lowest..=highest
+}
+
+fn page_footer(page: usize, page_count: usize, sort: LeaderboardSort) -> CreateEmbedFooter
+{
+	let mut footer = format!("Page {}/{page_count} • Sorted by {}", page + 1, sort.name());
+	if sort == LeaderboardSort::WinRate
 	{
-		embed = embed.thumbnail(guild_icon);
+		let _ = write!(
+			footer,
+			" (at least {} games)",
+			LeaderboardSort::WIN_RATE_MIN_GAMES
+		);
 	}
+	CreateEmbedFooter::new(footer)
+}
 
-	ctx.send(
-		CreateReply::default()
-			.embed(embed)
-			.reply(true)
-			.allowed_mentions(CreateAllowedMentions::new()),
-	)
-	.await?;
+fn page_buttons(page: usize, page_count: usize) -> Vec<CreateActionRow>
+{
+	vec![CreateActionRow::Buttons(vec![
+		CreateButton::new("rps-lb-previous")
+			.label("Previous")
+			.style(ButtonStyle::Secondary)
+			.disabled(page == 0),
+		CreateButton::new("rps-lb-next")
+			.label("Next")
+			.style(ButtonStyle::Secondary)
+			.disabled(page + 1 >= page_count),
+		CreateButton::new("rps-lb-me")
+			.label("Jump to me")
+			.style(ButtonStyle::Primary),
+	])]
+}
 
-	Ok(())
+/// Cuts text down to fit within `max_width` columns, ending it with an ellipsis if anything had to
+/// be cut off. Widths are measured the way a terminal would draw them, so wide characters like
+/// CJK and emoji count for two
+fn fit_to_width(text: &str, max_width: usize) -> String
+{
+	if text.width() <= max_width
+	{
+		return text.to_owned();
+	}
+
+	let mut fitted = String::new();
+	for c in text.chars()
+	{
+		fitted.push(c);
+		if fitted.width() + 1 > max_width
+		{
+			fitted.pop();
+			break;
+		}
+	}
+
+	// a joiner left dangling at the end would glue the ellipsis onto the previous emoji
+	format!("{}…", fitted.trim_end_matches('\u{200d}'))
+}
+
+/// Pads text with spaces until it takes up `width` columns. The standard formatting width counts
+/// chars rather than columns, which throws the table off for wide characters
+fn pad_to_width(text: impl AsRef<str>, width: usize) -> String
+{
+	let text = text.as_ref();
+	format!("{text}{}", " ".repeat(width.saturating_sub(text.width())))
 }
 
-fn get_max_lengths(leaderboard_entries: &[LeaderboardEntry<'_, String>]) -> StringLengths
+fn get_max_lengths(
+	leaderboard_entries: &[LeaderboardEntry<'_, String>],
+	rating_system: RatingSystem,
+) -> StringLengths
 {
 	let mut lengths = StringLengths::default();
 	for entry in leaderboard_entries
 	{
 		lengths.set_name(entry.user());
 		lengths.set_rank(entry.rank());
-		lengths.set_elo(entry.score().elo);
+		lengths.set_elo(&entry.score().rating_string(rating_system));
 		lengths.set_losses(entry.score().losses);
 		lengths.set_wins(entry.score().wins);
 		lengths.set_winrate(entry.score().win_rate());
@@ -205,23 +759,18 @@ impl StringLengths
 		}
 	}
 
-	pub fn cap_name_at(mut self, max_len: usize) -> Self
-	{
-		self.name = usize::min(self.name, max_len);
-		self
-	}
 	pub fn set_name(&mut self, name: &str)
 	{
-		let new = unidecode::unidecode(name).len();
+		let new = name.width();
 		if new > self.name
 		{
 			self.name = new;
 		}
 	}
 
-	pub fn set_elo(&mut self, elo: i32)
+	pub fn set_elo(&mut self, rating_string: &str)
 	{
-		let new = f64::from(elo.abs()).log10().floor() as usize + 1;
+		let new = rating_string.len();
 		if new > self.elo
 		{
 			self.elo = new;
@@ -292,6 +841,7 @@ async fn user_score(
 	ctx: Context<'_>,
 	guild: &PartialGuild,
 	target_member: Member,
+	as_image: bool,
 ) -> Result<(), Error>
 {
 	let target_member = ctx
@@ -299,23 +849,21 @@ async fn user_score(
 		.get_member(guild.id, target_member.user.id)
 		.await?;
 
-	if let Some(guild_data) = ctx.data().acquire_lock().await.guild_data(guild.id)
-		&& let Some(score) = guild_data.leaderboard().score(target_member.user.id)
-	{
-		ctx.send(
-			CreateReply::default()
-				.embed(create_user_score_embed(
-					&target_member,
-					guild,
-					score,
-					guild_data,
-				))
-				.reply(true)
-				.allowed_mentions(CreateAllowedMentions::new())
-				.ephemeral(false),
-		)
-		.await?;
-	}
+	let Some((embed, card)) = ctx
+		.data()
+		.acquire_lock()
+		.await
+		.guild_data(guild.id)
+		.and_then(|guild_data| {
+			let score = guild_data
+				.leaderboard()
+				.score(target_member.user.id)
+				.filter(|score| score.total_games() > 0)?;
+			Some((
+				create_user_score_embed(&target_member, guild, score, guild_data),
+				as_image.then(|| create_profile_card(&target_member, guild, score, guild_data)),
+			))
+		})
 	else
 	{
 		ctx.reply_error(format!(
@@ -323,11 +871,112 @@ async fn user_score(
 			target_member.mention()
 		))
 		.await?;
+		return Ok(());
 	};
 
+	if let Some((mut card, elo_range)) = card
+		&& let Some(fonts) = ctx.data().fonts()
+	{
+		card.avatar = render::fetch_circle(&target_member.face(), render::PROFILE_AVATAR_SIZE)
+			.await
+			.ok();
+
+		match render::encode_png(&render::render_profile_card(fonts, &card, &elo_range))
+		{
+			Ok(image) =>
+			{
+				ctx.send(
+					CreateReply::default()
+						.embed(
+							CreateEmbed::new()
+								.title("Rock Paper Scissors Stats")
+								.image("attachment://profile.png")
+								.color(crate::DEFAULT_COLOR),
+						)
+						.attachment(CreateAttachment::bytes(image, "profile.png"))
+						.reply(true)
+						.allowed_mentions(CreateAllowedMentions::new()),
+				)
+				.await?;
+				return Ok(());
+			}
+			Err(err) => log::error!("Could not render profile card! {err}"),
+		}
+	}
+
+	ctx.send(
+		CreateReply::default()
+			.embed(embed)
+			.reply(true)
+			.allowed_mentions(CreateAllowedMentions::new())
+			.ephemeral(false),
+	)
+	.await?;
+
 	Ok(())
 }
 
+/// Everything needed to draw a profile card except the avatar, which has to be downloaded
+/// separately once the data lock has been released
+fn create_profile_card(
+	target_member: &Member,
+	guild: &PartialGuild,
+	score: &Score,
+	guild_data: &GuildData,
+) -> (ProfileCard, RangeInclusive<i32>)
+{
+	let rating_system = guild_data.rps_settings().rating_system();
+
+	(
+		ProfileCard {
+			name: target_member.display_name().to_owned(),
+			avatar: None,
+			rank: format!(
+				"#{} in {}",
+				leaderboard_rank(target_member, guild, guild_data),
+				guild.name
+			),
+			rating: score.rating_string(rating_system),
+			elo: score.elo,
+			wins: score.wins,
+			losses: score.losses,
+			win_rate: score.win_rate(),
+			accent: render::rgba(
+				target_member
+					.user
+					.accent_colour
+					.unwrap_or(crate::DEFAULT_COLOR),
+			),
+		},
+		elo_range(
+			guild_data
+				.leaderboard()
+				.ordered_scores(None)
+				.iter()
+				.map(LeaderboardEntry::score),
+		),
+	)
+}
+
+pub(super) fn leaderboard_rank(
+	target_member: &Member,
+	guild: &PartialGuild,
+	guild_data: &GuildData,
+) -> usize
+{
+	guild_data
+		.leaderboard()
+		.ordered_scores(None)
+		.iter()
+		.position(|entry| target_member.user.id == *entry.user())
+		.unwrap_or_else(|| {
+			panic!(
+				"Could not find user {}({}) in {}({})",
+				target_member.user.name, target_member.user.id, guild.name, guild.id
+			)
+		}) + 1
+}
+
 fn create_user_score_embed(
 	target_member: &Member,
 	guild: &PartialGuild,
@@ -342,20 +991,29 @@ fn create_user_score_embed(
 			"Rank",
 			format!(
 				"#{} in {}",
-				guild_data
-					.leaderboard()
-					.ordered_scores(None)
-					.iter()
-					.position(|entry| target_member.user.id == *entry.user())
-					.unwrap_or_else(|| panic!(
-						"Could not find user {}({}) in {}({})",
-						target_member.user.name, target_member.user.id, guild.name, guild.id
-					)) + 1,
+				leaderboard_rank(target_member, guild, guild_data),
 				guild.name
 			),
 			true,
 		)
-		.field("Rating", score.elo.to_string(), false)
+		.field(
+			"Rating",
+			match guild_data.rps_settings().rating_system()
+			{
+				RatingSystem::Elo => score.elo.to_string(),
+				RatingSystem::Glicko2 =>
+				{
+					let glicko = score.glicko_rating(Timestamp::now());
+					format!(
+						"{} ± {:.0}{}",
+						score.elo,
+						glicko.deviation,
+						if glicko.is_provisional() { " (provisional)" } else { "" }
+					)
+				}
+			},
+			false,
+		)
 		.field("Wins", score.wins.to_string(), true)
 		.field("Losses", score.losses.to_string(), true)
 		.field(
@@ -371,3 +1029,65 @@ fn create_user_score_embed(
 		)
 		.thumbnail(target_member.face())
 }
+
+async fn bot_record(
+	ctx: Context<'_>,
+	guild: &PartialGuild,
+	target_member: Member,
+	difficulty: BotDifficulty,
+) -> Result<(), Error>
+{
+	let target_member = ctx
+		.http()
+		.get_member(guild.id, target_member.user.id)
+		.await?;
+	let bot_name = ctx.cache().current_user().name.clone();
+
+	let score = ctx
+		.data()
+		.acquire_lock()
+		.await
+		.guild_data(guild.id)
+		.and_then(|dat| dat.bot_ladder().record(target_member.user.id))
+		.map(|record| record[difficulty].clone())
+		.filter(|score| score.total_games() > 0);
+
+	if let Some(score) = score
+	{
+		ctx.send(
+			CreateReply::default()
+				.embed(
+					CreateEmbed::new()
+						.title(format!("vs {bot_name} Stats ({})", difficulty.name()))
+						.description(format!("# Stats for {}", target_member.mention()))
+						.field("Rating", score.elo.to_string(), false)
+						.field("Wins", score.wins.to_string(), true)
+						.field("Losses", score.losses.to_string(), true)
+						.field("Win Rate", format!("{:.2}%", score.win_rate() * 100.0), true)
+						.field("Current Streak", score.streak.max(0).to_string(), true)
+						.field("Longest Streak", score.longest_streak.to_string(), true)
+						.color(
+							target_member
+								.user
+								.accent_colour
+								.unwrap_or(crate::DEFAULT_COLOR),
+						)
+						.thumbnail(target_member.face()),
+				)
+				.reply(true)
+				.allowed_mentions(CreateAllowedMentions::new()),
+		)
+		.await?;
+	}
+	else
+	{
+		ctx.reply_error(format!(
+			"{} hasn't played against me on {}!",
+			target_member.mention(),
+			difficulty.name()
+		))
+		.await?;
+	}
+
+	Ok(())
+}
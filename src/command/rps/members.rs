@@ -0,0 +1,124 @@
+use std::{
+	collections::{HashMap, HashSet},
+	time::Duration,
+};
+
+use futures::StreamExt;
+use poise::serenity_prelude::{self as serenity, ChunkGuildFilter, Event, GuildId, Member, UserId};
+
+use crate::Context;
+
+/// how long to wait on Discord to send the members that weren't cached
+const CHUNK_TIMEOUT: Duration = Duration::from_secs(5);
+/// the most members the gateway will look up in a single request
+const CHUNK_REQUEST_SIZE: usize = 100;
+
+/// Which of a list of players are still members of a server, looked up all at once
+#[derive(Default)]
+pub struct MemberLookup
+{
+	members: HashMap<UserId, Member>,
+	/// players Discord confirmed aren't in the server anymore
+	departed: HashSet<UserId>,
+}
+impl MemberLookup
+{
+	/// Looks `players` up in the cache, then asks the gateway for everyone who wasn't cached in as
+	/// few requests as possible, rather than fetching them one by one over http. Players Discord
+	/// doesn't answer for in time end up neither members nor departed
+	pub async fn fetch(ctx: Context<'_>, guild_id: GuildId, players: &[UserId]) -> Self
+	{
+		let mut lookup = Self {
+			members: ctx
+				.cache()
+				.guild(guild_id)
+				.map(|guild| {
+					players
+						.iter()
+						.filter_map(|player| {
+							guild.members.get(player).map(|member| (*player, member.clone()))
+						})
+						.collect()
+				})
+				.unwrap_or_default(),
+			departed: HashSet::new(),
+		};
+
+		let uncached = players
+			.iter()
+			.filter(|player| !lookup.members.contains_key(player))
+			.copied()
+			.collect::<Vec<_>>();
+		if uncached.is_empty()
+		{
+			return lookup;
+		}
+
+		let shard = &ctx.serenity_context().shard;
+		let nonce_prefix = format!("rps-{}-", ctx.id());
+		// the collector has to be listening before any of the requests go out
+		let chunks = {
+			let nonce_prefix = nonce_prefix.clone();
+			serenity::collect(shard, move |event| match event
+			{
+				Event::GuildMembersChunk(chunk)
+					if chunk
+						.nonce
+						.as_ref()
+						.is_some_and(|nonce| nonce.starts_with(&nonce_prefix)) =>
+				{
+					Some(chunk.clone())
+				}
+				_ => None,
+			})
+		};
+
+		let batches = uncached.chunks(CHUNK_REQUEST_SIZE).collect::<Vec<_>>();
+		for (i, batch) in batches.iter().enumerate()
+		{
+			shard.chunk_guild(
+				guild_id,
+				None,
+				false,
+				ChunkGuildFilter::UserIds(batch.to_vec()),
+				Some(format!("{nonce_prefix}{i}")),
+			);
+		}
+
+		let mut finished_batches = 0;
+		let mut chunks = std::pin::pin!(chunks.take_until(tokio::time::sleep(CHUNK_TIMEOUT)));
+		while let Some(chunk) = chunks.next().await
+		{
+			lookup.members.extend(chunk.members);
+			lookup.departed.extend(
+				chunk
+					.not_found
+					.into_iter()
+					.map(|id| UserId::new(id.get())),
+			);
+
+			if chunk.chunk_index + 1 >= chunk.chunk_count
+			{
+				finished_batches += 1;
+				if finished_batches == batches.len()
+				{
+					return lookup;
+				}
+			}
+		}
+
+		log::warn!("Timed out waiting on the gateway for members of guild {guild_id}!");
+		lookup
+	}
+
+	pub fn member(&self, player: UserId) -> Option<&Member>
+	{
+		self.members.get(&player)
+	}
+
+	/// Whether `player` is known to have left the server
+	pub fn has_left(&self, player: UserId) -> bool
+	{
+		self.departed.contains(&player)
+	}
+}
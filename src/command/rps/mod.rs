@@ -1,115 +1,529 @@
+mod admin;
+mod bot;
+mod challenges;
+mod commitment;
+mod free_for_all;
+mod graph;
 mod leaderboard;
+mod members;
 mod model;
+mod odds;
+mod profile;
+mod queue;
+pub(super) mod rank_roles;
+mod rematch;
+mod scoreboard;
+mod season;
+mod team;
+mod versus;
 
-use std::{fmt::Write, time::Duration};
+use std::{
+	fmt::Write,
+	time::{Duration, Instant},
+};
 
-use lazy_static::lazy_static;
-use model::{ChallengerOpponentPair, Game, MatchOutcome, RoundOutcome, Selection, Side};
+use model::{
+	ChallengerOpponentPair, Game, MatchFormat, MatchFormatKind, MatchOutcome, Player,
+	RoundOutcome, Selection, Side,
+};
+use commitment::Commitment;
+use admin::FlaggedMatch;
+pub use admin::remove_departed;
+use rank_roles::RankChanges;
+use scoreboard::{Board, Scoreboard};
 use poise::{
 	serenity_prelude::{
-		ButtonStyle, CreateActionRow, CreateAllowedMentions, CreateButton, CreateEmbed,
-		CreateEmbedFooter, CreateMessage, GuildChannel, Member, Mentionable, Message, User,
+		AutoArchiveDuration, ButtonStyle, ComponentInteraction, CreateActionRow,
+		CreateAllowedMentions, CreateButton, CreateEmbed, CreateEmbedFooter,
+		CreateInteractionResponse, CreateInteractionResponseFollowup, CreateMessage, CreateThread,
+		EditMessage, EditThread, GuildChannel, GuildId, Member, Mentionable, Message, Timestamp,
+		User, UserId,
 	},
-	CreateReply,
+	ChoiceParameter, CreateReply,
 };
-use rand::Rng;
 
 use crate::{
-	command::parent_command,
-	data::{Leaderboard, Outcome, Score},
+	command::{format_duration, parent_command},
+	data::{
+		BotDifficulty, DataManager, GuildData, Leaderboard, MatchFlag, MatchRecord, Outcome,
+		RatingSystem, RpsSettings,
+	},
 	Context, Error, Reply, Respond,
 };
 
 use super::ExpectGuildOnly;
 
+/// how often a waiting challenge checks whether it's been withdrawn
+const CHALLENGE_TICK: Duration = Duration::from_secs(5);
+
 parent_command! {
 	let rps = poise::command(
 		prefix_command,
 		slash_command,
 		guild_only,
-		subcommands("challenge", "leaderboard::leaderboard")
+		subcommands(
+			"admin::admin",
+			"challenge",
+			"challenges::cancel",
+			"challenges::pending",
+			"commitment::verify",
+			"free_for_all::ffa",
+			"graph::graph",
+			"queue::queue",
+			"leaderboard::leaderboard",
+			"odds::odds",
+			"profile::profile",
+			"season::season",
+			"team::team",
+			"versus::versus"
+		)
 	)
 }
 
 /// Challenge another user to a game of Rock, Paper, Scissors
 #[poise::command(slash_command, prefix_command, guild_only)]
+#[allow(clippy::too_many_arguments)] // every argument is a command option
 pub async fn challenge(
 	ctx: Context<'_>,
-	#[description = "Player to challenge"] opponent: Member,
-	#[description = "The amount of games needed to win (default: 1)"] first_to: Option<u32>,
+	#[description = "Player to challenge (leave empty to let anyone accept)"]
+	opponent: Option<Member>,
+	#[description = "Rounds needed to win (default: 1)"]
+	#[min = 1]
+	first_to: Option<u32>,
+	#[description = "How the winner of the match is decided (default: First To)"]
+	format: Option<MatchFormatKind>,
+	#[description = "Rounds to play, for Best Of (default: 1)"]
+	#[min = 1]
+	rounds: Option<u32>,
+	#[description = "Whether the match affects ratings (default: true)"] ranked: Option<bool>,
+	#[description = "How hard the bot should try, if challenging the bot (default: Easy)"]
+	difficulty: Option<BotDifficulty>,
+	#[description = "Play the match in its own thread (default: the server's setting)"]
+	thread: Option<bool>,
+) -> Result<(), Error>
+{
+	let format = MatchFormat::from_options(format.unwrap_or_default(), first_to, rounds);
+	let ranked = ranked.unwrap_or(true);
+	match opponent
+	{
+		Some(opponent) if ctx.author().id == opponent.user.id =>
+		{
+			ctx.reply_error("You can't challenge yourself!").await?;
+			Ok(())
+		}
+		Some(opponent) if opponent.user.id == ctx.framework().bot_id =>
+		{
+			let player = [ctx.author().id];
+			if !challenges::try_start_playing(ctx, &player, ctx.channel_id()).await?
+			{
+				return Ok(());
+			}
+
+			let result =
+				start_bot_match(ctx, format, ranked, difficulty.unwrap_or_default()).await;
+			challenges::stop_playing(ctx, &player).await;
+			result
+		}
+		Some(opponent) if opponent.user.bot =>
+		{
+			ctx.reply_error("You can't challenge a bot!").await?;
+			Ok(())
+		}
+		opponent => start_challenge(ctx, opponent, format, ranked, thread).await,
+	}
+}
+
+async fn start_challenge(
+	ctx: Context<'_>,
+	opponent: Option<Member>,
+	format: MatchFormat,
+	ranked: bool,
+	thread: Option<bool>,
 ) -> Result<(), Error>
 {
-	if ctx.author().id == opponent.user.id
+	let opponent_user = opponent.as_ref().map(|member| &member.user);
+	let challenge_pending = ctx
+		.data()
+		.acquire_matchmaking_lock()
+		.await
+		.activity_mut(ctx.guild_id().expect_guild_only())
+		.challenge_by(ctx.author().id)
+		.is_some();
+	if challenge_pending
 	{
-		ctx.reply_error("You can't challenge yourself!").await?;
-		Ok(())
+		ctx.reply_error("You already have an open challenge! Withdraw it with `/rps cancel` first")
+			.await?;
+		return Ok(());
 	}
-	else if opponent.user.id == ctx.framework().bot_id
+	let players = [Some(ctx.author().id), opponent_user.map(|user| user.id)];
+	if !challenges::check_available(ctx, &players.into_iter().flatten().collect::<Vec<_>>()).await?
 	{
-		start_bot_match(ctx, first_to.unwrap_or(1)).await
+		return Ok(());
 	}
-	else if opponent.user.bot
+
+	let settings = rps_settings(ctx).await;
+	let accept_timeout = settings.accept_timeout();
+	let mut challenge_message = send_challenge_message(
+		ctx,
+		ctx.author(),
+		opponent_user,
+		format,
+		ranked,
+		accept_timeout,
+	)
+	.await?;
+	challenges::issue(
+		ctx,
+		opponent_user.map(|user| user.id),
+		challenge_message.channel_id,
+		challenge_message.id,
+	)
+	.await;
+
+	let accepted_by =
+		await_challenge_accept(ctx, &mut challenge_message, opponent_user, accept_timeout).await;
+	challenges::resolve(ctx, challenge_message.id).await;
+	let Some(opponent_id) = accepted_by?
+	else
 	{
-		ctx.reply_error("You can't challenge a bot!").await?;
-		Ok(())
+		return Ok(());
+	};
+
+	let channel = ctx.guild_channel().await.expect_guild_only();
+	let thread = if thread.unwrap_or_else(|| settings.match_threads())
+	{
+		create_match_thread(ctx, &channel, &challenge_message, opponent_id).await
 	}
 	else
 	{
-		start_challenge(ctx, opponent, first_to.unwrap_or(1)).await
+		None
+	};
+
+	// either of them may have started another game while the challenge was open
+	let players = [ctx.author().id, opponent_id];
+	if !challenges::try_start_playing(ctx, &players, thread.as_ref().unwrap_or(&channel).id).await?
+	{
+		if let Some(thread) = &thread
+		{
+			archive_thread(ctx, thread).await;
+		}
+		return Ok(());
+	}
+
+	play_match(ctx, ctx.author().id, opponent_id, format, ranked, &channel, thread.as_ref()).await
+}
+
+/// Starts a thread off of the challenge message for the match to be played in. Returns `None` if
+/// the thread couldn't be made, in which case the match is played in `channel` as usual
+async fn create_match_thread(
+	ctx: Context<'_>,
+	channel: &GuildChannel,
+	challenge_message: &Message,
+	opponent: UserId,
+) -> Option<GuildChannel>
+{
+	// threads can't have threads of their own
+	if channel.thread_metadata.is_some()
+	{
+		return None;
 	}
+
+	let opponent_name = opponent
+		.to_user(ctx)
+		.await
+		.map_or_else(|_| String::from("???"), |user| user.name);
+	channel
+		.create_thread_from_message(
+			ctx,
+			challenge_message.id,
+			CreateThread::new(format!(
+				"Rock Paper Scissors: {} vs {opponent_name}",
+				ctx.author().name
+			))
+			.auto_archive_duration(AutoArchiveDuration::OneDay),
+		)
+		.await
+		.inspect_err(|err| {
+			log::warn!("Could not create a thread for an rps match in {}! {err}", channel.id);
+		})
+		.ok()
 }
 
-async fn start_challenge(ctx: Context<'_>, opponent: Member, first_to: u32) -> Result<(), Error>
+/// Plays out a match between two users in `channel`, then keeps playing rematches for as long as
+/// both players agree to them. If there's a `thread`, the matches are played in it instead and
+/// only the results are posted in `channel`, archiving the thread once the players are done.
+/// Both players have to already be marked as playing for the first match. They only count as
+/// playing while a match is underway, so they're free to play something else while deciding on a
+/// rematch
+async fn play_match(
+	ctx: Context<'_>,
+	challenger: UserId,
+	opponent: UserId,
+	format: MatchFormat,
+	ranked: bool,
+	channel: &GuildChannel,
+	thread: Option<&GuildChannel>,
+) -> Result<(), Error>
 {
-	let challenge_message =
-		send_challenge_message(ctx, ctx.author(), &opponent.user, first_to).await?;
+	let players = ChallengerOpponentPair::new(challenger, opponent);
+	let mut format = format;
 
-	if await_challenge_accept(ctx, &challenge_message, &opponent).await?
+	loop
 	{
-		let guild_id = ctx.guild_id().expect_guild_only();
-		// we fetch member through http instead of just passing the reference from the commands
-		// so we can use the accent color later. -morgan 2024-01-18
-		let members = ChallengerOpponentPair::new(
-			ctx.http().get_member(guild_id, ctx.author().id).await?,
-			ctx.http().get_member(guild_id, opponent.user.id).await?,
-		);
-
-		let mut game = Game::start(
-			members.challenger.user.id,
-			members.opponent.user.id,
-			first_to,
-		);
+		let game_channel = thread.unwrap_or(channel);
+		let result = play_single_match(
+			ctx,
+			challenger,
+			opponent,
+			format,
+			ranked,
+			game_channel,
+			channel,
+		)
+		.await;
+		challenges::stop_playing(ctx, &[challenger, opponent]).await;
 
-		let channel = ctx.guild_channel().await.expect_guild_only();
-		// if none, selections timed out -morgan 2024-05-27
-		if let Some(match_outcome) = start_game(ctx, &mut game, &members, &channel, false).await?
+		let Some(mut result_message) = result?
+		else
 		{
-			let rating_changes = update_leaderboard(
-				ctx.data()
-					.acquire_lock()
-					.await
-					.guild_data_mut(ctx.guild_id().expect_guild_only())
-					.leaderboard_mut(),
-				&match_outcome,
-			);
+			break;
+		};
 
-			channel
-				.send_message(
-					ctx,
-					CreateMessage::new().embed(create_match_embed(
-						ctx,
-						&match_outcome,
-						&members,
-						Some(rating_changes),
-					)),
-				)
-				.await?;
+		let accept_timeout = rps_settings(ctx).await.accept_timeout();
+		let Some(rematch_format) =
+			rematch::await_rematch(ctx, &mut result_message, players, format, accept_timeout)
+				.await?
+		else
+		{
+			break;
+		};
+		// either of them may have started another game while the rematch was being decided
+		if !challenges::try_start_playing(ctx, &[challenger, opponent], game_channel.id).await?
+		{
+			break;
 		}
+		format = rematch_format;
+	}
+
+	if let Some(thread) = thread
+	{
+		archive_thread(ctx, thread).await;
 	}
 
 	Ok(())
 }
 
+async fn archive_thread(ctx: Context<'_>, thread: &GuildChannel)
+{
+	if let Err(err) = thread
+		.id
+		.edit_thread(ctx, EditThread::new().archived(true))
+		.await
+	{
+		log::warn!("Could not archive rps match thread {}! {err}", thread.id);
+	}
+}
+
+/// Plays out a single match between two users in `channel`, posting the results in
+/// `results_channel` once it concludes. Ranked matches also update the leaderboard. Returns the
+/// results message, or `None` if the match was abandoned
+async fn play_single_match(
+	ctx: Context<'_>,
+	challenger: UserId,
+	opponent: UserId,
+	format: MatchFormat,
+	ranked: bool,
+	channel: &GuildChannel,
+	results_channel: &GuildChannel,
+) -> Result<Option<Message>, Error>
+{
+	let guild_id = ctx.guild_id().expect_guild_only();
+	// we fetch member through http instead of just passing the reference from the commands
+	// so we can use the accent color later. -morgan 2024-01-18
+	let members = ChallengerOpponentPair::new(
+		ctx.http().get_member(guild_id, challenger).await?,
+		ctx.http().get_member(guild_id, opponent).await?,
+	);
+
+	let mut game = Game::start(
+		members.challenger.user.id,
+		members.opponent.user.id,
+		format,
+	);
+
+	let round_timeout = rps_settings(ctx).await.round_timeout();
+	// if none, selections timed out -morgan 2024-05-27
+	let Some(match_outcome) =
+		start_game(ctx, &mut game, &members, channel, None, round_timeout).await?
+	else
+	{
+		return Ok(None);
+	};
+
+	let RecordedMatch {
+		rating_changes,
+		rank_changes,
+		flags,
+	} = record_match(
+		&mut *ctx.data().acquire_lock().await,
+		guild_id,
+		&game,
+		&match_outcome,
+		ranked,
+	);
+
+	let mut embed = create_match_embed(ctx, &match_outcome, &members, rating_changes);
+	if !ranked
+	{
+		embed = embed.footer(casual_footer());
+	}
+
+	let result_message = results_channel
+		.send_message(
+			ctx,
+			CreateMessage::new()
+				.embed(embed)
+				.components(rematch::rematch_buttons(format)),
+		)
+		.await?;
+
+	if let Some(rank_changes) = rank_changes
+	{
+		rank_changes.apply(ctx, guild_id, &[challenger, opponent]).await;
+	}
+	for flag in flags
+	{
+		admin::report_flag(ctx, guild_id, &flag).await?;
+	}
+
+	Ok(Some(result_message))
+}
+
+/// What saving a finished match changed
+struct RecordedMatch<R = ChallengerOpponentPair<(i32, i32)>>
+{
+	/// each player's old rating, then their new one. `None` for casual matches
+	rating_changes: Option<R>,
+	rank_changes: Option<RankChanges>,
+	/// every recorded pairing that looks like win trading
+	flags: Vec<FlaggedMatch>,
+}
+
+/// Saves a finished match to the server's history, updating the leaderboards if it was ranked
+fn record_match(
+	data: &mut DataManager,
+	guild_id: GuildId,
+	game: &Game,
+	match_outcome: &MatchOutcome,
+	ranked: bool,
+) -> RecordedMatch
+{
+	let guild_data = data.guild_data_mut(guild_id);
+	let rating_system = guild_data.rps_settings().rating_system();
+	let counts_globally = ranked && guild_data.rps_settings().global_ladder_enabled();
+	// playing the same opponent over and over counts for less each time, so two players can't
+	// just trade wins to farm rating
+	let weight = guild_data.match_history().repeat_weight(
+		match_outcome.challenger().id(),
+		match_outcome.opponent().id(),
+		Timestamp::now(),
+	);
+
+	let (rating_changes, rank_changes) = ranked
+		.then(|| {
+			RankChanges::track(guild_data, |guild_data| {
+				update_leaderboard(
+					guild_data.leaderboard_mut(),
+					match_outcome,
+					rating_system,
+					weight,
+				)
+			})
+		})
+		.unzip();
+	if let Some(rating_changes) = rating_changes
+	{
+		let new_ratings = match_outcome
+			.players
+			.map_ref(Player::id)
+			.zip(rating_changes.map(|(_, new_rating)| new_rating));
+		guild_data
+			.rating_history_mut()
+			.record([new_ratings.challenger, new_ratings.opponent]);
+	}
+
+	let mut record = match_record(game, match_outcome, rating_changes);
+	let suspicion = guild_data.match_history().suspicion(&record);
+	record.flag = suspicion.map(MatchFlag::new);
+	let match_id = guild_data.match_history_mut().record(record);
+	let flag = suspicion.map(|reason| FlaggedMatch {
+		id: match_id,
+		winner: match_outcome.winner().id(),
+		loser: match_outcome.loser().id(),
+		reason,
+	});
+
+	// the global ladder always uses ELO, since servers can each pick their own system
+	if counts_globally
+	{
+		update_leaderboard(
+			data.global_leaderboard_mut(),
+			match_outcome,
+			RatingSystem::Elo,
+			weight,
+		);
+	}
+
+	RecordedMatch {
+		rating_changes,
+		rank_changes,
+		flags: flag.into_iter().collect(),
+	}
+}
+
+/// Saves every pairing from a free-for-all or team game, flagging the ones that look like win
+/// trading the same way one on one matches are. Returns the flagged pairings
+fn record_pairings(
+	guild_data: &mut GuildData,
+	records: impl IntoIterator<Item = MatchRecord>,
+) -> Vec<FlaggedMatch>
+{
+	let records = records
+		.into_iter()
+		.map(|record| MatchRecord {
+			flag: guild_data
+				.match_history()
+				.suspicion(&record)
+				.map(MatchFlag::new),
+			..record
+		})
+		.collect::<Vec<_>>();
+	let flags = records
+		.iter()
+		.map(|record| {
+			record
+				.flag
+				.map(|flag| (record.winner, record.opponent_of(record.winner), flag.reason))
+		})
+		.collect::<Vec<_>>();
+
+	guild_data
+		.match_history_mut()
+		.record_game(records)
+		.into_iter()
+		.zip(flags)
+		.filter_map(|(id, flag)| {
+			let (winner, loser, reason) = flag?;
+			Some(FlaggedMatch {
+				id,
+				winner,
+				loser,
+				reason,
+			})
+		})
+		.collect()
+}
+
 fn create_match_embed(
 	ctx: Context<'_>,
 	match_outcome: &MatchOutcome,
@@ -121,7 +535,15 @@ fn create_match_embed(
 
 	let mut embed = CreateEmbed::new()
 		.title("Game, Set, and Match")
-		.description(format!("# {} wins!", winner.mention()))
+		.description(match match_outcome.forfeited_by()
+		{
+			Some(forfeiting_side) => format!(
+				"# {} wins!\n{} forfeited the match",
+				winner.mention(),
+				members[forfeiting_side].mention()
+			),
+			None => format!("# {} wins!", winner.mention()),
+		})
 		.field(
 			"Score",
 			format!(
@@ -161,28 +583,90 @@ fn create_match_embed(
 	embed
 }
 
+fn match_record(
+	game: &Game,
+	match_outcome: &MatchOutcome,
+	rating_changes: Option<ChallengerOpponentPair<(i32, i32)>>,
+) -> MatchRecord
+{
+	MatchRecord {
+		challenger: match_outcome.challenger().id(),
+		opponent: match_outcome.opponent().id(),
+		winner: match_outcome.winner().id(),
+		forfeited: match_outcome.forfeited_by().is_some(),
+		ranked: rating_changes.is_some(),
+		played_at: Timestamp::now(),
+		rounds: game
+			.history()
+			.iter()
+			.copied()
+			.map(ChallengerOpponentPair::tuple)
+			.collect(),
+		rating_changes: rating_changes
+			.map(|changes| changes.map(|(old_elo, new_elo)| new_elo - old_elo).tuple()),
+		flag: None,
+		voided: false,
+		game_id: None,
+	}
+}
+
+/// One pairing out of a free-for-all or team game, saved as though the two had played a match
+/// against each other. `rounds` are the challenger's selection, then the opponent's, and
+/// `forfeited` is whether the loser forfeited rather than being beaten
+fn pairing_record(
+	players: ChallengerOpponentPair<UserId>,
+	winner: UserId,
+	forfeited: bool,
+	rounds: Vec<(Selection, Selection)>,
+	rating_changes: Option<(i32, i32)>,
+) -> MatchRecord
+{
+	MatchRecord {
+		challenger: players.challenger,
+		opponent: players.opponent,
+		winner,
+		forfeited,
+		ranked: rating_changes.is_some(),
+		played_at: Timestamp::now(),
+		rounds,
+		rating_changes,
+		flag: None,
+		voided: false,
+		game_id: None,
+	}
+}
+
+/// Rates both players against each other, with their rating changes scaled down by `weight`
 fn update_leaderboard(
 	leaderboard: &mut Leaderboard,
 	match_outcome: &MatchOutcome,
+	rating_system: RatingSystem,
+	weight: f64,
 ) -> ChallengerOpponentPair<(i32, i32)>
 {
-	let old_ratings = match_outcome.players.map_ref(|player| {
+	let now = Timestamp::now();
+
+	let old_scores = match_outcome.players.map_ref(|player| {
 		leaderboard
 			.score(player.id())
-			.map_or(Score::BASE_ELO, |score| score.elo)
+			.cloned()
+			.unwrap_or_default()
 	});
+	let old_ratings = old_scores.map_ref(|score| score.elo);
 
-	let new_ratings =
-		match_outcome
-			.players
-			.as_ref()
-			.zip(old_ratings.flip())
-			.map(|(player, opponent_elo)| {
-				leaderboard.score_mut(player.id()).update_elo(
-					opponent_elo,
-					Outcome::from(match_outcome.winner().id() == player.id()),
-				)
-			});
+	let new_ratings = match_outcome
+		.players
+		.as_ref()
+		.zip(old_scores.flip())
+		.map(|(player, opponent_score)| {
+			leaderboard.score_mut(player.id()).update_weighted_rating(
+				&opponent_score,
+				Outcome::from(match_outcome.winner().id() == player.id()),
+				rating_system,
+				now,
+				weight,
+			)
+		});
 
 	leaderboard
 		.score_mut(match_outcome.winner().id())
@@ -194,60 +678,141 @@ fn update_leaderboard(
 	old_ratings.zip(new_ratings)
 }
 
+/// Waits for someone to respond to the challenge. If `opponent` is `None`, the challenge is open
+/// and may be accepted by anyone other than the challenger. Returns the id of the user who
+/// accepted, or `None` if the challenge was declined or timed out
 async fn await_challenge_accept(
 	ctx: Context<'_>,
-	challenge_message: &Message,
-	opponent: &Member,
-) -> Result<bool, Error>
+	challenge_message: &mut Message,
+	opponent: Option<&User>,
+	accept_timeout: Duration,
+) -> Result<Option<UserId>, Error>
 {
-	let accepted = loop
+	let issued_at = Instant::now();
+	let accepted_by = loop
 	{
+		let remaining = accept_timeout.saturating_sub(issued_at.elapsed());
 		let Some(interaction) = challenge_message
 			.await_component_interaction(ctx)
-			.timeout(Duration::from_secs(3600))
+			.timeout(remaining.min(CHALLENGE_TICK))
 			.await
 		else
 		{
+			if !challenges::is_pending(ctx, challenge_message.id).await
+			{
+				close_challenge(
+					ctx,
+					challenge_message,
+					format!("{} withdrew their challenge", ctx.author().mention()),
+				)
+				.await?;
+				break None;
+			}
+			if issued_at.elapsed() < accept_timeout
+			{
+				continue;
+			}
+
 			log::info!(
-				"Rps challenge {}({}) v {}({}) timed out",
+				"Rps challenge {}({}) v {} timed out",
 				ctx.author().name,
 				ctx.author().id,
-				opponent.user.name,
-				opponent.user.id
+				opponent.map_or_else(
+					|| String::from("anyone"),
+					|opponent| format!("{}({})", opponent.name, opponent.id)
+				),
 			);
-			break false;
+
+			close_challenge(
+				ctx,
+				challenge_message,
+				format!("{}'s challenge has expired", ctx.author().mention()),
+			)
+			.await?;
+			break None;
 		};
 
-		if interaction.user.id != opponent.user.id
+		match opponent
 		{
-			interaction
-				.respond_ephemeral(
-					ctx,
-					crate::error_embed("Only the challenged user may accept or decline!"),
-				)
-				.await?;
+			Some(opponent) if interaction.user.id != opponent.id =>
+			{
+				interaction
+					.respond_ephemeral(
+						ctx,
+						crate::error_embed("Only the challenged user may accept or decline!"),
+					)
+					.await?;
 
-			continue;
+				continue;
+			}
+			None if interaction.user.id == ctx.author().id =>
+			{
+				interaction
+					.respond_ephemeral(
+						ctx,
+						crate::error_embed("You can't accept your own challenge!"),
+					)
+					.await?;
+
+				continue;
+			}
+			_ => (),
 		}
 
 		match interaction.data.custom_id.as_str()
 		{
 			"rps-accept" =>
 			{
+				let accepting_user = interaction.user.id;
+				if challenges::is_busy(ctx, accepting_user).await
+				{
+					interaction
+						.respond_ephemeral(
+							ctx,
+							crate::error_embed(
+								"You're already in a game or the matchmaking queue!",
+							),
+						)
+						.await?;
+					continue;
+				}
+				// the challenger may have gone on to play something else since issuing this,
+				// which withdraws it
+				if !challenges::is_pending(ctx, challenge_message.id).await
+				{
+					interaction
+						.respond_ephemeral(
+							ctx,
+							crate::error_embed("This challenge has been withdrawn!"),
+						)
+						.await?;
+					close_challenge(
+						ctx,
+						challenge_message,
+						format!("{} withdrew their challenge", ctx.author().mention()),
+					)
+					.await?;
+					break None;
+				}
+
 				interaction
 					.respond(
 						ctx,
 						CreateEmbed::new()
 							.title("Challenge accepted!")
-							.description(format!("{} accepts the challenge!", opponent.mention()))
+							.description(format!(
+								"{} accepts the challenge!",
+								accepting_user.mention()
+							))
 							.color(crate::DEFAULT_COLOR),
 					)
 					.await?;
 
-				break true;
+				break Some(accepting_user);
 			}
-			"rps-deny" =>
+			"rps-decline" =>
 			{
+				let declining_user = interaction.user.id;
 				interaction
 					.respond(
 						ctx,
@@ -255,56 +820,111 @@ async fn await_challenge_accept(
 							.title("Challenge declined!")
 							.description(format!(
 								"{} does not accept the challenge",
-								opponent.mention()
+								declining_user.mention()
 							))
 							.color(crate::DEFAULT_COLOR),
 					)
 					.await?;
 
-				break false;
+				break None;
 			}
 			_ => continue,
 		}
 	};
-	Ok(accepted)
+	Ok(accepted_by)
+}
+
+async fn close_challenge(
+	ctx: Context<'_>,
+	challenge_message: &mut Message,
+	description: String,
+) -> Result<(), Error>
+{
+	challenge_message
+		.edit(
+			ctx,
+			EditMessage::new()
+				.embed(
+					CreateEmbed::new()
+						.title("Rock Paper Scissors")
+						.description(description)
+						.color(crate::DEFAULT_COLOR),
+				)
+				.components(vec![]),
+		)
+		.await?;
+	Ok(())
 }
 
 async fn send_challenge_message(
 	ctx: Context<'_>,
 	challenger: &User,
-	opponent: &User,
-	first_to: u32,
+	opponent: Option<&User>,
+	format: MatchFormat,
+	ranked: bool,
+	accept_timeout: Duration,
 ) -> Result<Message, Error>
 {
-	ctx.send(CreateReply::default()
-		.content(opponent.mention().to_string())
+	let match_description = format!(
+		"{}{} Rock, Paper, Scissors match",
+		if ranked { "a" } else { "a casual" },
+		(format != MatchFormat::default())
+			.then(|| format!(" **{format}**"))
+			.unwrap_or_default(),
+	);
+
+	let mut reply = CreateReply::default();
+	let mut buttons = vec![CreateButton::new("rps-accept")
+		.emoji('\u{1f44d}')
+		.label("Accept")
+		.style(ButtonStyle::Success)];
+
+	let description = if let Some(opponent) = opponent
+	{
+		reply = reply.content(opponent.mention().to_string());
+		buttons.push(
+			CreateButton::new("rps-decline")
+				.emoji('\u{1f44e}')
+				.label("Decline")
+				.style(ButtonStyle::Danger),
+		);
+
+		let mut description = format!(
+			"{} challenges {} to {match_description}!\n{}, do you accept?",
+			challenger.mention(),
+			opponent.mention(),
+			opponent.mention()
+		);
+		if ranked
+		{
+			let players = ChallengerOpponentPair::new(challenger.id, opponent.id);
+			let (stakes, _) =
+				odds::match_stakes(ctx, ctx.guild_id().expect_guild_only(), players).await;
+			let _ = write!(
+				description,
+				"\n### At Stake\n{}",
+				odds::stakes_description(players, stakes)
+			);
+		}
+		description
+	}
+	else
+	{
+		format!(
+			"{} issues an open challenge to {match_description}!\nAnyone may accept",
+			challenger.mention(),
+		)
+	};
+
+	ctx.send(reply
 		.embed(
 			CreateEmbed::new()
 				.title("Rock Paper Scissors")
-				.description(format!(
-					"{} challenges {} to a{} Rock, Paper, Scissors match!\n{}, do you accept?",
-					challenger.mention(),
-					opponent.mention(),
-					(first_to > 1)
-						.then(|| format!(" **first-to {first_to}**"))
-						.unwrap_or_default(),
-					opponent.mention()
-				))
+				.description(description)
 				.color(crate::DEFAULT_COLOR)
-				.footer(CreateEmbedFooter::new(
-					"\u{2757} Interctions will only be valid within an hour of this message being sent",
-				)),
+				.footer(timeout_footer(accept_timeout)),
 		)
-		.components(vec![CreateActionRow::Buttons(vec![
-			CreateButton::new("rps-accept")
-				.emoji('\u{1f44d}')
-				.label("Accept")
-				.style(ButtonStyle::Success),
-			CreateButton::new("rps-decline")
-				.emoji('\u{1f44e}')
-				.label("Decline")
-				.style(ButtonStyle::Danger),
-		])])
+		.components(vec![CreateActionRow::Buttons(buttons)])
 		.reply(true)
 		.allowed_mentions(CreateAllowedMentions::new()))
 	.await?
@@ -315,65 +935,130 @@ async fn send_challenge_message(
 
 async fn start_game(
 	ctx: Context<'_>,
-	game: &mut Game<Option<Selection>>,
+	game: &mut Game,
 	members: &ChallengerOpponentPair<Member>,
 	channel: &GuildChannel,
-	is_bot_match: bool,
+	bot_difficulty: Option<BotDifficulty>,
+	round_timeout: Duration,
 ) -> Result<Option<MatchOutcome>, Error>
 {
+	// the bot picks before the player does, so it commits to its pick up front to prove it isn't
+	// peeking
+	let bot_commitment = |game: &mut Game| {
+		bot_difficulty.map(|difficulty| {
+			let selection = bot::choose_selection(difficulty, game.history());
+			game[Side::Opponent].select(selection);
+			Commitment::new(selection)
+		})
+	};
+
+	let mut scoreboard = Scoreboard::new(members, round_timeout);
+	scoreboard.commit(bot_commitment(game));
+	let mut match_message = channel
+		.send_message(
+			ctx,
+			scoreboard
+				.message(game)
+				.allowed_mentions(CreateAllowedMentions::new()),
+		)
+		.await?;
+
 	let match_outcome = loop
 	{
-		if is_bot_match
-		{
-			game[Side::Opponent].select(rand::thread_rng().gen());
-		}
-		let selection_message = channel
-			.send_message(ctx, SELECTION_MESSAGE_TEMPLATE.clone())
-			.await?;
-		let Some(round_outcome) = await_selections(ctx, &selection_message, game).await?
-		else
+		match await_selections(
+			ctx,
+			&mut match_message,
+			game,
+			members,
+			&mut scoreboard,
+			round_timeout,
+		)
+		.await?
 		{
-			break None;
-		};
-
-		channel
-			.send_message(
-				ctx,
-				CreateMessage::new().embed(round_outcome.winner_embed(members)),
-			)
-			.await?;
+			SelectionResult::Round(round_outcome) =>
+			{
+				scoreboard.finish_round(&round_outcome);
+				let match_outcome = round_outcome.try_delcare_match();
+				if let Some(match_outcome) = &match_outcome
+				{
+					scoreboard.conclude(format!(
+						"{} wins round {} and the match!",
+						members[match_outcome.winning_side()].mention(),
+						round_outcome.round_count()
+					));
+				}
+				else
+				{
+					scoreboard.commit(bot_commitment(game));
+				}
+				match_message.edit(ctx, scoreboard.edit(game)).await?;
 
-		if let Some(match_outcome) = round_outcome.try_delcare_match()
-		{
-			break Some(match_outcome);
+				if match_outcome.is_some()
+				{
+					break match_outcome;
+				}
+			}
+			SelectionResult::Forfeit(forfeiting_side) => break Some(game.forfeit(forfeiting_side)),
+			SelectionResult::Abandoned => break None,
 		}
 	};
 
 	Ok(match_outcome)
 }
 
+enum SelectionResult
+{
+	Round(RoundOutcome),
+	Forfeit(Side),
+	Abandoned,
+}
+
+/// Collects both players' selections for the round, updating the scoreboard as they come in
 async fn await_selections(
 	ctx: Context<'_>,
-	selection_message: &Message,
+	match_message: &mut Message,
 	game: &mut Game<Option<Selection>>,
-) -> Result<Option<RoundOutcome>, Error>
+	members: &ChallengerOpponentPair<Member>,
+	scoreboard: &mut Scoreboard<'_>,
+	round_timeout: Duration,
+) -> Result<SelectionResult, Error>
 {
-	let outcome = loop
+	let result = loop
 	{
-		let Some(interaction) = selection_message
+		let Some(interaction) = match_message
 			.await_component_interaction(ctx)
-			.timeout(Duration::from_secs(3600))
+			.timeout(round_timeout)
 			.await
 		else
 		{
 			log::info!(
 				"Rps game {}({}) v {}({}) timed out",
-				ctx.http().get_user(game[Side::Challenger].id()).await?.name,
-				game[Side::Challenger].id(),
-				ctx.http().get_user(game[Side::Opponent].id()).await?.name,
-				game[Side::Opponent].id(),
+				members.challenger.user.name,
+				members.challenger.user.id,
+				members.opponent.user.name,
+				members.opponent.user.id,
 			);
-			break None;
+
+			// if only one player has selected, the other is clearly stalling and gets penalized
+			// for it. if nobody selected, nobody is really at fault so the match is just dropped
+			let stalling_side = game.stalling_side();
+			scoreboard.conclude(stalling_side.map_or_else(
+				|| {
+					String::from(
+						"Time's up! Neither player made a selection in time. The match has been \
+						 abandoned",
+					)
+				},
+				|side| {
+					format!(
+						"Time's up! {} didn't make a selection in time and forfeits the match",
+						members[side].mention()
+					)
+				},
+			));
+			match_message.edit(ctx, scoreboard.edit(game)).await?;
+
+			break stalling_side.map_or(SelectionResult::Abandoned, SelectionResult::Forfeit);
 		};
 
 		let Some(side) = game.side_of(interaction.user.id)
@@ -388,6 +1073,19 @@ async fn await_selections(
 			continue;
 		};
 
+		if interaction.data.custom_id == "rps-forfeit"
+		{
+			scoreboard.conclude(format!(
+				"{} forfeits the match!",
+				interaction.user.id.mention()
+			));
+			interaction
+				.create_response(ctx, scoreboard.update(game))
+				.await?;
+
+			break SelectionResult::Forfeit(side);
+		}
+
 		if game[side].has_selected()
 		{
 			interaction
@@ -396,39 +1094,38 @@ async fn await_selections(
 			continue;
 		}
 
-		let selection = match interaction.data.custom_id.as_str()
+		let Some(selection) = model::selection_from_button(&interaction.data.custom_id)
+		else
 		{
-			"rock" => Selection::Rock,
-			"paper" => Selection::Paper,
-			"scissors" => Selection::Scissors,
-			_ => continue, // this should never happen? -morgan 2024-01-18
+			continue;
 		};
 		game[side].select(selection);
 
-		interaction
-			.respond_ephemeral(
-				ctx,
-				CreateEmbed::new()
-					.title("Selection made!")
-					.description(format!("You have selected {selection}"))
-					.color(crate::DEFAULT_COLOR),
-			)
-			.await?;
+		// the scoreboard is edited once the round is played, so the last selection of the round
+		// only needs acknowledging
+		let round_outcome = game.try_delcare_round();
+		let update = round_outcome.is_none().then(|| scoreboard.update(game));
+		confirm_selection(ctx, &interaction, update, selection).await?;
 
-		if let Some(round_outcome) = game.try_delcare_round()
+		if let Some(round_outcome) = round_outcome
 		{
-			break Some(round_outcome);
+			break SelectionResult::Round(round_outcome);
 		}
 	};
 
-	Ok(outcome)
+	Ok(result)
 }
 
-async fn start_bot_match(ctx: Context<'_>, first_to: u32) -> Result<(), Error>
+async fn start_bot_match(
+	ctx: Context<'_>,
+	format: MatchFormat,
+	ranked: bool,
+	difficulty: BotDifficulty,
+) -> Result<(), Error>
 {
 	let guild_id = ctx.guild_id().expect_guild_only();
 
-	let mut game = Game::start(ctx.author().id, ctx.framework().bot_id, first_to);
+	let mut game = Game::start(ctx.author().id, ctx.framework().bot_id, format);
 	let members = ChallengerOpponentPair::new(
 		ctx.http()
 			.get_member(guild_id, game.challenger().id())
@@ -445,8 +1142,9 @@ async fn start_bot_match(ctx: Context<'_>, first_to: u32) -> Result<(), Error>
 				CreateEmbed::new()
 					.title("Challenge accepted!")
 					.description(format!(
-						"I accept {}'s challenge!",
-						members.challenger.mention()
+						"I accept {}'s challenge! I'll be playing on **{}**",
+						members.challenger.mention(),
+						difficulty.name()
 					))
 					.color(crate::DEFAULT_COLOR),
 			)
@@ -455,13 +1153,52 @@ async fn start_bot_match(ctx: Context<'_>, first_to: u32) -> Result<(), Error>
 	)
 	.await?;
 
+	let round_timeout = rps_settings(ctx).await.round_timeout();
 	// a little wet but thats ok i for now i think -morgan 2024-05-30
-	if let Some(match_outcome) = start_game(ctx, &mut game, &members, &channel, true).await?
+	if let Some(match_outcome) =
+		start_game(ctx, &mut game, &members, &channel, Some(difficulty), round_timeout).await?
 	{
+		let embed = if ranked
+		{
+			let (rating_change, streak) = {
+				let mut data = ctx.data().acquire_lock().await;
+				let record = data
+					.guild_data_mut(guild_id)
+					.bot_ladder_mut()
+					.record_mut(match_outcome.challenger().id());
+
+				let rating_change = record.record_match(
+					difficulty,
+					Outcome::from(match_outcome.winning_side() == Side::Challenger),
+				);
+				(rating_change, record[difficulty].streak)
+			};
+
+			let bot_rating = difficulty.rating();
+			let mut embed = create_match_embed(
+				ctx,
+				&match_outcome,
+				&members,
+				Some(ChallengerOpponentPair::new(
+					rating_change,
+					(bot_rating, bot_rating),
+				)),
+			);
+			if streak > 1
+			{
+				embed = embed.field("Win streak", streak.to_string(), true);
+			}
+			embed
+		}
+		else
+		{
+			create_match_embed(ctx, &match_outcome, &members, None).footer(casual_footer())
+		};
+
 		channel
 			.send_message(
 				ctx,
-				CreateMessage::new().embed(create_match_embed(ctx, &match_outcome, &members, None)),
+				CreateMessage::new().embed(embed.field("Difficulty", difficulty.name(), true)),
 			)
 			.await?;
 	}
@@ -469,18 +1206,97 @@ async fn start_bot_match(ctx: Context<'_>, first_to: u32) -> Result<(), Error>
 	Ok(())
 }
 
-lazy_static! {
-	static ref SELECTION_MESSAGE_TEMPLATE: CreateMessage = CreateMessage::new()
-		.embed(
-			CreateEmbed::new()
-				.title("Make your selection!")
-				.description("Pick rock, paper, or, scissors")
-				.color(crate::DEFAULT_COLOR)
-				.footer(CreateEmbedFooter::new(
-					"\u{2757} Interctions will only be valid within an hour of this message being sent",
-				)),
+/// Responds to a selection on a game's board, updating the board with `update`, or just
+/// acknowledging it if the board is about to be edited anyway, then privately tells the player
+/// what they picked
+async fn confirm_selection(
+	ctx: Context<'_>,
+	interaction: &ComponentInteraction,
+	update: Option<CreateInteractionResponse>,
+	selection: Selection,
+) -> Result<(), Error>
+{
+	interaction
+		.create_response(
+			ctx,
+			update.unwrap_or(CreateInteractionResponse::Acknowledge),
+		)
+		.await?;
+	interaction
+		.create_followup(
+			ctx,
+			CreateInteractionResponseFollowup::new()
+				.embed(
+					CreateEmbed::new()
+						.title("Selection made!")
+						.description(format!("You have selected {selection}"))
+						.color(crate::DEFAULT_COLOR),
+				)
+				.ephemeral(true),
 		)
-		.components(vec![CreateActionRow::Buttons(
-			Selection::map_all(Selection::button).collect(),
-		)]);
+		.await?;
+
+	Ok(())
+}
+
+fn selection_buttons(disabled: bool) -> Vec<CreateActionRow>
+{
+	vec![CreateActionRow::Buttons(
+		Selection::map_all(|selection| model::selection_button(selection).disabled(disabled))
+			.chain(std::iter::once(
+				CreateButton::new("rps-forfeit")
+					.emoji('\u{1f3f3}')
+					.label("Forfeit")
+					.style(ButtonStyle::Danger)
+					.disabled(disabled),
+			))
+			.collect(),
+	)]
+}
+
+/// Accept and decline buttons, for anything players have to agree to
+fn response_buttons() -> Vec<CreateActionRow>
+{
+	vec![CreateActionRow::Buttons(vec![
+		CreateButton::new("rps-accept")
+			.emoji('\u{1f44d}')
+			.label("Accept")
+			.style(ButtonStyle::Success),
+		CreateButton::new("rps-decline")
+			.emoji('\u{1f44e}')
+			.label("Decline")
+			.style(ButtonStyle::Danger),
+	])]
+}
+
+fn mention_list(players: &[UserId]) -> String
+{
+	players
+		.iter()
+		.map(|player| player.mention().to_string())
+		.collect::<Vec<_>>()
+		.join(", ")
+}
+
+fn casual_footer() -> CreateEmbedFooter
+{
+	CreateEmbedFooter::new("Casual match: ratings were not affected")
+}
+
+fn timeout_footer(timeout: Duration) -> CreateEmbedFooter
+{
+	CreateEmbedFooter::new(format!(
+		"\u{2757} Interactions will only be valid within {} of this message being sent",
+		format_duration(timeout)
+	))
+}
+
+async fn rps_settings(ctx: Context<'_>) -> RpsSettings
+{
+	ctx.data()
+		.acquire_lock()
+		.await
+		.guild_data(ctx.guild_id().expect_guild_only())
+		.map(|dat| dat.rps_settings().clone())
+		.unwrap_or_default()
 }
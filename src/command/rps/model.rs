@@ -1,8 +1,8 @@
 use std::{cmp::Ordering, fmt::Display};
 
-use poise::serenity_prelude::{CreateButton, CreateEmbed, Member, Mentionable, UserId};
-use strum::IntoEnumIterator;
-use strum_macros::{EnumIter, IntoStaticStr};
+use poise::serenity_prelude::{CreateButton, UserId};
+
+pub use crate::data::Selection;
 
 pub type RoundOutcome = Game<Selection>;
 
@@ -10,8 +10,9 @@ pub type RoundOutcome = Game<Selection>;
 pub struct Game<S = Option<Selection>>
 {
 	players: ChallengerOpponentPair<Player<S>>,
-	first_to: u32,
+	format: MatchFormat,
 	round_count: u32,
+	history: Vec<ChallengerOpponentPair<Selection>>,
 }
 impl<S> Game<S>
 {
@@ -24,6 +25,32 @@ impl<S> Game<S>
 		&self.players.opponent
 	}
 
+	/// Every decided round so far, in order
+	pub fn history(&self) -> &[ChallengerOpponentPair<Selection>]
+	{
+		&self.history
+	}
+
+	pub fn format(&self) -> MatchFormat
+	{
+		self.format
+	}
+
+	/// The round being played, or the round that was played for a round outcome
+	pub fn round_count(&self) -> u32
+	{
+		self.round_count
+	}
+
+	/// Whether the next decided round will end the match no matter who wins it
+	pub fn is_sudden_death(&self) -> bool
+	{
+		let (challenger_score, opponent_score) = (self.challenger().score, self.opponent().score);
+		challenger_score == opponent_score
+			&& challenger_score > 0
+			&& self.format.is_decided(challenger_score + 1, opponent_score)
+	}
+
 	pub fn side_of(&self, id: UserId) -> Option<Side>
 	{
 		if id == self.challenger().id()
@@ -55,20 +82,46 @@ impl<S: Copy> Game<S>
 	pub fn try_delcare_match(&self) -> Option<MatchOutcome>
 	{
 		self.current_winner()
-			.is_some_and(|winner| self[winner].score >= self.first_to)
+			.is_some_and(|winner| {
+				self.format
+					.is_decided(self[winner].score, self[!winner].score)
+			})
 			.then(|| MatchOutcome::from_game(self))
 	}
+
+	pub fn forfeit(&self, forfeiting_side: Side) -> MatchOutcome
+	{
+		MatchOutcome {
+			forfeited_by: Some(forfeiting_side),
+			..MatchOutcome::from_game(self)
+		}
+	}
 }
 impl Game
 {
-	pub fn start(challenger: UserId, opponent: UserId, first_to: u32) -> Self
+	pub fn start(challenger: UserId, opponent: UserId, format: MatchFormat) -> Self
 	{
 		Self {
 			players: ChallengerOpponentPair::generate(challenger, opponent, Player::new),
 			// challenger: Player::new(challenger),
 			// opponent: Player::new(opponent),
-			first_to,
+			format,
 			round_count: 1,
+			history: Vec::new(),
+		}
+	}
+
+	/// The side that has yet to make a selection while the other side already has, if any
+	pub fn stalling_side(&self) -> Option<Side>
+	{
+		match (
+			self.challenger().has_selected(),
+			self.opponent().has_selected(),
+		)
+		{
+			(false, true) => Some(Side::Challenger),
+			(true, false) => Some(Side::Opponent),
+			_ => None,
 		}
 	}
 
@@ -79,14 +132,17 @@ impl Game
 			.map_ref(|player| player.selection)
 			.zipped()
 			.map(|(challenger_sel, opponent_sel)| {
+				self.history
+					.push(ChallengerOpponentPair::new(challenger_sel, opponent_sel));
 				let mut outcome = Game {
 					players: self.players.clone().gen_map(
 						challenger_sel,
 						opponent_sel,
 						Player::with_selection,
 					),
-					first_to: self.first_to,
+					format: self.format,
 					round_count: self.round_count,
+					history: self.history.clone(),
 				};
 
 				self.round_count += 1;
@@ -121,42 +177,6 @@ impl RoundOutcome
 			| (Selection::Scissors, Selection::Scissors) => None,
 		}
 	}
-
-	pub fn winner_embed(&self, members: &ChallengerOpponentPair<Member>) -> CreateEmbed
-	{
-		self.winner()
-			.map_or_else(
-				|| {
-					CreateEmbed::new()
-						.description("# It's a tie!")
-						.color(crate::DEFAULT_COLOR)
-				},
-				|winning_side| {
-					let winner = &members[winning_side];
-					CreateEmbed::new()
-						.description(format!("# {} wins!", winner.mention(),))
-						.color(winner.user.accent_colour.unwrap_or(crate::DEFAULT_COLOR))
-						.thumbnail(winner.face())
-				},
-			)
-			.title(format!("Round {}", self.round_count))
-			.field(
-				"Selections",
-				format!(
-					"{} chose {}\n{} chose {}",
-					members.challenger.mention(),
-					self.challenger().selection,
-					members.opponent.mention(),
-					self.opponent().selection,
-				),
-				false,
-			)
-			.field(
-				"Score",
-				format!("{}-{}", self.challenger().score, self.opponent().score),
-				true,
-			)
-	}
 }
 
 impl<S> std::ops::Index<Side> for Game<S>
@@ -187,6 +207,7 @@ impl<S> std::ops::IndexMut<Side> for Game<S>
 pub struct MatchOutcome
 {
 	pub players: ChallengerOpponentPair<Player<()>>,
+	forfeited_by: Option<Side>,
 }
 impl MatchOutcome
 {
@@ -194,6 +215,7 @@ impl MatchOutcome
 	{
 		Self {
 			players: game.players.map_ref(|player| player.clone().map_to(())),
+			forfeited_by: None,
 		}
 	}
 
@@ -206,9 +228,18 @@ impl MatchOutcome
 		&self.players.opponent
 	}
 
+	pub fn forfeited_by(&self) -> Option<Side>
+	{
+		self.forfeited_by
+	}
+
 	pub fn winning_side(&self) -> Side
 	{
-		if self.challenger().score > self.opponent().score
+		if let Some(forfeiting_side) = self.forfeited_by
+		{
+			!forfeiting_side
+		}
+		else if self.challenger().score > self.opponent().score
 		{
 			Side::Challenger
 		}
@@ -232,6 +263,121 @@ impl MatchOutcome
 	}
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatchFormat
+{
+	/// the first player to win this many rounds wins the match
+	FirstTo(u32),
+	/// whoever wins the most out of this many rounds wins the match, which ends as soon as the
+	/// trailing player can no longer catch up
+	BestOf(u32),
+	/// the first player to win this many rounds while at least two rounds ahead wins the match
+	WinByTwo(u32),
+}
+impl MatchFormat
+{
+	/// how many rounds past the target a win-by-two match can go before the next round decides it
+	const WIN_BY_TWO_EXTENSION: u32 = 3;
+
+	pub fn new(kind: MatchFormatKind, rounds: u32) -> Self
+	{
+		match kind
+		{
+			MatchFormatKind::FirstTo => Self::FirstTo(rounds),
+			MatchFormatKind::BestOf => Self::BestOf(rounds),
+			MatchFormatKind::WinByTwo => Self::WinByTwo(rounds),
+		}
+	}
+
+	/// Builds a format from the `first_to` and `rounds` command options. Best Of goes by
+	/// `rounds` and the others by `first_to`, but whichever was given is used if only one was
+	pub fn from_options(kind: MatchFormatKind, first_to: Option<u32>, rounds: Option<u32>) -> Self
+	{
+		let count = match kind
+		{
+			MatchFormatKind::BestOf => rounds.or(first_to),
+			MatchFormatKind::FirstTo | MatchFormatKind::WinByTwo => first_to.or(rounds),
+		};
+		Self::new(kind, count.unwrap_or(1))
+	}
+
+	/// The same format, played for one more win. Best-of matches go up by two so they stay odd
+	/// if they were
+	pub fn extended(self) -> Self
+	{
+		match self
+		{
+			Self::FirstTo(rounds) => Self::FirstTo(rounds + 1),
+			Self::BestOf(rounds) => Self::BestOf(rounds + 2),
+			Self::WinByTwo(rounds) => Self::WinByTwo(rounds + 1),
+		}
+	}
+
+	pub fn is_decided(self, leading_score: u32, trailing_score: u32) -> bool
+	{
+		match self
+		{
+			Self::FirstTo(rounds) => leading_score >= rounds,
+			// a tie after every round of an even best-of is broken by the next round, which also
+			// gives the leader a majority
+			Self::BestOf(rounds) => leading_score * 2 > rounds,
+			Self::WinByTwo(rounds) =>
+			{
+				leading_score >= rounds
+					&& (leading_score - trailing_score >= 2
+						|| leading_score >= rounds + Self::WIN_BY_TWO_EXTENSION)
+			}
+		}
+	}
+
+	/// The tied score at which the next round decides the match, for formats that can otherwise
+	/// go on past their target
+	fn sudden_death_score(self) -> Option<u32>
+	{
+		match self
+		{
+			Self::FirstTo(_) => None,
+			Self::BestOf(rounds) => (rounds % 2 == 0).then_some(rounds / 2),
+			Self::WinByTwo(rounds) => Some(rounds + Self::WIN_BY_TWO_EXTENSION - 1),
+		}
+	}
+}
+impl Default for MatchFormat
+{
+	fn default() -> Self
+	{
+		Self::FirstTo(1)
+	}
+}
+impl Display for MatchFormat
+{
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result
+	{
+		match self
+		{
+			Self::FirstTo(rounds) => write!(f, "first-to {rounds}")?,
+			Self::BestOf(rounds) => write!(f, "best-of {rounds}")?,
+			Self::WinByTwo(rounds) => write!(f, "first-to {rounds}, win by two")?,
+		}
+
+		self.sudden_death_score().map_or(Ok(()), |score| {
+			write!(f, " (sudden death at {score}-{score})")
+		})
+	}
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, poise::ChoiceParameter)]
+pub enum MatchFormatKind
+{
+	#[default]
+	#[name = "First To"]
+	FirstTo,
+	#[name = "Best Of"]
+	BestOf,
+	#[name = "Win By Two"]
+	WinByTwo,
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct ChallengerOpponentPair<T>
 {
@@ -305,6 +451,21 @@ impl<T> ChallengerOpponentPair<T>
 		Self::new(self.opponent, self.challenger)
 	}
 }
+impl<T: PartialEq> ChallengerOpponentPair<T>
+{
+	/// Whichever of the pair isn't `value`
+	pub fn other(&self, value: &T) -> &T
+	{
+		if self.challenger == *value
+		{
+			&self.opponent
+		}
+		else
+		{
+			&self.challenger
+		}
+	}
+}
 impl<T> ChallengerOpponentPair<Option<T>>
 {
 	pub fn zipped(self) -> Option<(T, T)>
@@ -325,6 +486,17 @@ impl<T> std::ops::Index<Side> for ChallengerOpponentPair<T>
 		}
 	}
 }
+impl<T> std::ops::IndexMut<Side> for ChallengerOpponentPair<T>
+{
+	fn index_mut(&mut self, index: Side) -> &mut Self::Output
+	{
+		match index
+		{
+			Side::Challenger => &mut self.challenger,
+			Side::Opponent => &mut self.opponent,
+		}
+	}
+}
 impl<T, U> std::ops::Sub<ChallengerOpponentPair<U>> for ChallengerOpponentPair<T>
 where
 	T: std::ops::Sub<U>,
@@ -425,59 +597,110 @@ impl std::ops::Not for Side
 	}
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, IntoStaticStr, EnumIter)]
-pub enum Selection
+pub fn selection_button(selection: Selection) -> CreateButton
 {
-	Rock,
-	Paper,
-	Scissors,
+	CreateButton::new(selection.as_str().to_lowercase())
+		.label(selection.as_str())
+		.emoji(selection.emoji())
 }
-impl Selection
+
+/// The selection a button from [`selection_button`] was made for
+pub fn selection_from_button(custom_id: &str) -> Option<Selection>
 {
-	pub fn map_all<T>(f: impl FnMut(Self) -> T) -> impl Iterator<Item = T>
+	Selection::map_all(|selection| selection)
+		.find(|selection| selection.as_str().eq_ignore_ascii_case(custom_id))
+}
+
+#[cfg(test)]
+mod tests
+{
+	use super::{MatchFormat, MatchFormatKind};
+
+	#[test]
+	fn first_to_is_decided_at_target()
 	{
-		Self::iter().map(f)
+		let format = MatchFormat::FirstTo(3);
+		assert!(!format.is_decided(2, 2));
+		assert!(format.is_decided(3, 2));
+		assert!(format.is_decided(3, 0));
+		assert_eq!(format.sudden_death_score(), None);
 	}
 
-	pub fn emoji(self) -> char
+	#[test]
+	fn best_of_ends_once_the_lead_cant_be_caught()
 	{
-		match self
-		{
-			Self::Rock => '\u{270a}',
-			Self::Paper => '\u{1f590}',
-			Self::Scissors => '\u{270c}',
-		}
+		let format = MatchFormat::BestOf(5);
+		assert!(!format.is_decided(2, 2));
+		assert!(format.is_decided(3, 0));
+		assert!(format.is_decided(3, 2));
+		assert_eq!(format.sudden_death_score(), None);
 	}
 
-	pub fn as_str(self) -> &'static str
+	#[test]
+	fn even_best_of_goes_to_sudden_death()
 	{
-		self.into()
+		let format = MatchFormat::BestOf(4);
+		assert!(!format.is_decided(2, 1));
+		assert!(!format.is_decided(2, 2));
+		assert!(format.is_decided(3, 1));
+		assert!(format.is_decided(3, 2));
+		assert_eq!(format.sudden_death_score(), Some(2));
 	}
 
-	pub fn button(self) -> CreateButton
+	#[test]
+	fn win_by_two_needs_a_two_round_lead()
 	{
-		CreateButton::new(self.as_str().to_lowercase())
-			.label(self.as_str())
-			.emoji(self.emoji())
+		let format = MatchFormat::WinByTwo(3);
+		assert!(!format.is_decided(2, 0));
+		assert!(format.is_decided(3, 1));
+		assert!(!format.is_decided(3, 2));
+		assert!(format.is_decided(4, 2));
+		assert!(!format.is_decided(5, 4));
 	}
-}
-impl rand::distributions::Distribution<Selection> for rand::distributions::Standard
-{
-	fn sample<R: rand::prelude::Rng + ?Sized>(&self, rng: &mut R) -> Selection
+
+	#[test]
+	fn long_win_by_two_goes_to_sudden_death()
+	{
+		let format = MatchFormat::WinByTwo(3);
+		assert_eq!(format.sudden_death_score(), Some(5));
+		assert!(format.is_decided(6, 5));
+	}
+
+	/// the sudden death score is the first tie where the next round decides the match
+	#[test]
+	fn sudden_death_score_matches_is_decided()
 	{
-		match rng.gen_range(0..3)
+		for format in [
+			MatchFormat::BestOf(2),
+			MatchFormat::BestOf(6),
+			MatchFormat::WinByTwo(1),
+			MatchFormat::WinByTwo(4),
+		]
 		{
-			0 => Selection::Rock,
-			1 => Selection::Paper,
-			2 => Selection::Scissors,
-			_ => panic!("Rng generated an out-of-bounds value trying to create a Selection!"),
+			let score = format.sudden_death_score().unwrap();
+			assert!(format.is_decided(score + 1, score), "{format}");
+			assert!(!format.is_decided(score, score - 1), "{format}");
 		}
 	}
-}
-impl Display for Selection
-{
-	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result
-	{
-		write!(f, "{} {}", (*self).as_str(), (*self).emoji())
+
+	#[test]
+	fn options_go_to_the_format_they_belong_to()
+	{
+		assert_eq!(
+			MatchFormat::from_options(MatchFormatKind::FirstTo, Some(3), Some(5)),
+			MatchFormat::FirstTo(3)
+		);
+		assert_eq!(
+			MatchFormat::from_options(MatchFormatKind::BestOf, Some(3), Some(5)),
+			MatchFormat::BestOf(5)
+		);
+		assert_eq!(
+			MatchFormat::from_options(MatchFormatKind::BestOf, Some(3), None),
+			MatchFormat::BestOf(3)
+		);
+		assert_eq!(
+			MatchFormat::from_options(MatchFormatKind::WinByTwo, None, None),
+			MatchFormat::WinByTwo(1)
+		);
 	}
 }
@@ -0,0 +1,132 @@
+use std::fmt::Write;
+
+use poise::{
+	serenity_prelude::{
+		CreateAllowedMentions, CreateEmbed, GuildId, Member, Mentionable, Timestamp, UserId,
+	},
+	CreateReply,
+};
+
+use super::model::ChallengerOpponentPair;
+use crate::{
+	command::ExpectGuildOnly,
+	data::RatingStakes,
+	Context, Error, Reply,
+};
+
+/// See what's at stake in a ranked game of Rock, Paper, Scissors against another player
+#[poise::command(slash_command, prefix_command, guild_only)]
+pub async fn odds(
+	ctx: Context<'_>,
+	#[description = "The player to play against"] user: Member,
+) -> Result<(), Error>
+{
+	if user.user.id == ctx.author().id
+	{
+		ctx.reply_error("You can't play against yourself!").await?;
+		return Ok(());
+	}
+	if user.user.bot
+	{
+		ctx.reply_error("Matches against bots don't affect your rating!")
+			.await?;
+		return Ok(());
+	}
+
+	let players = ChallengerOpponentPair::new(ctx.author().id, user.user.id);
+	let (stakes, weight) = match_stakes(ctx, ctx.guild_id().expect_guild_only(), players).await;
+
+	let mut embed = CreateEmbed::new()
+		.title("Rock Paper Scissors Odds")
+		.description(format!(
+			"# {} vs {}\n{}",
+			players.challenger.mention(),
+			players.opponent.mention(),
+			stakes_description(players, stakes)
+		))
+		.color(crate::DEFAULT_COLOR);
+	if weight < 1.0
+	{
+		embed = embed.field(
+			"Repeat Matches",
+			format!(
+				"You've played each other recently, so a match now only counts for {:.0}% of \
+				 the usual rating",
+				weight * 100.0
+			),
+			false,
+		);
+	}
+
+	ctx.send(
+		CreateReply::default()
+			.embed(embed)
+			.reply(true)
+			.allowed_mentions(CreateAllowedMentions::new()),
+	)
+	.await?;
+
+	Ok(())
+}
+
+/// What a ranked match between `players` would be worth to each of them if it were played now,
+/// along with how much it counts for after playing each other recently
+pub(super) async fn match_stakes(
+	ctx: Context<'_>,
+	guild_id: GuildId,
+	players: ChallengerOpponentPair<UserId>,
+) -> (ChallengerOpponentPair<RatingStakes>, f64)
+{
+	let data = ctx.data().acquire_lock().await;
+	let guild_data = data.guild_data(guild_id);
+	let rating_system = guild_data
+		.map(|guild_data| guild_data.rps_settings().rating_system())
+		.unwrap_or_default();
+	let now = Timestamp::now();
+	let weight = guild_data.map_or(1.0, |guild_data| {
+		guild_data
+			.match_history()
+			.repeat_weight(players.challenger, players.opponent, now)
+	});
+
+	let scores = players.map(|player| {
+		guild_data
+			.and_then(|guild_data| guild_data.leaderboard().score(player))
+			.cloned()
+			.unwrap_or_default()
+	});
+	let stakes = ChallengerOpponentPair::new(
+		scores
+			.challenger
+			.stakes(&scores.opponent, rating_system, now, weight),
+		scores
+			.opponent
+			.stakes(&scores.challenger, rating_system, now, weight),
+	);
+
+	(stakes, weight)
+}
+
+/// Each player's chance of winning and how much they'd gain or lose, one line each
+pub(super) fn stakes_description(
+	players: ChallengerOpponentPair<UserId>,
+	stakes: ChallengerOpponentPair<RatingStakes>,
+) -> String
+{
+	let mut description = String::new();
+	for (player, stakes) in [
+		(players.challenger, stakes.challenger),
+		(players.opponent, stakes.opponent),
+	]
+	{
+		let _ = writeln!(
+			description,
+			"{}: **{:.0}%** to win, **{:+}** for a win, **{:+}** for a loss",
+			player.mention(),
+			stakes.win_probability * 100.0,
+			stakes.gain,
+			stakes.loss,
+		);
+	}
+	description
+}
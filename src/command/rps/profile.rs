@@ -0,0 +1,214 @@
+use std::fmt::Write;
+
+use poise::{
+	serenity_prelude::{CreateAllowedMentions, CreateEmbed, Member, Mentionable, UserId},
+	CreateReply,
+};
+
+use super::leaderboard::leaderboard_rank;
+use crate::{
+	command::ExpectGuildOnly,
+	data::{Outcome, PlayerStats, Score, Selection},
+	Context, Error, Reply,
+};
+
+/// View detailed Rock, Paper, Scissors statistics for yourself or another player
+#[poise::command(slash_command, prefix_command, guild_only)]
+pub async fn profile(
+	ctx: Context<'_>,
+	#[description = "The player to view (default: you)"] user: Option<Member>,
+) -> Result<(), Error>
+{
+	let guild = ctx.partial_guild().await.expect_guild_only();
+	let target_id = user.map_or_else(|| ctx.author().id, |member| member.user.id);
+	let target_member = ctx.http().get_member(guild.id, target_id).await?;
+
+	let profile = ctx
+		.data()
+		.acquire_lock()
+		.await
+		.guild_data(guild.id)
+		.and_then(|guild_data| {
+			let score = guild_data
+				.leaderboard()
+				.score(target_id)
+				.filter(|score| score.total_games() > 0);
+			let stats = guild_data.match_history().player_stats(target_id);
+			if score.is_none() && stats.recent_form.is_empty()
+			{
+				return None;
+			}
+
+			Some((
+				score.map(|score| {
+					(
+						score.clone(),
+						leaderboard_rank(&target_member, &guild, guild_data),
+						score.rating_string(guild_data.rps_settings().rating_system()),
+					)
+				}),
+				stats,
+			))
+		});
+
+	let Some((ranking, stats)) = profile
+	else
+	{
+		ctx.reply_error(format!(
+			"{} hasn't played any rock paper scissors yet!",
+			target_member.mention()
+		))
+		.await?;
+		return Ok(());
+	};
+
+	ctx.send(
+		CreateReply::default()
+			.embed(create_profile_embed(
+				&target_member,
+				&guild.name,
+				ranking.as_ref(),
+				&stats,
+			))
+			.reply(true)
+			.allowed_mentions(CreateAllowedMentions::new()),
+	)
+	.await?;
+
+	Ok(())
+}
+
+/// `ranking` is the player's score, rank, and displayed rating, if they've played ranked matches
+fn create_profile_embed(
+	target_member: &Member,
+	guild_name: &str,
+	ranking: Option<&(Score, usize, String)>,
+	stats: &PlayerStats,
+) -> CreateEmbed
+{
+	let mut embed = CreateEmbed::new()
+		.title("Rock Paper Scissors Profile")
+		.description(format!("# {}", target_member.mention()))
+		.color(
+			target_member
+				.user
+				.accent_colour
+				.unwrap_or(crate::DEFAULT_COLOR),
+		)
+		.thumbnail(target_member.face());
+
+	if let Some((score, rank, rating)) = ranking
+	{
+		embed = embed
+			.field("Rank", format!("#{rank} in {guild_name}"), true)
+			.field("Rating", format!("{rating} (peak {})", score.peak_elo()), true)
+			.field(
+				"Record",
+				format!(
+					"{}-{} ({:.2}%)",
+					score.wins,
+					score.losses,
+					score.win_rate() * 100.0
+				),
+				true,
+			)
+			.field("Streaks", streak_string(score), true);
+	}
+	else
+	{
+		embed = embed.field("Rank", "Unranked (only casual matches played)", false);
+	}
+
+	if stats.total_rounds() > 0
+	{
+		embed = embed
+			.field("Throws", throws_string(stats), true)
+			.field(
+				"Round Win Rate",
+				format!(
+					"{:.2}% ({}-{}, {} tied)",
+					stats.round_win_rate() * 100.0,
+					stats.rounds_won,
+					stats.rounds_lost,
+					stats.rounds_tied
+				),
+				true,
+			);
+	}
+
+	embed
+		.field(
+			"Favorite Opponent",
+			opponent_string(stats.favorite_opponent, "match", "matches"),
+			true,
+		)
+		.field(
+			"Nemesis",
+			opponent_string(stats.nemesis, "loss", "losses"),
+			true,
+		)
+		.field("Recent Form", form_string(&stats.recent_form), false)
+}
+
+fn streak_string(score: &Score) -> String
+{
+	let current = match score.streak
+	{
+		0 => String::from("None"),
+		1 => String::from("1 win"),
+		-1 => String::from("1 loss"),
+		wins @ 2.. => format!("{wins} wins"),
+		losses => format!("{} losses", losses.unsigned_abs()),
+	};
+
+	format!("Current: {current}\nLongest: {} wins", score.longest_streak)
+}
+
+fn throws_string(stats: &PlayerStats) -> String
+{
+	let mut throws = String::new();
+	for selection in Selection::map_all(|selection| selection)
+	{
+		let _ = writeln!(
+			throws,
+			"{} {:.1}% ({})",
+			selection.emoji(),
+			stats.throws.share(selection) * 100.0,
+			stats.throws[selection]
+		);
+	}
+	throws
+}
+
+fn opponent_string(opponent: Option<(UserId, u32)>, singular: &str, plural: &str) -> String
+{
+	opponent.map_or_else(
+		|| String::from("Nobody yet"),
+		|(id, count)| {
+			format!(
+				"{} ({count} {})",
+				id.mention(),
+				if count == 1 { singular } else { plural }
+			)
+		},
+	)
+}
+
+/// Recent results from oldest to newest, so they read left to right
+fn form_string(recent_form: &[Outcome]) -> String
+{
+	if recent_form.is_empty()
+	{
+		return String::from("No matches yet");
+	}
+
+	recent_form
+		.iter()
+		.rev()
+		.map(|outcome| match outcome
+		{
+			Outcome::Win => '🟩',
+			Outcome::Loss => '🟥',
+		})
+		.collect()
+}
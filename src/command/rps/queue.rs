@@ -0,0 +1,274 @@
+use std::time::{Duration, Instant};
+
+use poise::{
+	serenity_prelude::{
+		ButtonStyle, CreateActionRow, CreateAllowedMentions, CreateButton, CreateEmbed,
+		CreateInteractionResponse, CreateInteractionResponseMessage, CreateMessage, EditMessage,
+		GuildChannel, Mentionable, Message,
+	},
+	CreateReply,
+};
+
+use super::model::MatchFormat;
+use crate::{
+	command::ExpectGuildOnly,
+	data::{Pairing, Score},
+	Context, Error, Reply, Respond,
+};
+
+/// how often the queue is checked for a suitable opponent
+const QUEUE_TICK: Duration = Duration::from_secs(5);
+const QUEUE_TIMEOUT: Duration = Duration::from_secs(15 * 60);
+
+/// Join the matchmaking queue to be paired with a player of similar rating
+#[poise::command(slash_command, prefix_command, guild_only)]
+pub async fn queue(ctx: Context<'_>) -> Result<(), Error>
+{
+	let guild_id = ctx.guild_id().expect_guild_only();
+	let user_id = ctx.author().id;
+
+	let elo = ctx
+		.data()
+		.acquire_lock()
+		.await
+		.guild_data(guild_id)
+		.and_then(|dat| dat.leaderboard().score(user_id))
+		.map_or(Score::BASE_ELO, |score| score.elo);
+
+	{
+		let mut matchmaking = ctx.data().acquire_matchmaking_lock().await;
+		if matchmaking.first_busy(guild_id, &[user_id]).is_some()
+		{
+			drop(matchmaking);
+			ctx.reply_error("You're already in a game or the matchmaking queue!")
+				.await?;
+			return Ok(());
+		}
+		matchmaking
+			.queue_mut(guild_id)
+			.join(user_id, elo, ctx.channel_id());
+	}
+
+	let mut queue_message = ctx
+		.send(
+			CreateReply::default()
+				.embed(
+					CreateEmbed::new()
+						.title("Searching for a match...")
+						.description(format!(
+							"{} joined the matchmaking queue with a rating of **{elo}**",
+							ctx.author().mention()
+						))
+						.color(crate::DEFAULT_COLOR),
+				)
+				.components(vec![CreateActionRow::Buttons(vec![CreateButton::new(
+					"rps-leave-queue",
+				)
+				.label("Leave queue")
+				.style(ButtonStyle::Secondary)])])
+				.reply(true)
+				.allowed_mentions(CreateAllowedMentions::new()),
+		)
+		.await?
+		.into_message()
+		.await?;
+
+	let queued_at = Instant::now();
+	let pairing = loop
+	{
+		if let Some(interaction) = queue_message
+			.await_component_interaction(ctx)
+			.timeout(QUEUE_TICK)
+			.await
+		{
+			if interaction.user.id != user_id
+			{
+				interaction
+					.respond_ephemeral(
+						ctx,
+						crate::error_embed("Only the queued player may leave the queue!"),
+					)
+					.await?;
+			}
+			else if ctx
+				.data()
+				.acquire_matchmaking_lock()
+				.await
+				.queue_mut(guild_id)
+				.leave(user_id)
+			{
+				interaction
+					.create_response(
+						ctx,
+						CreateInteractionResponse::UpdateMessage(
+							CreateInteractionResponseMessage::new()
+								.embed(queue_closed_embed("Left the matchmaking queue"))
+								.components(vec![]),
+						),
+					)
+					.await?;
+				return Ok(());
+			}
+		}
+
+		let mut matchmaking = ctx.data().acquire_matchmaking_lock().await;
+		let queue = matchmaking.queue_mut(guild_id);
+
+		// someone else's queue task may have already paired us up
+		if let Some(pairing) = queue.take_pairing(user_id)
+		{
+			drop(matchmaking);
+			queue_message
+				.edit(
+					ctx,
+					EditMessage::new()
+						.embed(match_found_embed(&pairing))
+						.components(vec![]),
+				)
+				.await?;
+			return Ok(());
+		}
+
+		// the other player's queue task may have put us back in the queue, or taken us out of it
+		// if we couldn't play
+		if !queue.contains(user_id)
+		{
+			drop(matchmaking);
+			queue_message
+				.edit(
+					ctx,
+					EditMessage::new()
+						.embed(queue_closed_embed("You're busy with another game"))
+						.components(vec![]),
+				)
+				.await?;
+			return Ok(());
+		}
+
+		if let Some(pairing) = queue.try_pair(user_id)
+		{
+			// both players are checked under the same lock that paired them, so neither can end
+			// up in two games at once
+			let players = [pairing.earlier.user, pairing.later.user];
+			match matchmaking.try_start_playing(guild_id, &players, pairing.later.channel)
+			{
+				Ok(()) => break pairing,
+				Err(busy) => matchmaking
+					.queue_mut(guild_id)
+					.undo_pairing(&pairing, busy),
+			}
+			continue;
+		}
+
+		if queued_at.elapsed() >= QUEUE_TIMEOUT
+		{
+			queue.leave(user_id);
+			drop(matchmaking);
+			queue_message
+				.edit(
+					ctx,
+					EditMessage::new()
+						.embed(queue_closed_embed("No suitable opponent was found"))
+						.components(vec![]),
+				)
+				.await?;
+			return Ok(());
+		}
+	};
+
+	// the players were marked as playing when they were paired, so they have to be freed up
+	// again if the match can't be started
+	let channel = match announce_match(ctx, &mut queue_message, &pairing).await
+	{
+		Ok(channel) => channel,
+		Err(err) =>
+		{
+			super::challenges::stop_playing(ctx, &[pairing.earlier.user, pairing.later.user])
+				.await;
+			return Err(err);
+		}
+	};
+
+	super::play_match(
+		ctx,
+		pairing.earlier.user,
+		pairing.later.user,
+		MatchFormat::default(),
+		true,
+		&channel,
+		None,
+	)
+	.await
+}
+
+/// Tells both players they've been paired, returning the channel the match is played in
+async fn announce_match(
+	ctx: Context<'_>,
+	queue_message: &mut Message,
+	pairing: &Pairing,
+) -> Result<GuildChannel, Error>
+{
+	queue_message
+		.edit(
+			ctx,
+			EditMessage::new()
+				.embed(match_found_embed(pairing))
+				.components(vec![]),
+		)
+		.await?;
+
+	let channel = pairing
+		.later
+		.channel
+		.to_channel(ctx)
+		.await?
+		.guild()
+		.expect_guild_only();
+
+	channel
+		.send_message(
+			ctx,
+			CreateMessage::new()
+				.content(format!(
+					"{} {}",
+					pairing.earlier.user.mention(),
+					pairing.later.user.mention()
+				))
+				.embed(
+					CreateEmbed::new()
+						.title("Match found!")
+						.description(format!(
+							"{} (**{}**) has been paired with {} (**{}**)",
+							pairing.earlier.user.mention(),
+							pairing.earlier.elo,
+							pairing.later.user.mention(),
+							pairing.later.elo
+						))
+						.color(crate::DEFAULT_COLOR),
+				),
+		)
+		.await?;
+
+	Ok(channel)
+}
+
+fn match_found_embed(pairing: &Pairing) -> CreateEmbed
+{
+	CreateEmbed::new()
+		.title("Match found!")
+		.description(format!(
+			"{} vs {}! The match will take place in {}",
+			pairing.earlier.user.mention(),
+			pairing.later.user.mention(),
+			pairing.later.channel.mention()
+		))
+		.color(crate::DEFAULT_COLOR)
+}
+
+fn queue_closed_embed(reason: &str) -> CreateEmbed
+{
+	CreateEmbed::new()
+		.title("Matchmaking")
+		.description(reason)
+		.color(crate::DEFAULT_COLOR)
+}
@@ -0,0 +1,239 @@
+use std::fmt::Write;
+
+use poise::serenity_prelude::{
+	CacheHttp, ChannelId, CreateAllowedMentions, CreateEmbed, CreateMessage, GuildId, Mentionable,
+	RoleId, UserId,
+};
+
+use crate::{
+	data::{GuildData, RankStanding},
+	Context, Error,
+};
+
+/// How the leaderboard's rank roles and leader changed over the course of an update
+pub struct RankChanges
+{
+	role_ids: Vec<RoleId>,
+	announcement_channel: Option<ChannelId>,
+	before: RankStanding,
+	after: RankStanding,
+}
+impl RankChanges
+{
+	/// Runs `update` on the guild's data, noting down how it changed everyone's rank roles
+	pub fn track<T>(
+		guild_data: &mut GuildData,
+		update: impl FnOnce(&mut GuildData) -> T,
+	) -> (T, Self)
+	{
+		let rank_roles = guild_data.rps_settings().rank_roles().clone();
+		let before = rank_roles.standing(guild_data.leaderboard());
+		let output = update(guild_data);
+		let after = rank_roles.standing(guild_data.leaderboard());
+
+		(
+			output,
+			Self {
+				role_ids: rank_roles.role_ids(),
+				announcement_channel: guild_data.rps_settings().announcement_channel(),
+				before,
+				after,
+			},
+		)
+	}
+
+	/// Gives and takes rank roles from everyone whose roles changed, then announces any rank
+	/// changes `players` made. Failures are only logged, like in [`update_member_roles`]
+	pub async fn apply(self, ctx: impl CacheHttp + Copy, guild_id: GuildId, players: &[UserId])
+	{
+		if !self.role_ids.is_empty()
+		{
+			for player in self.before.changed_players(&self.after)
+			{
+				update_member_roles(
+					ctx,
+					guild_id,
+					player,
+					self.after.earned_roles(player),
+					&self.role_ids,
+				)
+				.await;
+			}
+		}
+
+		let Some(channel) = self.announcement_channel
+		else
+		{
+			return;
+		};
+		let announcements = self.announcements(players);
+		if announcements.is_empty()
+		{
+			return;
+		}
+
+		if let Err(err) = channel
+			.send_message(
+				ctx,
+				CreateMessage::new()
+					.embed(
+						CreateEmbed::new()
+							.title("Leaderboard Update")
+							.description(announcements)
+							.color(crate::DEFAULT_COLOR),
+					)
+					.allowed_mentions(CreateAllowedMentions::new()),
+			)
+			.await
+		{
+			log::warn!("Could not announce rank changes in {channel}! {err}");
+		}
+	}
+
+	fn announcements(&self, players: &[UserId]) -> String
+	{
+		let mut announcements = String::new();
+
+		if self.after.leader != self.before.leader
+			&& let Some(leader) = self.after.leader
+		{
+			let _ = match self.before.leader
+			{
+				Some(old_leader) => writeln!(
+					announcements,
+					"👑 {} took the top spot from {}!",
+					leader.mention(),
+					old_leader.mention()
+				),
+				None => writeln!(announcements, "👑 {} took the top spot!", leader.mention()),
+			};
+		}
+
+		for player in players
+		{
+			let old_tier = self.before.tiers.get(player).copied();
+			let new_tier = self.after.tiers.get(player).copied();
+			let old_rating = old_tier.map(|(min_rating, _)| min_rating);
+			let new_rating = new_tier.map(|(min_rating, _)| min_rating);
+
+			let _ = match (old_tier, new_tier)
+			{
+				(_, Some((_, new_role))) if new_rating > old_rating => writeln!(
+					announcements,
+					"⬆️ {} reached {}!",
+					player.mention(),
+					new_role.mention()
+				),
+				(Some((_, old_role)), None) => writeln!(
+					announcements,
+					"⬇️ {} dropped out of {}",
+					player.mention(),
+					old_role.mention()
+				),
+				(Some(_), Some((_, new_role))) if new_rating < old_rating => writeln!(
+					announcements,
+					"⬇️ {} dropped to {}",
+					player.mention(),
+					new_role.mention()
+				),
+				_ => Ok(()),
+			};
+		}
+
+		announcements
+	}
+}
+
+/// Brings everyone's rank roles in line with the leaderboard, including anyone holding a rank
+/// role they shouldn't. `stale_roles` are roles that used to be rank roles and should be taken
+/// back from everyone
+pub async fn sync_rank_roles(
+	ctx: Context<'_>,
+	guild_id: GuildId,
+	stale_roles: &[RoleId],
+) -> Result<(), Error>
+{
+	let (standing, mut role_ids) = {
+		let data = ctx.data().acquire_lock().await;
+		let Some(guild_data) = data.guild_data(guild_id)
+		else
+		{
+			return Ok(());
+		};
+		let rank_roles = guild_data.rps_settings().rank_roles();
+		(
+			rank_roles.standing(guild_data.leaderboard()),
+			rank_roles.role_ids(),
+		)
+	};
+	role_ids.extend_from_slice(stale_roles);
+
+	let mut players = ctx
+		.cache()
+		.guild(guild_id)
+		.map(|guild| {
+			guild
+				.members
+				.values()
+				.filter(|member| member.roles.iter().any(|role| role_ids.contains(role)))
+				.map(|member| member.user.id)
+				.collect::<Vec<_>>()
+		})
+		.unwrap_or_default();
+	players.extend(standing.earned_roles.keys());
+	players.sort_unstable();
+	players.dedup();
+
+	for player in players
+	{
+		update_member_roles(
+			ctx,
+			guild_id,
+			player,
+			standing.earned_roles(player),
+			&role_ids,
+		)
+		.await;
+	}
+
+	Ok(())
+}
+
+/// Gives `player` the rank roles they've earned and takes away any others they hold. Failures
+/// are only logged, since a missing permission shouldn't stop a match from finishing
+async fn update_member_roles(
+	ctx: impl CacheHttp + Copy,
+	guild_id: GuildId,
+	player: UserId,
+	earned_roles: &[RoleId],
+	role_ids: &[RoleId],
+)
+{
+	// players who have left the server can't hold roles anyway
+	let Ok(member) = guild_id.member(ctx, player).await
+	else
+	{
+		return;
+	};
+
+	let to_add = earned_roles
+		.iter()
+		.filter(|role| !member.roles.contains(role))
+		.copied()
+		.collect::<Vec<_>>();
+	let to_remove = member
+		.roles
+		.iter()
+		.filter(|role| role_ids.contains(role) && !earned_roles.contains(role))
+		.copied()
+		.collect::<Vec<_>>();
+
+	if let Err(err) = member.add_roles(ctx.http(), &to_add).await
+	{
+		log::warn!("Could not give rank roles to {}({player})! {err}", member.user.name);
+	}
+	if let Err(err) = member.remove_roles(ctx.http(), &to_remove).await
+	{
+		log::warn!("Could not remove rank roles from {}({player})! {err}", member.user.name);
+	}
+}
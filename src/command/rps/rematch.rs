@@ -0,0 +1,162 @@
+use std::time::Duration;
+
+use poise::serenity_prelude::{
+	ButtonStyle, CreateActionRow, CreateAllowedMentions, CreateButton, CreateInteractionResponse,
+	CreateInteractionResponseMessage, EditMessage, Mentionable, Message, UserId,
+};
+
+use super::model::{ChallengerOpponentPair, MatchFormat};
+use crate::{Context, Error, Respond};
+
+pub fn rematch_buttons(format: MatchFormat) -> Vec<CreateActionRow>
+{
+	vec![CreateActionRow::Buttons(vec![
+		CreateButton::new("rps-rematch")
+			.emoji('\u{1f501}')
+			.label("Rematch")
+			.style(ButtonStyle::Primary),
+		CreateButton::new("rps-run-it-back")
+			.emoji('\u{23eb}')
+			.label(format!("Run it back, {}", format.extended()))
+			.style(ButtonStyle::Secondary),
+	])]
+}
+
+/// Waits for either player to ask for a rematch on the results of their match, then for the other
+/// player to accept it. Returns the format of the rematch once both have agreed, or `None` if
+/// nobody asked in time or the rematch was declined
+pub async fn await_rematch(
+	ctx: Context<'_>,
+	result_message: &mut Message,
+	players: ChallengerOpponentPair<UserId>,
+	format: MatchFormat,
+	timeout: Duration,
+) -> Result<Option<MatchFormat>, Error>
+{
+	// the player who asked for the rematch and the format they asked for, once someone has
+	let mut request: Option<(UserId, MatchFormat)> = None;
+
+	let rematch_format = loop
+	{
+		let Some(interaction) = result_message
+			.await_component_interaction(ctx)
+			.timeout(timeout)
+			.await
+		else
+		{
+			result_message
+				.edit(ctx, EditMessage::new().content("").components(vec![]))
+				.await?;
+			break None;
+		};
+
+		let user = interaction.user.id;
+		if user != players.challenger && user != players.opponent
+		{
+			interaction
+				.respond_ephemeral(
+					ctx,
+					crate::error_embed("Only the players in the match can ask for a rematch!"),
+				)
+				.await?;
+			continue;
+		}
+
+		match (request, interaction.data.custom_id.as_str())
+		{
+			(None, custom_id) =>
+			{
+				let Some(requested_format) = requested_format(custom_id, format)
+				else
+				{
+					continue;
+				};
+				let other_player = *players.other(&user);
+
+				interaction
+					.create_response(
+						ctx,
+						rematch_update(
+							format!(
+								"{} wants a **{requested_format}** rematch! {}, do you accept?",
+								user.mention(),
+								other_player.mention()
+							),
+							Some(other_player),
+							super::response_buttons(),
+						),
+					)
+					.await?;
+				request = Some((user, requested_format));
+			}
+			(Some((requester, _)), _) if user == requester =>
+			{
+				interaction
+					.respond_ephemeral(
+						ctx,
+						crate::error_embed("Your opponent has to accept the rematch!"),
+					)
+					.await?;
+			}
+			(Some((_, requested_format)), "rps-accept") =>
+			{
+				interaction
+					.create_response(
+						ctx,
+						rematch_update(
+							format!("Rematch accepted: **{requested_format}**!"),
+							None,
+							vec![],
+						),
+					)
+					.await?;
+				break Some(requested_format);
+			}
+			(Some((requester, _)), _) =>
+			{
+				interaction
+					.create_response(
+						ctx,
+						rematch_update(
+							format!(
+								"{} declined {}'s rematch",
+								user.mention(),
+								requester.mention()
+							),
+							None,
+							vec![],
+						),
+					)
+					.await?;
+				break None;
+			}
+		}
+	};
+
+	Ok(rematch_format)
+}
+
+fn requested_format(custom_id: &str, format: MatchFormat) -> Option<MatchFormat>
+{
+	match custom_id
+	{
+		"rps-rematch" => Some(format),
+		"rps-run-it-back" => Some(format.extended()),
+		_ => None,
+	}
+}
+
+/// Updates the results message with where the rematch stands, only pinging `ping` if anyone
+fn rematch_update(
+	content: String,
+	ping: Option<UserId>,
+	buttons: Vec<CreateActionRow>,
+) -> CreateInteractionResponse
+{
+	CreateInteractionResponse::UpdateMessage(
+		CreateInteractionResponseMessage::new()
+			.content(content)
+			.allowed_mentions(CreateAllowedMentions::new().users(ping))
+			.components(buttons),
+	)
+}
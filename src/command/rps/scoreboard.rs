@@ -0,0 +1,226 @@
+use std::{fmt::Write, time::Duration};
+
+use poise::serenity_prelude::{
+	CreateEmbed, CreateEmbedFooter, CreateInteractionResponse, CreateInteractionResponseMessage,
+	CreateMessage, EditMessage, Member, Mentionable, UserId,
+};
+
+use super::{
+	commitment::Commitment,
+	model::{ChallengerOpponentPair, Game, RoundOutcome, Side},
+};
+use crate::{command::format_duration, data::Outcome};
+
+/// how many of the latest rounds are listed in the round log
+const ROUND_LOG_LENGTH: usize = 10;
+
+/// A message a game of type `G` is played out on, which is edited in place as the game goes on
+/// instead of sending new messages every round
+pub trait Board<G>
+{
+	fn embed(&self, game: &G) -> CreateEmbed;
+
+	/// Whether the game is over, which disables the buttons
+	fn concluded(&self) -> bool;
+
+	fn message(&self, game: &G) -> CreateMessage
+	{
+		CreateMessage::new()
+			.embed(self.embed(game))
+			.components(super::selection_buttons(self.concluded()))
+	}
+
+	fn edit(&self, game: &G) -> EditMessage
+	{
+		EditMessage::new()
+			.embed(self.embed(game))
+			.components(super::selection_buttons(self.concluded()))
+	}
+
+	fn update(&self, game: &G) -> CreateInteractionResponse
+	{
+		CreateInteractionResponse::UpdateMessage(
+			CreateInteractionResponseMessage::new()
+				.embed(self.embed(game))
+				.components(super::selection_buttons(self.concluded())),
+		)
+	}
+}
+
+/// The footer every board has, reminding players how long they have to pick
+pub fn round_timeout_footer(round_timeout: Duration) -> CreateEmbedFooter
+{
+	CreateEmbedFooter::new(format!(
+		"\u{2757} Each round has to be played within {}",
+		format_duration(round_timeout)
+	))
+}
+
+/// Every player on its own line, checked off if they've made their selection
+pub fn waiting_on(players: impl IntoIterator<Item = (UserId, bool)>) -> String
+{
+	players
+		.into_iter()
+		.map(|(player, selected)| {
+			format!(
+				"{} {}",
+				if selected { '\u{2705}' } else { '\u{23f3}' },
+				player.mention()
+			)
+		})
+		.collect::<Vec<_>>()
+		.join("\n")
+}
+
+/// The board a one on one match is played out on
+pub struct Scoreboard<'a>
+{
+	members: &'a ChallengerOpponentPair<Member>,
+	round_timeout: Duration,
+	/// the bot's commitment to its selection this round, if playing the bot
+	commitment: Option<Commitment>,
+	/// the bot's commitment from the last round, revealed now that the round is over
+	revealed: Option<Commitment>,
+	/// what just happened in the match
+	status: String,
+	concluded: bool,
+}
+impl<'a> Scoreboard<'a>
+{
+	pub fn new(members: &'a ChallengerOpponentPair<Member>, round_timeout: Duration) -> Self
+	{
+		Self {
+			members,
+			round_timeout,
+			commitment: None,
+			revealed: None,
+			status: String::from("Pick rock, paper, or scissors!"),
+			concluded: false,
+		}
+	}
+
+	pub fn commit(&mut self, commitment: Option<Commitment>)
+	{
+		self.commitment = commitment;
+	}
+
+	/// Notes down who won the round, revealing the bot's selection if it made one
+	pub fn finish_round(&mut self, round_outcome: &RoundOutcome)
+	{
+		self.status = round_outcome.winner().map_or_else(
+			|| format!("Round {} is a tie!", round_outcome.round_count()),
+			|side| {
+				format!(
+					"{} wins round {}!",
+					self.members[side].mention(),
+					round_outcome.round_count()
+				)
+			},
+		);
+		self.revealed = self.commitment.take();
+	}
+
+	/// Ends the match, disabling the buttons
+	pub fn conclude(&mut self, status: String)
+	{
+		self.status = status;
+		self.concluded = true;
+	}
+
+	fn round_log(&self, game: &Game) -> String
+	{
+		let history = game.history();
+		let skipped = history.len().saturating_sub(ROUND_LOG_LENGTH);
+
+		let mut log = String::new();
+		if skipped > 0
+		{
+			let _ = writeln!(log, "*...and {skipped} earlier rounds*");
+		}
+		for (i, round) in history.iter().enumerate().skip(skipped)
+		{
+			let result = match round.challenger.against(round.opponent)
+			{
+				Some(Outcome::Win) => self.members.challenger.mention().to_string(),
+				Some(Outcome::Loss) => self.members.opponent.mention().to_string(),
+				None => String::from("tie"),
+			};
+			let _ = writeln!(
+				log,
+				"`{}` {} vs {} → {result}",
+				i + 1,
+				round.challenger.emoji(),
+				round.opponent.emoji()
+			);
+		}
+		log
+	}
+}
+impl Board<Game> for Scoreboard<'_>
+{
+	fn embed(&self, game: &Game) -> CreateEmbed
+	{
+		let mut embed = CreateEmbed::new()
+			.title(if self.concluded
+			{
+				String::from("Match over")
+			}
+			else
+			{
+				format!("Round {}", game.round_count())
+			})
+			.description(&self.status)
+			.field(
+				"Score",
+				format!(
+					"{} **{}** - **{}** {}",
+					self.members.challenger.mention(),
+					game.challenger().score(),
+					game.opponent().score(),
+					self.members.opponent.mention()
+				),
+				true,
+			)
+			.field("Format", game.format().to_string(), true)
+			.color(crate::DEFAULT_COLOR)
+			.footer(round_timeout_footer(self.round_timeout));
+
+		if !game.history().is_empty()
+		{
+			embed = embed.field("Rounds", self.round_log(game), false);
+		}
+		if !self.concluded
+		{
+			embed = embed.field(
+				"Waiting on",
+				waiting_on([Side::Challenger, Side::Opponent].map(|side| {
+					(self.members[side].user.id, game[side].has_selected())
+				})),
+				false,
+			);
+			if game.is_sudden_death()
+			{
+				embed = embed.field("Sudden death!", "The next round decides the match", false);
+			}
+			if let Some(commitment) = &self.commitment
+			{
+				embed = embed.field(
+					"My selection is locked in",
+					format!("`{}`", commitment.hash()),
+					false,
+				);
+			}
+		}
+		if let Some(revealed) = &self.revealed
+		{
+			embed = embed.field("Proof of fair play", revealed.reveal(), false);
+		}
+
+		embed
+	}
+
+	fn concluded(&self) -> bool
+	{
+		self.concluded
+	}
+}
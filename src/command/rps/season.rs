@@ -0,0 +1,173 @@
+use std::fmt::Write;
+
+use poise::{
+	serenity_prelude::{CreateAllowedMentions, CreateEmbed, Mentionable},
+	CreateReply,
+};
+
+use crate::{
+	command::{parent_command, ExpectGuildOnly},
+	data::SeasonReset,
+	Context, Error, Reply,
+};
+
+parent_command! {
+	let season = poise::command(
+		prefix_command,
+		slash_command,
+		guild_only,
+		subcommands("end", "list")
+	)
+}
+
+/// End the current leaderboard season, archiving the final standings
+#[poise::command(
+	prefix_command,
+	slash_command,
+	guild_only,
+	required_permissions = "MANAGE_GUILD"
+)]
+pub async fn end(
+	ctx: Context<'_>,
+	#[description = "The name to archive this season under"] name: String,
+	#[description = "How ratings should be reset for the next season (default: Hard)"]
+	reset: Option<SeasonReset>,
+) -> Result<(), Error>
+{
+	let name = name.trim().to_owned();
+	let guild_id = ctx.guild_id().expect_guild_only();
+
+	let error = {
+		let mut data = ctx.data().acquire_lock().await;
+		let guild_data = data.guild_data_mut(guild_id);
+
+		if name.is_empty()
+		{
+			Some(String::from("The season needs a name!"))
+		}
+		else if guild_data.season(&name).is_some()
+		{
+			Some(format!("A season named `{name}` already exists!"))
+		}
+		else if guild_data.leaderboard().ordered_scores(Some(1)).is_empty()
+		{
+			Some(String::from("Nobody has played this season yet!"))
+		}
+		else
+		{
+			guild_data.end_season(name.clone(), reset.unwrap_or_default());
+			None
+		}
+	};
+
+	if let Some(error) = error
+	{
+		ctx.reply_error(error).await?;
+		return Ok(());
+	}
+
+	let champion = ctx
+		.data()
+		.acquire_lock()
+		.await
+		.guild_data(guild_id)
+		.and_then(|dat| dat.season(&name))
+		.and_then(|season| season.standings.first())
+		.map(|standing| standing.user);
+
+	ctx.send(
+		CreateReply::default()
+			.embed(
+				CreateEmbed::new()
+					.title("Season over!")
+					.description(format!(
+						"**{name}** has come to a close{}\nThe leaderboard has been reset for \
+						 the next season. Good luck!",
+						champion
+							.map(|user| format!(" with {} on top", user.mention()))
+							.unwrap_or_default()
+					))
+					.color(crate::DEFAULT_COLOR),
+			)
+			.reply(true)
+			.allowed_mentions(CreateAllowedMentions::new()),
+	)
+	.await?;
+
+	// the reset moves everyone around, so rank roles have to be handed out again
+	super::rank_roles::sync_rank_roles(ctx, guild_id, &[]).await?;
+
+	Ok(())
+}
+
+/// List this server's past leaderboard seasons
+#[poise::command(prefix_command, slash_command, guild_only)]
+pub async fn list(ctx: Context<'_>) -> Result<(), Error>
+{
+	let seasons_string = ctx
+		.data()
+		.acquire_lock()
+		.await
+		.guild_data(ctx.guild_id().expect_guild_only())
+		.map(|dat| {
+			dat.seasons()
+				.iter()
+				.fold(String::new(), |mut seasons_string, season| {
+					let _ = write!(
+						seasons_string,
+						"**{}** (ended <t:{}:D>)",
+						season.name,
+						season.ended_at.unix_timestamp()
+					);
+					if let Some(champion) = season.standings.first()
+					{
+						let _ = write!(seasons_string, " \u{1f451} {}", champion.user.mention());
+					}
+					seasons_string.push('\n');
+					seasons_string
+				})
+		})
+		.unwrap_or_default();
+
+	if seasons_string.is_empty()
+	{
+		ctx.reply_error("This server has no past seasons!").await?;
+		return Ok(());
+	}
+
+	ctx.send(
+		CreateReply::default()
+			.embed(
+				CreateEmbed::new()
+					.title("Past Seasons")
+					.description(seasons_string)
+					.color(crate::DEFAULT_COLOR),
+			)
+			.reply(true)
+			.allowed_mentions(CreateAllowedMentions::new()),
+	)
+	.await?;
+
+	Ok(())
+}
+
+pub async fn autocomplete_season(ctx: Context<'_>, partial: &str) -> Vec<String>
+{
+	ctx.data()
+		.acquire_lock()
+		.await
+		.guild_data(ctx.guild_id().expect_guild_only())
+		.map(|dat| {
+			dat.seasons()
+				.iter()
+				.filter(|season| {
+					season
+						.name
+						.to_lowercase()
+						.starts_with(&partial.to_lowercase())
+				})
+				.map(|season| season.name.clone())
+				.collect()
+		})
+		.unwrap_or_default()
+}
@@ -0,0 +1,981 @@
+use std::{fmt::Write, time::Duration};
+
+use poise::{
+	serenity_prelude::{
+		CreateActionRow, CreateAllowedMentions, CreateEmbed, CreateInteractionResponse,
+		CreateInteractionResponseMessage, CreateMessage, EditMessage, GuildChannel, GuildId, Member,
+		Mentionable, Message, Timestamp, UserId,
+	},
+	CreateReply,
+};
+
+use super::{
+	model::{self, ChallengerOpponentPair, MatchFormat, MatchFormatKind, Selection, Side},
+	rank_roles::RankChanges,
+	scoreboard::{self, Board},
+	RecordedMatch,
+};
+use crate::{
+	command::ExpectGuildOnly,
+	data::{DataManager, Glicko2Rating, Leaderboard, Outcome, RatingSystem, Score},
+	Context, Error, Reply, Respond,
+};
+
+type Team<T> = [T; 2];
+/// every player's old rating, then their new one
+type TeamRatingChanges = ChallengerOpponentPair<Team<(i32, i32)>>;
+
+/// Challenge two other users to a 2v2 game of Rock, Paper, Scissors
+#[poise::command(slash_command, prefix_command, guild_only)]
+#[allow(clippy::too_many_arguments)] // every argument is a command option
+pub async fn team(
+	ctx: Context<'_>,
+	#[description = "Your teammate"] teammate: Member,
+	#[description = "The first player on the other team"] opponent_a: Member,
+	#[description = "The second player on the other team"] opponent_b: Member,
+	#[description = "Rounds needed to win (default: 1)"]
+	#[min = 1]
+	first_to: Option<u32>,
+	#[description = "How the winner of the match is decided (default: First To)"]
+	format: Option<MatchFormatKind>,
+	#[description = "Rounds to play, for Best Of (default: 1)"]
+	#[min = 1]
+	rounds: Option<u32>,
+	#[description = "Whether the match affects ratings (default: true)"] ranked: Option<bool>,
+) -> Result<(), Error>
+{
+	let format = MatchFormat::from_options(format.unwrap_or_default(), first_to, rounds);
+	let ranked = ranked.unwrap_or(true);
+	let teams = ChallengerOpponentPair::new(
+		[ctx.author().id, teammate.user.id],
+		[opponent_a.user.id, opponent_b.user.id],
+	);
+
+	let mut distinct = teams.challenger.iter().chain(&teams.opponent).collect::<Vec<_>>();
+	distinct.sort_unstable();
+	distinct.dedup();
+	if distinct.len() < 4
+	{
+		ctx.reply_error("Every player in a team match has to be different!")
+			.await?;
+		return Ok(());
+	}
+	if [&teammate, &opponent_a, &opponent_b]
+		.iter()
+		.any(|member| member.user.bot)
+	{
+		ctx.reply_error("You can't challenge a bot to a team match!")
+			.await?;
+		return Ok(());
+	}
+
+	let players = teams
+		.challenger
+		.into_iter()
+		.chain(teams.opponent)
+		.collect::<Vec<_>>();
+	if !super::challenges::check_available(ctx, &players).await?
+	{
+		return Ok(());
+	}
+
+	let settings = super::rps_settings(ctx).await;
+	if !await_confirmations(ctx, teams, format, ranked, settings.accept_timeout()).await?
+	{
+		return Ok(());
+	}
+
+	// anyone could have started another game while the others were accepting
+	let channel = ctx.guild_channel().await.expect_guild_only();
+	if !super::challenges::try_start_playing(ctx, &players, channel.id).await?
+	{
+		return Ok(());
+	}
+	let mut game = TeamGame::start(teams, format);
+	let winning_side = play_rounds(ctx, &mut game, &channel, settings.round_timeout()).await;
+	super::challenges::stop_playing(ctx, &players).await;
+	let Some(winning_side) = winning_side?
+	else
+	{
+		return Ok(());
+	};
+
+	let guild_id = ctx.guild_id().expect_guild_only();
+	let RecordedMatch {
+		rating_changes,
+		rank_changes,
+		flags,
+	} = record_game(
+		&mut *ctx.data().acquire_lock().await,
+		guild_id,
+		&game,
+		winning_side,
+		ranked,
+	);
+
+	let mut embed = match_embed(&game, winning_side, rating_changes);
+	if !ranked
+	{
+		embed = embed.footer(super::casual_footer());
+	}
+	channel
+		.send_message(
+			ctx,
+			CreateMessage::new()
+				.embed(embed)
+				.allowed_mentions(CreateAllowedMentions::new()),
+		)
+		.await?;
+
+	if let Some(rank_changes) = rank_changes
+	{
+		rank_changes.apply(ctx, guild_id, &players).await;
+	}
+	for flag in flags
+	{
+		super::admin::report_flag(ctx, guild_id, &flag).await?;
+	}
+
+	Ok(())
+}
+
+/// Saves a finished match to the server's history as a pairing between every player and each of
+/// their opponents, updating the leaderboards if it was ranked
+fn record_game(
+	data: &mut DataManager,
+	guild_id: GuildId,
+	game: &TeamGame,
+	winning_side: Side,
+	ranked: bool,
+) -> RecordedMatch<TeamRatingChanges>
+{
+	let guild_data = data.guild_data_mut(guild_id);
+	let rating_system = guild_data.rps_settings().rating_system();
+	let counts_globally = ranked && guild_data.rps_settings().global_ladder_enabled();
+	let teams = game.teams;
+	// playing the same opponents over and over counts for less each time, just like it does in
+	// one on one matches. each player's weight is the average of their weights against both
+	// opponents, since they're rated against the other team as a whole
+	let now = Timestamp::now();
+	let weights = teams.gen_map(Side::Challenger, Side::Opponent, |team, side| {
+		team.map(|player| {
+			teams[!side]
+				.iter()
+				.map(|opponent| {
+					guild_data
+						.match_history()
+						.repeat_weight(player, *opponent, now)
+				})
+				.sum::<f64>()
+				/ 2.0
+		})
+	});
+
+	let (rating_changes, rank_changes) = ranked
+		.then(|| {
+			RankChanges::track(guild_data, |guild_data| {
+				update_leaderboard(
+					guild_data.leaderboard_mut(),
+					teams,
+					winning_side,
+					rating_system,
+					weights,
+				)
+			})
+		})
+		.unzip();
+	if let Some(rating_changes) = rating_changes
+	{
+		let new_ratings = teams.zip(rating_changes);
+		guild_data.rating_history_mut().record(
+			[new_ratings.challenger, new_ratings.opponent]
+				.into_iter()
+				.flat_map(|(team, changes)| team.into_iter().zip(changes))
+				.map(|(player, (_, new_rating))| (player, new_rating)),
+		);
+	}
+
+	// each player's rating change is split evenly between the two opponents they played
+	let pairing_change = |(old_elo, new_elo): (i32, i32), opponent_index: usize| {
+		let change = new_elo - old_elo;
+		if opponent_index == 0 { change / 2 } else { change - change / 2 }
+	};
+	let records = (0..2).flat_map(|i| (0..2).map(move |j| (i, j))).map(|(i, j)| {
+		let players = ChallengerOpponentPair::new(teams.challenger[i], teams.opponent[j]);
+		super::pairing_record(
+			players,
+			teams[winning_side][if winning_side == Side::Challenger { i } else { j }],
+			game.forfeited_by.is_some(),
+			game.history
+				.iter()
+				.map(|round| (round.challenger[i], round.opponent[j]))
+				.collect(),
+			rating_changes.map(|changes| {
+				(
+					pairing_change(changes.challenger[i], j),
+					pairing_change(changes.opponent[j], i),
+				)
+			}),
+		)
+	});
+	let flags = super::record_pairings(guild_data, records);
+
+	// the global ladder always uses ELO, since servers can each pick their own system
+	if counts_globally
+	{
+		update_leaderboard(
+			data.global_leaderboard_mut(),
+			teams,
+			winning_side,
+			RatingSystem::Elo,
+			weights,
+		);
+	}
+
+	RecordedMatch {
+		rating_changes,
+		rank_changes,
+		flags,
+	}
+}
+
+/// Sends the challenge and waits for the other three players to accept it. Returns `false` if
+/// anyone declined or the challenge timed out
+async fn await_confirmations(
+	ctx: Context<'_>,
+	teams: ChallengerOpponentPair<Team<UserId>>,
+	format: MatchFormat,
+	ranked: bool,
+	accept_timeout: Duration,
+) -> Result<bool, Error>
+{
+	let mut waiting_on = vec![teams.challenger[1], teams.opponent[0], teams.opponent[1]];
+
+	let mut challenge_message = ctx
+		.send(
+			CreateReply::default()
+				.content(super::mention_list(&waiting_on))
+				.embed(challenge_embed(teams, format, ranked, &waiting_on, accept_timeout))
+				.components(super::response_buttons())
+				.reply(true),
+		)
+		.await?
+		.into_message()
+		.await?;
+
+	let accepted = loop
+	{
+		let Some(interaction) = challenge_message
+			.await_component_interaction(ctx)
+			.timeout(accept_timeout)
+			.await
+		else
+		{
+			challenge_message
+				.edit(
+					ctx,
+					EditMessage::new()
+						.content("")
+						.embed(expired_embed(&waiting_on))
+						.components(vec![]),
+				)
+				.await?;
+			break false;
+		};
+
+		let user = interaction.user.id;
+		if !waiting_on.contains(&user)
+		{
+			interaction
+				.respond_ephemeral(
+					ctx,
+					crate::error_embed("Only players who haven't accepted yet can respond!"),
+				)
+				.await?;
+			continue;
+		}
+
+		match interaction.data.custom_id.as_str()
+		{
+			"rps-accept" =>
+			{
+				waiting_on.retain(|player| *player != user);
+				let update = if waiting_on.is_empty()
+				{
+					challenge_update(accepted_embed(teams), vec![])
+				}
+				else
+				{
+					challenge_update(
+						challenge_embed(teams, format, ranked, &waiting_on, accept_timeout),
+						super::response_buttons(),
+					)
+				};
+				interaction.create_response(ctx, update).await?;
+
+				if waiting_on.is_empty()
+				{
+					break true;
+				}
+			}
+			"rps-decline" =>
+			{
+				interaction
+					.create_response(
+						ctx,
+						challenge_update(
+							CreateEmbed::new()
+								.title("Challenge declined!")
+								.description(format!(
+									"{} does not accept the challenge",
+									user.mention()
+								))
+								.color(crate::DEFAULT_COLOR),
+							vec![],
+						),
+					)
+					.await?;
+				break false;
+			}
+			_ => (),
+		}
+	};
+
+	Ok(accepted)
+}
+
+fn challenge_embed(
+	teams: ChallengerOpponentPair<Team<UserId>>,
+	format: MatchFormat,
+	ranked: bool,
+	waiting_on: &[UserId],
+	accept_timeout: Duration,
+) -> CreateEmbed
+{
+	CreateEmbed::new()
+		.title("Rock Paper Scissors")
+		.description(format!(
+			"{} challenges {} to {} **{format}** 2v2 Rock, Paper, Scissors match!\nEveryone has \
+			 to accept before the match starts",
+			team_string(&teams.challenger),
+			team_string(&teams.opponent),
+			if ranked { "a" } else { "a casual" }
+		))
+		.field("Waiting on", super::mention_list(waiting_on), false)
+		.color(crate::DEFAULT_COLOR)
+		.footer(super::timeout_footer(accept_timeout))
+}
+
+fn accepted_embed(teams: ChallengerOpponentPair<Team<UserId>>) -> CreateEmbed
+{
+	CreateEmbed::new()
+		.title("Challenge accepted!")
+		.description(format!(
+			"{} vs {}",
+			team_string(&teams.challenger),
+			team_string(&teams.opponent)
+		))
+		.color(crate::DEFAULT_COLOR)
+}
+
+fn expired_embed(waiting_on: &[UserId]) -> CreateEmbed
+{
+	CreateEmbed::new()
+		.title("Challenge expired")
+		.description(format!("{} didn't accept in time", super::mention_list(waiting_on)))
+		.color(crate::DEFAULT_COLOR)
+}
+
+fn challenge_update(embed: CreateEmbed, buttons: Vec<CreateActionRow>) -> CreateInteractionResponse
+{
+	CreateInteractionResponse::UpdateMessage(
+		CreateInteractionResponseMessage::new()
+			.content("")
+			.embed(embed)
+			.components(buttons)
+			.allowed_mentions(CreateAllowedMentions::new()),
+	)
+}
+
+/// Plays rounds until one team wins the match, all on one message that's edited as the match goes
+/// on, returning the winning side. Returns `None` if the match was abandoned
+async fn play_rounds(
+	ctx: Context<'_>,
+	game: &mut TeamGame,
+	channel: &GuildChannel,
+	round_timeout: Duration,
+) -> Result<Option<Side>, Error>
+{
+	let mut board = TeamBoard::new(round_timeout);
+	let mut match_message = channel
+		.send_message(
+			ctx,
+			board
+				.message(game)
+				.allowed_mentions(CreateAllowedMentions::new()),
+		)
+		.await?;
+
+	loop
+	{
+		match await_selections(ctx, &mut match_message, game, &mut board).await?
+		{
+			SelectionResult::Round(round) =>
+			{
+				board.status = round_summary(&round, game);
+				let winner = game.winner();
+				if let Some(winning_side) = winner
+				{
+					board.conclude(&format!(
+						"{} win the match!",
+						team_string(&game.teams[winning_side])
+					));
+				}
+				match_message.edit(ctx, board.edit(game)).await?;
+
+				if winner.is_some()
+				{
+					return Ok(winner);
+				}
+			}
+			SelectionResult::Forfeit(forfeiting_side) =>
+			{
+				game.forfeited_by = Some(forfeiting_side);
+				return Ok(Some(!forfeiting_side));
+			}
+			SelectionResult::Abandoned => return Ok(None),
+		}
+	}
+}
+
+enum SelectionResult
+{
+	Round(TeamRound),
+	Forfeit(Side),
+	Abandoned,
+}
+
+/// Collects all four players' selections for the round, updating the board as they come in
+async fn await_selections(
+	ctx: Context<'_>,
+	match_message: &mut Message,
+	game: &mut TeamGame,
+	board: &mut TeamBoard,
+) -> Result<SelectionResult, Error>
+{
+	loop
+	{
+		let Some(interaction) = match_message
+			.await_component_interaction(ctx)
+			.timeout(board.round_timeout)
+			.await
+		else
+		{
+			// a team that's still waiting on someone while the other team is ready is stalling
+			// and forfeits. if both teams are, the match is dropped
+			let stalling_side = game.stalling_side();
+			board.conclude(&stalling_side.map_or_else(
+				|| {
+					String::from(
+						"Time's up! Neither team made their selections in time. The match has \
+						 been abandoned",
+					)
+				},
+				|side| {
+					format!(
+						"Time's up! {} didn't make their selections in time and forfeit the match",
+						team_string(&game.teams[side])
+					)
+				},
+			));
+			match_message.edit(ctx, board.edit(game)).await?;
+
+			return Ok(stalling_side.map_or(SelectionResult::Abandoned, SelectionResult::Forfeit));
+		};
+
+		let user = interaction.user.id;
+		let Some((side, index)) = game.position_of(user)
+		else
+		{
+			interaction
+				.respond_ephemeral(
+					ctx,
+					crate::error_embed("Only the players in the game are allowed to respond!"),
+				)
+				.await?;
+			continue;
+		};
+
+		if interaction.data.custom_id == "rps-forfeit"
+		{
+			board.conclude(&format!(
+				"{} forfeits the match for {}!",
+				user.mention(),
+				team_string(&game.teams[side])
+			));
+			interaction
+				.create_response(ctx, board.update(game))
+				.await?;
+
+			return Ok(SelectionResult::Forfeit(side));
+		}
+
+		if game.selections[side][index].is_some()
+		{
+			interaction
+				.respond_ephemeral(ctx, crate::error_embed("You have already selected!"))
+				.await?;
+			continue;
+		}
+
+		let Some(selection) = model::selection_from_button(&interaction.data.custom_id)
+		else
+		{
+			continue;
+		};
+		game.selections[side][index] = Some(selection);
+
+		// the board is edited once the round is played, so the last selection of the round only
+		// needs acknowledging
+		let round = game.try_declare_round();
+		let update = round.is_none().then(|| board.update(game));
+		super::confirm_selection(ctx, &interaction, update, selection).await?;
+
+		if let Some(round) = round
+		{
+			return Ok(SelectionResult::Round(round));
+		}
+	}
+}
+
+/// What everyone threw in the round and which team won it
+fn round_summary(round: &TeamRound, game: &TeamGame) -> String
+{
+	let mut summary = format!("**Round {}**\n", game.round_count);
+	for side in [Side::Challenger, Side::Opponent]
+	{
+		for (player, selection) in game.teams[side].iter().zip(round.selections[side])
+		{
+			let _ = writeln!(summary, "{} chose {selection}", player.mention());
+		}
+	}
+
+	let _ = write!(
+		summary,
+		"Pairings won: {}-{}\n{}",
+		round.pairings_won.challenger,
+		round.pairings_won.opponent,
+		round.winner().map_or_else(
+			|| String::from("It's a tie!"),
+			|side| format!("{} win the round!", team_string(&game.teams[side])),
+		)
+	);
+	summary
+}
+
+/// The board a team match is played out on
+struct TeamBoard
+{
+	round_timeout: Duration,
+	/// what happened last, shown above the score
+	status: String,
+	concluded: bool,
+}
+impl TeamBoard
+{
+	fn new(round_timeout: Duration) -> Self
+	{
+		Self {
+			round_timeout,
+			status: String::from("Pick rock, paper, or scissors!"),
+			concluded: false,
+		}
+	}
+
+	/// Ends the match with `result` under whatever happened last, disabling the buttons
+	fn conclude(&mut self, result: &str)
+	{
+		self.status = format!("{}\n\n**{result}**", self.status);
+		self.concluded = true;
+	}
+}
+impl Board<TeamGame> for TeamBoard
+{
+	fn embed(&self, game: &TeamGame) -> CreateEmbed
+	{
+		let mut embed = CreateEmbed::new()
+			.title(if self.concluded
+			{
+				String::from("Match over")
+			}
+			else
+			{
+				format!("Round {}", game.round_count + 1)
+			})
+			.description(&self.status)
+			.field(
+				"Score",
+				format!(
+					"{} **{}** - **{}** {}",
+					team_string(&game.teams.challenger),
+					game.score.challenger,
+					game.score.opponent,
+					team_string(&game.teams.opponent)
+				),
+				true,
+			)
+			.field("Format", game.format.to_string(), true)
+			.color(crate::DEFAULT_COLOR)
+			.footer(scoreboard::round_timeout_footer(self.round_timeout));
+
+		if !self.concluded
+		{
+			embed = embed.field(
+				"Waiting on",
+				scoreboard::waiting_on([Side::Challenger, Side::Opponent].into_iter().flat_map(
+					|side| {
+						game.teams[side]
+							.into_iter()
+							.zip(game.selections[side])
+							.map(|(player, selection)| (player, selection.is_some()))
+					},
+				)),
+				false,
+			);
+		}
+
+		embed
+	}
+
+	fn concluded(&self) -> bool
+	{
+		self.concluded
+	}
+}
+
+fn match_embed(
+	game: &TeamGame,
+	winning_side: Side,
+	rating_changes: Option<TeamRatingChanges>,
+) -> CreateEmbed
+{
+	let mut embed = CreateEmbed::new()
+		.title("Game, Set, and Match")
+		.description(format!(
+			"# {} win!",
+			team_string(&game.teams[winning_side])
+		))
+		.field(
+			"Score",
+			format!("{} - {}", game.score.challenger, game.score.opponent),
+			true,
+		)
+		.color(crate::DEFAULT_COLOR);
+
+	if let Some(rating_changes) = rating_changes
+	{
+		let mut ratings_string = String::new();
+		for side in [Side::Challenger, Side::Opponent]
+		{
+			for (player, (old_elo, new_elo)) in game.teams[side].iter().zip(rating_changes[side])
+			{
+				let _ = writeln!(
+					ratings_string,
+					"{}: {old_elo} → {new_elo} ({:+})",
+					player.mention(),
+					new_elo - old_elo
+				);
+			}
+		}
+		embed = embed.field("Ratings", ratings_string, false);
+	}
+
+	embed
+}
+
+fn team_string(team: &Team<UserId>) -> String
+{
+	format!("{} & {}", team[0].mention(), team[1].mention())
+}
+
+/// Rates each player against the average rating of the other team, so carrying a weaker
+/// teammate to a win is worth more than being carried. Each player's rating change is scaled by
+/// their weight
+fn update_leaderboard(
+	leaderboard: &mut Leaderboard,
+	teams: ChallengerOpponentPair<Team<UserId>>,
+	winning_side: Side,
+	rating_system: RatingSystem,
+	weights: ChallengerOpponentPair<Team<f64>>,
+) -> TeamRatingChanges
+{
+	let now = Timestamp::now();
+
+	let old_scores = teams.map(|team| {
+		team.map(|player| leaderboard.score(player).cloned().unwrap_or_default())
+	});
+	let team_scores = old_scores.map_ref(|scores| team_score(scores, now));
+
+	teams.zip(old_scores).zip(weights).gen_map(
+		Side::Challenger,
+		Side::Opponent,
+		|((team, old_scores), weights), side| {
+			let mut changes = [(0, 0); 2];
+			for (((player, old_score), weight), change) in
+				team.iter().zip(old_scores).zip(weights).zip(&mut changes)
+			{
+				let score = leaderboard.score_mut(*player);
+				let new_elo = score.update_weighted_rating(
+					&team_scores[!side],
+					Outcome::from(side == winning_side),
+					rating_system,
+					now,
+					weight,
+				);
+				if side == winning_side
+				{
+					score.increment_wins();
+				}
+				else
+				{
+					score.increment_losses();
+				}
+				*change = (old_score.elo, new_elo);
+			}
+			changes
+		},
+	)
+}
+
+/// A stand-in score for a whole team to be rated against, averaging the players' ratings. Under
+/// Glicko-2 the deviations are combined as a root mean square, and the team counts as having
+/// just played since each player's deviation has already grown for their own time away
+fn team_score(scores: &Team<Score>, now: Timestamp) -> Score
+{
+	let [first, second] = scores.each_ref().map(|score| score.glicko_rating(now));
+	Score {
+		elo: i32::midpoint(scores[0].elo, scores[1].elo),
+		glicko: Some(Glicko2Rating {
+			rating: f64::midpoint(first.rating, second.rating),
+			deviation: f64::midpoint(first.deviation.powi(2), second.deviation.powi(2)).sqrt(),
+			volatility: f64::midpoint(first.volatility, second.volatility),
+		}),
+		last_played: Some(now),
+		..Score::default()
+	}
+}
+
+/// A match of Rock, Paper, Scissors between two teams of two. Every player throws against both
+/// players on the other team, and the team that wins more of those pairings wins the round
+#[derive(Debug, Clone)]
+struct TeamGame
+{
+	format: MatchFormat,
+	teams: ChallengerOpponentPair<Team<UserId>>,
+	selections: ChallengerOpponentPair<Team<Option<Selection>>>,
+	score: ChallengerOpponentPair<u32>,
+	round_count: u32,
+	/// everyone's selection in every round played
+	history: Vec<ChallengerOpponentPair<Team<Selection>>>,
+	/// the team that forfeited the match, if one did
+	forfeited_by: Option<Side>,
+}
+impl TeamGame
+{
+	fn start(teams: ChallengerOpponentPair<Team<UserId>>, format: MatchFormat) -> Self
+	{
+		Self {
+			format,
+			teams,
+			selections: ChallengerOpponentPair::new([None; 2], [None; 2]),
+			score: ChallengerOpponentPair::new(0, 0),
+			round_count: 0,
+			history: Vec::new(),
+			forfeited_by: None,
+		}
+	}
+
+	fn position_of(&self, player: UserId) -> Option<(Side, usize)>
+	{
+		[Side::Challenger, Side::Opponent]
+			.into_iter()
+			.find_map(|side| {
+				let index = self.teams[side].iter().position(|id| *id == player)?;
+				Some((side, index))
+			})
+	}
+
+	/// The team that hasn't finished selecting while the other team has, if only one hasn't
+	fn stalling_side(&self) -> Option<Side>
+	{
+		let ready = self
+			.selections
+			.map_ref(|selections| selections.iter().all(Option::is_some));
+		match (ready.challenger, ready.opponent)
+		{
+			(true, false) => Some(Side::Opponent),
+			(false, true) => Some(Side::Challenger),
+			_ => None,
+		}
+	}
+
+	/// Plays the round once everyone has selected, clearing the selections for the next one
+	fn try_declare_round(&mut self) -> Option<TeamRound>
+	{
+		let selections = self.selections.map_ref(|team| Some([team[0]?, team[1]?]));
+		let selections = ChallengerOpponentPair::new(selections.challenger?, selections.opponent?);
+
+		let mut pairings_won = ChallengerOpponentPair::new(0, 0);
+		for challenger_selection in selections.challenger
+		{
+			for opponent_selection in selections.opponent
+			{
+				match challenger_selection.against(opponent_selection)
+				{
+					Some(Outcome::Win) => pairings_won.challenger += 1,
+					Some(Outcome::Loss) => pairings_won.opponent += 1,
+					None => (),
+				}
+			}
+		}
+
+		let round = TeamRound {
+			selections,
+			pairings_won,
+		};
+		if let Some(winning_side) = round.winner()
+		{
+			self.score[winning_side] += 1;
+		}
+		self.round_count += 1;
+		self.history.push(selections);
+		self.selections = ChallengerOpponentPair::new([None; 2], [None; 2]);
+
+		Some(round)
+	}
+
+	fn winner(&self) -> Option<Side>
+	{
+		let (leader, trailing_score) = if self.score.challenger >= self.score.opponent
+		{
+			(Side::Challenger, self.score.opponent)
+		}
+		else
+		{
+			(Side::Opponent, self.score.challenger)
+		};
+
+		self.format
+			.is_decided(self.score[leader], trailing_score)
+			.then_some(leader)
+	}
+}
+
+struct TeamRound
+{
+	selections: ChallengerOpponentPair<Team<Selection>>,
+	pairings_won: ChallengerOpponentPair<u32>,
+}
+impl TeamRound
+{
+	fn winner(&self) -> Option<Side>
+	{
+		match self.pairings_won.challenger.cmp(&self.pairings_won.opponent)
+		{
+			std::cmp::Ordering::Greater => Some(Side::Challenger),
+			std::cmp::Ordering::Less => Some(Side::Opponent),
+			std::cmp::Ordering::Equal => None,
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests
+{
+	use poise::serenity_prelude::{Timestamp, UserId};
+
+	use super::{team_score, update_leaderboard, ChallengerOpponentPair, Side};
+	use crate::data::{Glicko2Rating, Leaderboard, RatingSystem, Score};
+
+	fn rated(rating: i32, deviation: f64) -> Score
+	{
+		Score {
+			elo: rating,
+			glicko: Some(Glicko2Rating {
+				rating: f64::from(rating),
+				deviation,
+				volatility: Glicko2Rating::DEFAULT_VOLATILITY,
+			}),
+			last_played: Some(Timestamp::now()),
+			..Score::default()
+		}
+	}
+
+	/// How much the first player on an established 1500 team gains from beating a team whose
+	/// players are rated `opponent_rating` with `opponent_deviation`
+	fn winner_gain(
+		opponent_rating: i32,
+		opponent_deviation: f64,
+		rating_system: RatingSystem,
+	) -> i32
+	{
+		let teams = ChallengerOpponentPair::new(
+			[UserId::new(1), UserId::new(2)],
+			[UserId::new(3), UserId::new(4)],
+		);
+		let mut leaderboard = Leaderboard::default();
+		for player in teams.challenger
+		{
+			*leaderboard.score_mut(player) = rated(1500, 60.0);
+		}
+		for player in teams.opponent
+		{
+			*leaderboard.score_mut(player) = rated(opponent_rating, opponent_deviation);
+		}
+
+		let weights = ChallengerOpponentPair::new([1.0; 2], [1.0; 2]);
+		let changes =
+			update_leaderboard(&mut leaderboard, teams, Side::Challenger, rating_system, weights);
+		let (old_rating, new_rating) = changes.challenger[0];
+		new_rating - old_rating
+	}
+
+	#[test]
+	fn team_score_combines_both_players()
+	{
+		let score = team_score(&[rated(1400, 60.0), rated(1600, 80.0)], Timestamp::now());
+		let glicko = score.glicko.unwrap();
+
+		assert_eq!(score.elo, 1500);
+		assert!((glicko.rating - 1500.0).abs() < 0.01);
+		assert!((glicko.deviation - 5000_f64.sqrt()).abs() < 0.01);
+	}
+
+	/// beating a stronger team is worth more under both systems
+	#[test]
+	fn teams_are_rated_against_the_other_teams_rating()
+	{
+		for rating_system in [RatingSystem::Elo, RatingSystem::Glicko2]
+		{
+			let even = winner_gain(1500, 60.0, rating_system);
+			let upset = winner_gain(1800, 60.0, rating_system);
+			assert!(even > 0, "{rating_system:?}");
+			assert!(upset > even, "{rating_system:?}: {upset} vs {even}");
+		}
+	}
+
+	/// elo only goes by the other team's rating, but glicko-2 counts a win over an established
+	/// team for more than one over a team nobody knows much about yet
+	#[test]
+	fn glicko_teams_keep_their_deviation()
+	{
+		assert_eq!(
+			winner_gain(1500, 60.0, RatingSystem::Elo),
+			winner_gain(1500, 350.0, RatingSystem::Elo)
+		);
+		assert!(
+			winner_gain(1500, 60.0, RatingSystem::Glicko2)
+				> winner_gain(1500, 350.0, RatingSystem::Glicko2)
+		);
+	}
+}
@@ -0,0 +1,147 @@
+use std::fmt::Write;
+
+use poise::{
+	serenity_prelude::{CreateAllowedMentions, CreateEmbed, Member, Mentionable},
+	CreateReply,
+};
+
+use crate::{
+	command::ExpectGuildOnly,
+	data::{MatchRecord, Rivalry},
+	Context, Error, Reply,
+};
+
+/// View the head to head record between two Rock, Paper, Scissors players
+#[poise::command(slash_command, prefix_command, guild_only)]
+pub async fn versus(
+	ctx: Context<'_>,
+	#[description = "The first player"] user_a: Member,
+	#[description = "The second player"] user_b: Member,
+) -> Result<(), Error>
+{
+	let guild_id = ctx.guild_id().expect_guild_only();
+	let (player, rival) = (user_a.user.id, user_b.user.id);
+
+	if player == rival
+	{
+		ctx.reply_error("Pick two different players!").await?;
+		return Ok(());
+	}
+
+	let embed = ctx
+		.data()
+		.acquire_lock()
+		.await
+		.guild_data(guild_id)
+		.map(|guild_data| guild_data.match_history().rivalry(player, rival))
+		.filter(|rivalry| rivalry.total_matches() > 0)
+		.map(|rivalry| create_rivalry_embed(&rivalry));
+
+	let Some(embed) = embed
+	else
+	{
+		ctx.reply_error(format!(
+			"{} and {} haven't played each other yet!",
+			player.mention(),
+			rival.mention()
+		))
+		.await?;
+		return Ok(());
+	};
+
+	ctx.send(
+		CreateReply::default()
+			.embed(embed)
+			.reply(true)
+			.allowed_mentions(CreateAllowedMentions::new()),
+	)
+	.await?;
+
+	Ok(())
+}
+
+fn create_rivalry_embed(rivalry: &Rivalry) -> CreateEmbed
+{
+	let (player, rival) = (rivalry.player.mention(), rivalry.rival.mention());
+
+	let mut embed = CreateEmbed::new()
+		.title("Rock Paper Scissors Rivalry")
+		.description(format!(
+			"# {player} vs {rival}\n{}",
+			rivalry.leader().map_or_else(
+				|| String::from("All square!"),
+				|leader| format!("{} leads the rivalry", leader.mention())
+			)
+		))
+		.field(
+			"Matches",
+			format!("{player} {} - {} {rival}", rivalry.wins, rivalry.losses),
+			false,
+		)
+		.field(
+			"Rounds",
+			format!(
+				"{player} {} - {} {rival}",
+				rivalry.rounds_won, rivalry.rounds_lost
+			),
+			false,
+		)
+		.field("Net Rating Exchanged", net_rating_string(rivalry), true)
+		.color(crate::DEFAULT_COLOR);
+
+	if let Some((winner, length)) = rivalry.longest_streak
+	{
+		embed = embed.field(
+			"Longest Streak",
+			format!("{} won {length} in a row", winner.mention()),
+			true,
+		);
+	}
+
+	embed.field(
+		"Recent Matches",
+		recent_matches_string(&rivalry.recent_matches),
+		false,
+	)
+}
+
+fn net_rating_string(rivalry: &Rivalry) -> String
+{
+	let (gainer, loser) = if rivalry.net_rating >= 0
+	{
+		(rivalry.player, rivalry.rival)
+	}
+	else
+	{
+		(rivalry.rival, rivalry.player)
+	};
+
+	match rivalry.net_rating
+	{
+		0 => String::from("Even"),
+		net_rating => format!(
+			"{} is up {} on {}",
+			gainer.mention(),
+			net_rating.unsigned_abs(),
+			loser.mention()
+		),
+	}
+}
+
+fn recent_matches_string(recent_matches: &[&MatchRecord]) -> String
+{
+	let mut matches = String::new();
+	for record in recent_matches
+	{
+		let (winner_rounds, loser_rounds) = record.round_score_of(record.winner);
+		let _ = writeln!(
+			matches,
+			"<t:{}:d> {} won {winner_rounds}-{loser_rounds}{}{}",
+			record.played_at.unix_timestamp(),
+			record.winner.mention(),
+			if record.forfeited { " by forfeit" } else { "" },
+			if record.ranked { "" } else { " (casual)" }
+		);
+	}
+	matches
+}
@@ -16,6 +16,11 @@ pub struct Config
 
 	#[serde(default)]
 	pub status: Option<String>,
+
+	/// font files used to draw leaderboard images, in order of preference. characters missing
+	/// from one font are drawn with the next one that has them
+	#[serde(default)]
+	pub fonts: Vec<PathBuf>,
 }
 impl Default for Config
 {
@@ -26,6 +31,7 @@ impl Default for Config
 			prefix: Self::default_prefix(),
 			data_directory: Self::default_data_dir(),
 			status: None,
+			fonts: Vec::new(),
 		}
 	}
 }
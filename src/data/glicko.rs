@@ -0,0 +1,252 @@
+use std::f64::consts::PI;
+
+/// A Glicko-2 rating. Values are stored on the familiar Glicko scale (the same one ELO uses) and
+/// only converted to the internal Glicko-2 scale while calculating
+///
+/// See <http://www.glicko.net/glicko/glicko2.pdf> for the details of the system
+#[derive(Debug, Clone, Copy, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct Glicko2Rating
+{
+	pub rating: f64,
+	pub deviation: f64,
+	pub volatility: f64,
+}
+impl Glicko2Rating
+{
+	pub const DEFAULT_DEVIATION: f64 = 350.0;
+	pub const DEFAULT_VOLATILITY: f64 = 0.06;
+	/// deviations above this are considered too uncertain to take the rating at face value
+	pub const PROVISIONAL_DEVIATION: f64 = 110.0;
+
+	/// constrains how much volatility can change over time. smaller values mean less change
+	const TAU: f64 = 0.5;
+	/// conversion factor between the glicko and glicko-2 scales
+	const SCALE: f64 = 173.7178;
+	const CONVERGENCE_TOLERANCE: f64 = 0.000_001;
+
+	/// Converts an existing ELO rating. The more games someone has played, the more confident we
+	/// can be that their ELO is accurate, so the deviation starts lower
+	pub fn from_elo(elo: i32, games_played: u32) -> Self
+	{
+		Self {
+			rating: f64::from(elo),
+			deviation: Self::DEFAULT_DEVIATION / (1.0 + f64::from(games_played) / 5.0).sqrt(),
+			volatility: Self::DEFAULT_VOLATILITY,
+		}
+	}
+
+	pub fn is_provisional(&self) -> bool
+	{
+		self.deviation > Self::PROVISIONAL_DEVIATION
+	}
+
+	/// Grows the deviation to account for `periods` rating periods spent not playing
+	pub fn decayed(self, periods: f64) -> Self
+	{
+		let phi = self.deviation / Self::SCALE;
+		let decayed_phi = self.volatility.mul_add(self.volatility * periods, phi * phi).sqrt();
+
+		Self {
+			deviation: f64::min(decayed_phi * Self::SCALE, Self::DEFAULT_DEVIATION),
+			..self
+		}
+	}
+
+	/// The probability of beating `opponent`
+	pub fn expected_score(&self, opponent: &Self) -> f64
+	{
+		let (mu, _) = self.scaled();
+		let (opponent_mu, opponent_phi) = opponent.scaled();
+
+		Self::expectation(mu, opponent_mu, opponent_phi)
+	}
+
+	/// Calculates the new rating after a single game against `opponent`, where `score` is 1.0 for
+	/// a win and 0.0 for a loss
+	pub fn update(&self, opponent: &Self, score: f64) -> Self
+	{
+		self.update_many(&[(*opponent, score)]).0
+	}
+
+	/// Calculates the new rating after a rating period of `games`, each given as the opponent's
+	/// rating and the score against them, along with how far each game moved the rating. Leaves
+	/// the rating as it is if there are no games
+	pub fn update_many(&self, games: &[(Self, f64)]) -> (Self, Vec<f64>)
+	{
+		if games.is_empty()
+		{
+			return (*self, Vec::new());
+		}
+
+		let (mu, phi) = self.scaled();
+		// each game's g(φ) and E(μ, μj, φj), as named in the paper
+		let rated_games = games
+			.iter()
+			.map(|(opponent, score)| {
+				let (opponent_mu, opponent_phi) = opponent.scaled();
+				(
+					Self::g(opponent_phi),
+					Self::expectation(mu, opponent_mu, opponent_phi),
+					*score,
+				)
+			})
+			.collect::<Vec<_>>();
+
+		let variance = rated_games
+			.iter()
+			.map(|(g, expected, _)| g * g * expected * (1.0 - expected))
+			.sum::<f64>()
+			.recip();
+		let improvement = rated_games
+			.iter()
+			.map(|(g, expected, score)| g * (score - expected))
+			.sum::<f64>();
+		let delta = variance * improvement;
+
+		let volatility = self.new_volatility(phi, variance, delta);
+		let pre_period_phi = phi.hypot(volatility);
+		let new_phi = (pre_period_phi.powi(2).recip() + variance.recip())
+			.sqrt()
+			.recip();
+		let new_mu = (new_phi * new_phi).mul_add(improvement, mu);
+		let changes = rated_games
+			.iter()
+			.map(|(g, expected, score)| new_phi * new_phi * g * (score - expected) * Self::SCALE)
+			.collect();
+
+		(
+			Self {
+				rating: new_mu.mul_add(Self::SCALE, 1500.0),
+				deviation: new_phi * Self::SCALE,
+				volatility,
+			},
+			changes,
+		)
+	}
+
+	fn scaled(&self) -> (f64, f64)
+	{
+		(
+			(self.rating - 1500.0) / Self::SCALE,
+			self.deviation / Self::SCALE,
+		)
+	}
+
+	fn g(phi: f64) -> f64
+	{
+		(1.0 + 3.0 * phi * phi / (PI * PI)).sqrt().recip()
+	}
+
+	fn expectation(mu: f64, opponent_mu: f64, opponent_phi: f64) -> f64
+	{
+		(1.0 + (-Self::g(opponent_phi) * (mu - opponent_mu)).exp()).recip()
+	}
+
+	/// step 5 of the glicko-2 paper, finding the new volatility using the illinois algorithm
+	fn new_volatility(&self, phi: f64, variance: f64, delta: f64) -> f64
+	{
+		let a = (self.volatility * self.volatility).ln();
+		let f = |x: f64| {
+			let ex = x.exp();
+			let denominator = phi.mul_add(phi, variance + ex);
+			ex * (delta * delta - phi * phi - variance - ex) / (2.0 * denominator * denominator)
+				- (x - a) / (Self::TAU * Self::TAU)
+		};
+
+		let mut lower = a;
+		let mut upper = if delta * delta > phi.mul_add(phi, variance)
+		{
+			phi.mul_add(-phi, delta * delta - variance).ln()
+		}
+		else
+		{
+			let mut k = 1.0;
+			while f(a - k * Self::TAU) < 0.0
+			{
+				k += 1.0;
+			}
+			a - k * Self::TAU
+		};
+
+		let mut f_lower = f(lower);
+		let mut f_upper = f(upper);
+		while (upper - lower).abs() > Self::CONVERGENCE_TOLERANCE
+		{
+			let next = (lower - upper) * f_lower / (f_upper - f_lower) + lower;
+			let f_next = f(next);
+			if f_next * f_upper <= 0.0
+			{
+				lower = upper;
+				f_lower = f_upper;
+			}
+			else
+			{
+				f_lower /= 2.0;
+			}
+			upper = next;
+			f_upper = f_next;
+		}
+
+		(lower / 2.0).exp()
+	}
+}
+
+#[cfg(test)]
+mod tests
+{
+	use super::Glicko2Rating;
+
+	fn rating(rating: f64, deviation: f64) -> Glicko2Rating
+	{
+		Glicko2Rating {
+			rating,
+			deviation,
+			volatility: Glicko2Rating::DEFAULT_VOLATILITY,
+		}
+	}
+
+	/// the worked example from the glicko-2 paper
+	#[test]
+	fn update_many_matches_paper()
+	{
+		let player = rating(1500.0, 200.0);
+		let games = [
+			(rating(1400.0, 30.0), 1.0),
+			(rating(1550.0, 100.0), 0.0),
+			(rating(1700.0, 300.0), 0.0),
+		];
+
+		let (updated, changes) = player.update_many(&games);
+
+		assert!((updated.rating - 1464.06).abs() < 0.01, "{updated:?}");
+		assert!((updated.deviation - 151.52).abs() < 0.01, "{updated:?}");
+		assert!((updated.volatility - 0.05999).abs() < 0.000_01, "{updated:?}");
+		assert!((changes.iter().sum::<f64>() - (updated.rating - player.rating)).abs() < 1e-9);
+	}
+
+	#[test]
+	fn update_many_without_games_keeps_rating()
+	{
+		let player = rating(1500.0, 200.0);
+		assert_eq!(player.update_many(&[]), (player, Vec::new()));
+	}
+
+	#[test]
+	fn update_moves_toward_result()
+	{
+		let player = rating(1500.0, 200.0);
+		let opponent = rating(1500.0, 200.0);
+
+		assert!(player.update(&opponent, 1.0).rating > player.rating);
+		assert!(player.update(&opponent, 0.0).rating < player.rating);
+		assert!(player.update(&opponent, 1.0).deviation < player.deviation);
+	}
+
+	#[test]
+	fn decay_is_capped()
+	{
+		let player = rating(1500.0, 200.0);
+		assert!(player.decayed(1.0).deviation > player.deviation);
+		assert!(player.decayed(1e9).deviation <= Glicko2Rating::DEFAULT_DEVIATION);
+	}
+}
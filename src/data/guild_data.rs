@@ -1,6 +1,9 @@
-use poise::serenity_prelude::{ChannelId, RoleId};
+use poise::serenity_prelude::{ChannelId, RoleId, Timestamp};
 
-use super::Leaderboard;
+use super::{
+	AuditLog, BotLadder, Leaderboard, MatchHistory, RatingHistory, RpsSettings, Score, Season,
+	SeasonReset,
+};
 
 #[derive(Debug, Default, serde::Deserialize, serde::Serialize)]
 pub struct GuildData
@@ -11,6 +14,18 @@ pub struct GuildData
 	quotes_channel: Option<ChannelId>,
 	#[serde(default)]
 	rps_leaderboard: Leaderboard,
+	#[serde(default)]
+	rps_seasons: Vec<Season>,
+	#[serde(default)]
+	rps_history: MatchHistory,
+	#[serde(default)]
+	rps_rating_history: RatingHistory,
+	#[serde(default)]
+	rps_bot_ladder: BotLadder,
+	#[serde(default)]
+	rps_settings: RpsSettings,
+	#[serde(default)]
+	rps_audit_log: AuditLog,
 }
 impl GuildData
 {
@@ -40,4 +55,88 @@ impl GuildData
 	{
 		&mut self.rps_leaderboard
 	}
+
+	pub fn seasons(&self) -> &[Season]
+	{
+		&self.rps_seasons
+	}
+	pub fn season(&self, name: &str) -> Option<&Season>
+	{
+		self.rps_seasons
+			.iter()
+			.find(|season| season.name.eq_ignore_ascii_case(name))
+	}
+
+	/// When the current season started, or `None` if no season has ended yet
+	pub fn season_started_at(&self) -> Option<Timestamp>
+	{
+		self.rps_seasons.last().map(|season| season.ended_at)
+	}
+
+	/// Archives the current leaderboard standings under `name` and resets the leaderboard
+	pub fn end_season(&mut self, name: String, reset: SeasonReset)
+	{
+		let standings = self
+			.rps_leaderboard
+			.ordered_scores(None)
+			.into_iter()
+			.map(Into::into)
+			.collect();
+
+		let season = Season::new(name, standings);
+		self.rps_leaderboard.reset(reset);
+
+		// so graphs show the drop from the reset instead of it getting lost in the next match
+		self.rps_rating_history
+			.record(season.standings.iter().map(|standing| {
+				let score = self.rps_leaderboard.score(standing.user);
+				(standing.user, score.map_or(Score::BASE_ELO, |score| score.elo))
+			}));
+		self.rps_seasons.push(season);
+	}
+
+	pub fn match_history(&self) -> &MatchHistory
+	{
+		&self.rps_history
+	}
+	pub fn match_history_mut(&mut self) -> &mut MatchHistory
+	{
+		&mut self.rps_history
+	}
+
+	pub fn rating_history(&self) -> &RatingHistory
+	{
+		&self.rps_rating_history
+	}
+	pub fn rating_history_mut(&mut self) -> &mut RatingHistory
+	{
+		&mut self.rps_rating_history
+	}
+
+	pub fn audit_log(&self) -> &AuditLog
+	{
+		&self.rps_audit_log
+	}
+	pub fn audit_log_mut(&mut self) -> &mut AuditLog
+	{
+		&mut self.rps_audit_log
+	}
+
+	pub fn bot_ladder(&self) -> &BotLadder
+	{
+		&self.rps_bot_ladder
+	}
+	pub fn bot_ladder_mut(&mut self) -> &mut BotLadder
+	{
+		&mut self.rps_bot_ladder
+	}
+
+	pub fn rps_settings(&self) -> &RpsSettings
+	{
+		&self.rps_settings
+	}
+	pub fn rps_settings_mut(&mut self) -> &mut RpsSettings
+	{
+		&mut self.rps_settings
+	}
 }
@@ -0,0 +1,193 @@
+use std::{
+	collections::HashMap,
+	time::{Duration, Instant},
+};
+
+use poise::serenity_prelude::{ChannelId, GuildId, UserId};
+
+use super::RpsActivity;
+
+#[derive(Debug, Default)]
+pub struct Matchmaking
+{
+	queues: HashMap<GuildId, MatchmakingQueue>,
+	activity: HashMap<GuildId, RpsActivity>,
+}
+impl Matchmaking
+{
+	pub fn queue_mut(&mut self, guild_id: GuildId) -> &mut MatchmakingQueue
+	{
+		self.queues.entry(guild_id).or_default()
+	}
+
+	pub fn activity_mut(&mut self, guild_id: GuildId) -> &mut RpsActivity
+	{
+		self.activity.entry(guild_id).or_default()
+	}
+
+	/// The first of `players` who is busy, either waiting in the queue or in a game
+	pub fn first_busy(&mut self, guild_id: GuildId, players: &[UserId]) -> Option<UserId>
+	{
+		let queue = self.queue_mut(guild_id);
+		players
+			.iter()
+			.find(|player| queue.contains(**player))
+			.copied()
+			.or_else(|| self.activity_mut(guild_id).first_playing(players))
+	}
+
+	/// Marks `players` as being in a game in `channel`, unless one of them is busy, in which case
+	/// nobody is marked and that player is returned
+	pub fn try_start_playing(
+		&mut self,
+		guild_id: GuildId,
+		players: &[UserId],
+		channel: ChannelId,
+	) -> Result<(), UserId>
+	{
+		let queue = self.queue_mut(guild_id);
+		if let Some(player) = players.iter().find(|player| queue.contains(**player))
+		{
+			return Err(*player);
+		}
+		self.activity_mut(guild_id)
+			.try_start_playing(players, channel)
+	}
+}
+
+#[derive(Debug, Default)]
+pub struct MatchmakingQueue
+{
+	entries: Vec<QueueEntry>,
+	matched: HashMap<UserId, Pairing>,
+}
+impl MatchmakingQueue
+{
+	pub fn contains(&self, user: UserId) -> bool
+	{
+		self.entries.iter().any(|entry| entry.user == user)
+	}
+
+	pub fn join(&mut self, user: UserId, elo: i32, channel: ChannelId)
+	{
+		self.matched.remove(&user);
+		self.entries.push(QueueEntry {
+			user,
+			elo,
+			channel,
+			joined_at: Instant::now(),
+		});
+	}
+
+	pub fn leave(&mut self, user: UserId) -> bool
+	{
+		let len_before = self.entries.len();
+		self.entries.retain(|entry| entry.user != user);
+		self.entries.len() != len_before
+	}
+
+	/// Looks for the queued player closest in rating to `user`. Two players can be paired once
+	/// their rating difference falls within the search window of whichever of them has been
+	/// waiting longer. If a pairing is found, both players are removed from the queue and the
+	/// partner can pick it up with [`Self::take_pairing`]
+	pub fn try_pair(&mut self, user: UserId) -> Option<Pairing>
+	{
+		let own_entry = self.entries.iter().find(|entry| entry.user == user)?;
+
+		let partner = self
+			.entries
+			.iter()
+			.filter(|entry| entry.user != user)
+			.filter(|entry| {
+				(entry.elo - own_entry.elo).abs()
+					<= i32::max(entry.search_window(), own_entry.search_window())
+			})
+			.min_by_key(|entry| (entry.elo - own_entry.elo).abs())?;
+
+		let pairing = if partner.joined_at <= own_entry.joined_at
+		{
+			Pairing {
+				earlier: partner.clone(),
+				later: own_entry.clone(),
+			}
+		}
+		else
+		{
+			Pairing {
+				earlier: own_entry.clone(),
+				later: partner.clone(),
+			}
+		};
+
+		self.leave(pairing.earlier.user);
+		self.leave(pairing.later.user);
+		self.matched
+			.insert(pairing.partner_of(user).user, pairing.clone());
+
+		Some(pairing)
+	}
+
+	pub fn take_pairing(&mut self, user: UserId) -> Option<Pairing>
+	{
+		self.matched.remove(&user)
+	}
+
+	/// Calls off `pairing` because `busy` couldn't play, putting the other player back in the
+	/// queue with their place kept
+	pub fn undo_pairing(&mut self, pairing: &Pairing, busy: UserId)
+	{
+		self.matched.remove(&pairing.earlier.user);
+		self.matched.remove(&pairing.later.user);
+		self.entries.push(pairing.partner_of(busy).clone());
+	}
+}
+
+#[derive(Debug, Clone)]
+pub struct QueueEntry
+{
+	pub user: UserId,
+	pub elo: i32,
+	pub channel: ChannelId,
+	pub joined_at: Instant,
+}
+impl QueueEntry
+{
+	const BASE_SEARCH_WINDOW: i32 = 50;
+	const MAX_SEARCH_WINDOW: i32 = 800;
+	/// how far the search window grows for every [`Self::WINDOW_GROWTH_INTERVAL`] spent waiting
+	const WINDOW_GROWTH: i32 = 25;
+	const WINDOW_GROWTH_INTERVAL: Duration = Duration::from_secs(10);
+
+	#[allow(clippy::cast_possible_truncation)]
+	pub fn search_window(&self) -> i32
+	{
+		let intervals_waited = (self.joined_at.elapsed().as_secs()
+			/ Self::WINDOW_GROWTH_INTERVAL.as_secs())
+		.min(u64::from(u32::MAX)) as i32;
+
+		Self::BASE_SEARCH_WINDOW
+			.saturating_add(intervals_waited.saturating_mul(Self::WINDOW_GROWTH))
+			.min(Self::MAX_SEARCH_WINDOW)
+	}
+}
+
+#[derive(Debug, Clone)]
+pub struct Pairing
+{
+	pub earlier: QueueEntry,
+	pub later: QueueEntry,
+}
+impl Pairing
+{
+	pub fn partner_of(&self, user: UserId) -> &QueueEntry
+	{
+		if self.earlier.user == user
+		{
+			&self.later
+		}
+		else
+		{
+			&self.earlier
+		}
+	}
+}
@@ -1,27 +1,57 @@
 pub mod config;
+mod glicko;
 mod guild_data;
+mod matchmaking;
+mod rps_activity;
+mod rps_audit;
+mod rps_bot_ladder;
+mod rps_history;
 mod rps_leaderboard;
+mod rps_rank_roles;
+mod rps_rating_history;
+mod rps_seasons;
+mod rps_settings;
 
 use futures::lock::{Mutex, MutexGuard};
+pub use glicko::*;
 pub use guild_data::*;
+pub use matchmaking::*;
+pub use rps_activity::*;
+pub use rps_audit::*;
+pub use rps_bot_ladder::*;
+pub use rps_history::*;
 pub use rps_leaderboard::*;
+pub use rps_rank_roles::*;
+pub use rps_rating_history::*;
+pub use rps_seasons::*;
+pub use rps_settings::*;
 
-use std::{collections::HashMap, path::PathBuf, sync::Arc};
+use std::{
+	collections::HashMap,
+	path::{Path, PathBuf},
+	sync::Arc,
+};
 
 use poise::serenity_prelude::GuildId;
 
+use crate::render::Fonts;
+
 pub struct Data
 {
 	status: Option<String>,
 	data_manager: Arc<Mutex<DataManager>>,
+	matchmaking: Mutex<Matchmaking>,
+	fonts: Option<Fonts>,
 }
 impl Data
 {
-	pub fn new(status: Option<String>, data_manager: DataManager) -> Self
+	pub fn new(status: Option<String>, data_manager: DataManager, fonts: Option<Fonts>) -> Self
 	{
 		Self {
 			status,
 			data_manager: Arc::new(Mutex::new(data_manager)),
+			matchmaking: Mutex::new(Matchmaking::default()),
+			fonts,
 		}
 	}
 
@@ -30,10 +60,21 @@ impl Data
 		self.status.as_deref()
 	}
 
+	/// The fonts used for rendering images, if any were configured
+	pub fn fonts(&self) -> Option<&Fonts>
+	{
+		self.fonts.as_ref()
+	}
+
 	pub async fn acquire_lock(&self) -> MutexGuard<DataManager>
 	{
 		self.data_manager.lock().await
 	}
+
+	pub async fn acquire_matchmaking_lock(&self) -> MutexGuard<Matchmaking>
+	{
+		self.matchmaking.lock().await
+	}
 }
 
 #[derive(Debug)]
@@ -42,6 +83,8 @@ pub struct DataManager
 	dir_path: PathBuf,
 	unsynced: bool,
 	guild_data: HashMap<GuildId, GuildData>,
+	/// ratings from ranked matches across every server that hasn't opted out of the global ladder
+	global_leaderboard: Leaderboard,
 }
 impl DataManager
 {
@@ -51,19 +94,58 @@ impl DataManager
 			dir_path,
 			unsynced: false,
 			guild_data: HashMap::default(),
+			global_leaderboard: Leaderboard::default(),
 		}
 	}
 	pub fn load_from_dir(dir_path: PathBuf) -> Result<Self, DataLoadError>
 	{
 		let guild_data_path = dir_path.join("guild_data.toml");
+		let guild_data = toml::from_str(&std::fs::read_to_string(guild_data_path)?)?;
+		let global_leaderboard = Self::load_global_leaderboard(&dir_path);
 
 		Ok(Self {
 			dir_path,
 			unsynced: false,
-			guild_data: toml::from_str(&std::fs::read_to_string(guild_data_path)?)?,
+			guild_data,
+			global_leaderboard,
 		})
 	}
 
+	/// The global leaderboard came later than guild data, so it's loaded on its own. A missing or
+	/// unreadable file starts it over instead of failing the whole load, which would throw away
+	/// every server's data on the next sync. An unreadable file is set aside rather than
+	/// overwritten, so it can still be recovered by hand
+	fn load_global_leaderboard(dir_path: &Path) -> Leaderboard
+	{
+		let path = dir_path.join("global_leaderboard.toml");
+		let loaded = std::fs::read_to_string(&path)
+			.map_err(DataLoadError::from)
+			.and_then(|file_content| Ok(toml::from_str(&file_content)?));
+
+		match loaded
+		{
+			Ok(leaderboard) => leaderboard,
+			Err(DataLoadError::IoError(err)) if err.kind() == std::io::ErrorKind::NotFound =>
+			{
+				Leaderboard::default()
+			}
+			Err(err) =>
+			{
+				let backup_path = path.with_extension("toml.bak");
+				log::warn!(
+					"Could not load the global leaderboard, starting it over and moving the old \
+					 file to {}! {err:?}",
+					backup_path.display()
+				);
+				if let Err(err) = std::fs::rename(&path, &backup_path)
+				{
+					log::warn!("Could not set aside the old global leaderboard file! {err}");
+				}
+				Leaderboard::default()
+			}
+		}
+	}
+
 	pub fn load_or_create_from_dir(dir_path: PathBuf) -> Self
 	{
 		let _ = std::fs::create_dir_all(&dir_path);
@@ -85,6 +167,21 @@ impl DataManager
 		self.guild_data.entry(guild_id).or_default()
 	}
 
+	pub fn global_leaderboard(&self) -> &Leaderboard
+	{
+		&self.global_leaderboard
+	}
+	pub fn global_leaderboard_mut(&mut self) -> &mut Leaderboard
+	{
+		if self.unsynced
+		{
+			self.sync();
+		}
+		self.unsynced = true;
+
+		&mut self.global_leaderboard
+	}
+
 	pub fn sync(&mut self)
 	{
 		std::fs::write(
@@ -93,6 +190,12 @@ impl DataManager
 				.unwrap_or_else(|err| panic!("Unable to serialize toml data! {err}")),
 		)
 		.expect("Unable to write to guild data file!");
+		std::fs::write(
+			self.dir_path.join("global_leaderboard.toml"),
+			toml::to_string_pretty(&self.global_leaderboard)
+				.unwrap_or_else(|err| panic!("Unable to serialize toml data! {err}")),
+		)
+		.expect("Unable to write to global leaderboard file!");
 		self.unsynced = false;
 	}
 }
@@ -106,3 +209,28 @@ pub enum DataLoadError
 	#[error("Could not parse data from file!")]
 	TomlError(#[from] toml::de::Error),
 }
+
+#[cfg(test)]
+mod tests
+{
+	use super::DataManager;
+
+	#[test]
+	fn unreadable_global_leaderboard_keeps_guild_data()
+	{
+		let dir = std::env::temp_dir()
+			.join(format!("appalachia-load-test-{}", std::process::id()));
+		std::fs::create_dir_all(&dir).unwrap();
+		std::fs::write(dir.join("guild_data.toml"), "[1234]\n").unwrap();
+		std::fs::write(dir.join("global_leaderboard.toml"), "not = [valid").unwrap();
+
+		let data = DataManager::load_from_dir(dir.clone());
+		let backed_up = dir.join("global_leaderboard.toml.bak").exists();
+		std::fs::remove_dir_all(&dir).unwrap();
+
+		let data = data.unwrap();
+		assert!(data.guild_data(1234.into()).is_some());
+		assert_eq!(data.global_leaderboard().players().count(), 0);
+		assert!(backed_up);
+	}
+}
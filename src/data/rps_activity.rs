@@ -0,0 +1,126 @@
+use std::collections::HashMap;
+
+use poise::serenity_prelude::{ChannelId, MessageId, Timestamp, UserId};
+
+/// The challenges waiting on a response and the games being played in a server. Nothing here is
+/// saved, since none of it survives a restart anyway
+#[derive(Debug, Default)]
+pub struct RpsActivity
+{
+	challenges: Vec<PendingChallenge>,
+	/// everyone in a game, along with the channel it's being played in
+	playing: HashMap<UserId, ChannelId>,
+}
+impl RpsActivity
+{
+	pub fn challenges(&self) -> &[PendingChallenge]
+	{
+		&self.challenges
+	}
+
+	/// The challenge `challenger` has issued, if they're waiting on one
+	pub fn challenge_by(&self, challenger: UserId) -> Option<&PendingChallenge>
+	{
+		self.challenges
+			.iter()
+			.find(|challenge| challenge.challenger == challenger)
+	}
+
+	pub fn is_pending(&self, message: MessageId) -> bool
+	{
+		self.challenges
+			.iter()
+			.any(|challenge| challenge.message == message)
+	}
+
+	pub fn issue(&mut self, challenge: PendingChallenge)
+	{
+		self.challenges.push(challenge);
+	}
+
+	/// Removes the challenge on `message`, returning whether it was still pending
+	pub fn resolve(&mut self, message: MessageId) -> bool
+	{
+		let len = self.challenges.len();
+		self.challenges
+			.retain(|challenge| challenge.message != message);
+		self.challenges.len() != len
+	}
+
+	/// Removes the challenge `challenger` has issued, if they have one
+	pub fn withdraw(&mut self, challenger: UserId) -> Option<PendingChallenge>
+	{
+		let index = self
+			.challenges
+			.iter()
+			.position(|challenge| challenge.challenger == challenger)?;
+		Some(self.challenges.remove(index))
+	}
+
+	/// The channel `player` is playing a game in, if they're in one
+	pub fn game_of(&self, player: UserId) -> Option<ChannelId>
+	{
+		self.playing.get(&player).copied()
+	}
+
+	pub fn is_playing(&self, player: UserId) -> bool
+	{
+		self.playing.contains_key(&player)
+	}
+
+	/// The first of `players` who is already in a game, if any are
+	pub fn first_playing(&self, players: &[UserId]) -> Option<UserId>
+	{
+		players
+			.iter()
+			.find(|player| self.is_playing(**player))
+			.copied()
+	}
+
+	/// Marks `players` as being in a game in `channel`, unless one of them already is, in which
+	/// case nobody is marked and that player is returned. Any challenge one of them issued is
+	/// withdrawn, since it could otherwise be accepted while they're busy with this game
+	pub fn try_start_playing(
+		&mut self,
+		players: &[UserId],
+		channel: ChannelId,
+	) -> Result<(), UserId>
+	{
+		if let Some(player) = self.first_playing(players)
+		{
+			return Err(player);
+		}
+
+		self.challenges
+			.retain(|challenge| !players.contains(&challenge.challenger));
+		self.playing
+			.extend(players.iter().map(|player| (*player, channel)));
+		Ok(())
+	}
+
+	pub fn stop_playing(&mut self, players: &[UserId])
+	{
+		for player in players
+		{
+			self.playing.remove(player);
+		}
+	}
+}
+
+#[derive(Debug, Clone)]
+pub struct PendingChallenge
+{
+	pub challenger: UserId,
+	/// `None` for open challenges anyone can accept
+	pub opponent: Option<UserId>,
+	pub channel: ChannelId,
+	pub message: MessageId,
+	pub issued_at: Timestamp,
+}
+impl PendingChallenge
+{
+	pub fn involves(&self, player: UserId) -> bool
+	{
+		self.challenger == player || self.opponent == Some(player)
+	}
+}
@@ -0,0 +1,87 @@
+use std::fmt::Display;
+
+use poise::serenity_prelude::{Mentionable, Timestamp, UserId};
+
+use super::ScoreStat;
+
+/// Every change moderators have made to a server's rps records by hand, oldest first
+#[derive(Debug, Default, serde::Deserialize, serde::Serialize)]
+#[serde(transparent)]
+pub struct AuditLog
+{
+	entries: Vec<AuditEntry>,
+}
+impl AuditLog
+{
+	pub fn record(&mut self, moderator: UserId, action: AuditAction, reason: Option<String>)
+	{
+		self.entries.push(AuditEntry {
+			moderator,
+			action,
+			reason,
+			at: Timestamp::now(),
+		});
+	}
+
+	pub fn entries(&self) -> &[AuditEntry]
+	{
+		&self.entries
+	}
+}
+
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
+pub struct AuditEntry
+{
+	pub moderator: UserId,
+	pub action: AuditAction,
+	#[serde(default)]
+	pub reason: Option<String>,
+	pub at: Timestamp,
+}
+
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
+pub enum AuditAction
+{
+	/// the player's score was wiped from the leaderboard
+	ResetPlayer { player: UserId },
+	/// one part of the player's score was changed from `old` to `new`
+	SetStat
+	{
+		player: UserId,
+		stat: ScoreStat,
+		old: i32,
+		new: i32,
+	},
+	/// everyone in `players` had left the server and was taken off the leaderboard
+	PruneDeparted { players: Vec<UserId> },
+	/// `alt`'s score and matches were folded into `main`'s
+	MergeAccounts { alt: UserId, main: UserId },
+	VoidMatch { id: usize },
+	DismissFlag { id: usize },
+}
+impl Display for AuditAction
+{
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result
+	{
+		match self
+		{
+			Self::ResetPlayer { player } => write!(f, "Reset {}", player.mention()),
+			Self::SetStat {
+				player,
+				stat,
+				old,
+				new,
+			} => write!(f, "Changed {}'s {stat} from {old} to {new}", player.mention()),
+			Self::PruneDeparted { players } =>
+			{
+				write!(f, "Removed {} players who left the server", players.len())
+			}
+			Self::MergeAccounts { alt, main } =>
+			{
+				write!(f, "Merged {} into {}", alt.mention(), main.mention())
+			}
+			Self::VoidMatch { id } => write!(f, "Voided match #{id}"),
+			Self::DismissFlag { id } => write!(f, "Dismissed the flag on match #{id}"),
+		}
+	}
+}
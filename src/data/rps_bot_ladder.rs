@@ -0,0 +1,128 @@
+use std::collections::HashMap;
+
+use poise::serenity_prelude::UserId;
+
+use super::{LeaderboardEntry, LeaderboardSort, Outcome, Score};
+
+/// Records of every player's matches against the bot, kept separately from the main leaderboard
+#[derive(Debug, Default, serde::Deserialize, serde::Serialize)]
+#[serde(transparent)]
+pub struct BotLadder
+{
+	map: HashMap<UserId, BotRecord>,
+}
+impl BotLadder
+{
+	pub fn record(&self, player: UserId) -> Option<&BotRecord>
+	{
+		self.map.get(&player)
+	}
+	pub fn record_mut(&mut self, player: UserId) -> &mut BotRecord
+	{
+		self.map.entry(player).or_default()
+	}
+
+	pub fn ordered_scores(
+		&self,
+		difficulty: BotDifficulty,
+		sort: LeaderboardSort,
+		limit: Option<usize>,
+	) -> Vec<LeaderboardEntry<'_>>
+	{
+		super::rank_scores(
+			self.map
+				.iter()
+				.map(|(id, record)| (*id, &record[difficulty])),
+			sort,
+			limit,
+		)
+	}
+}
+
+#[derive(Debug, Default, serde::Deserialize, serde::Serialize)]
+pub struct BotRecord
+{
+	#[serde(default)]
+	easy: Score,
+	#[serde(default)]
+	normal: Score,
+	#[serde(default)]
+	hard: Score,
+}
+impl BotRecord
+{
+	/// Records a match against the bot, returning the player's rating on that difficulty before
+	/// and after
+	pub fn record_match(&mut self, difficulty: BotDifficulty, outcome: Outcome) -> (i32, i32)
+	{
+		let score = &mut self[difficulty];
+		let old_elo = score.elo;
+		let new_elo = score.update_elo(difficulty.rating(), outcome);
+
+		match outcome
+		{
+			Outcome::Win => score.increment_wins(),
+			Outcome::Loss => score.increment_losses(),
+		}
+
+		(old_elo, new_elo)
+	}
+}
+impl std::ops::Index<BotDifficulty> for BotRecord
+{
+	type Output = Score;
+
+	fn index(&self, index: BotDifficulty) -> &Self::Output
+	{
+		match index
+		{
+			BotDifficulty::Easy => &self.easy,
+			BotDifficulty::Normal => &self.normal,
+			BotDifficulty::Hard => &self.hard,
+		}
+	}
+}
+impl std::ops::IndexMut<BotDifficulty> for BotRecord
+{
+	fn index_mut(&mut self, index: BotDifficulty) -> &mut Self::Output
+	{
+		match index
+		{
+			BotDifficulty::Easy => &mut self.easy,
+			BotDifficulty::Normal => &mut self.normal,
+			BotDifficulty::Hard => &mut self.hard,
+		}
+	}
+}
+
+#[derive(
+	Debug,
+	Default,
+	Clone,
+	Copy,
+	PartialEq,
+	Eq,
+	poise::ChoiceParameter,
+	serde::Deserialize,
+	serde::Serialize,
+)]
+pub enum BotDifficulty
+{
+	#[default]
+	Easy,
+	Normal,
+	Hard,
+}
+impl BotDifficulty
+{
+	/// the fixed rating the bot plays at on this difficulty
+	pub fn rating(self) -> i32
+	{
+		match self
+		{
+			Self::Easy => 1200,
+			Self::Normal => Score::BASE_ELO,
+			Self::Hard => 1800,
+		}
+	}
+}
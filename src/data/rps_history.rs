@@ -0,0 +1,672 @@
+use std::{collections::HashMap, fmt::Display};
+
+use poise::serenity_prelude::{Timestamp, UserId};
+use strum::IntoEnumIterator;
+use strum_macros::{EnumIter, IntoStaticStr};
+
+use super::Outcome;
+
+/// Every match played between players in a server, oldest first
+#[derive(Debug, Default, serde::Deserialize, serde::Serialize)]
+#[serde(transparent)]
+pub struct MatchHistory
+{
+	matches: Vec<MatchRecord>,
+}
+impl MatchHistory
+{
+	/// how far back, in seconds, earlier ranked matches between the same two players count
+	/// against the rating they can gain from each other
+	const REPEAT_WINDOW: i64 = 24 * 60 * 60;
+	/// the share of the usual rating change kept for every ranked match the pair already played
+	/// within the window
+	const REPEAT_DAMPING: f64 = 0.7;
+	/// repeated matches never count for less than this share of the usual rating change
+	const MIN_REPEAT_WEIGHT: f64 = 0.1;
+	/// how many ranked matches a pair has to play within the window before a one-sided record
+	/// between them gets flagged
+	const LOPSIDED_MIN_MATCHES: u32 = 5;
+	/// the share of those matches one player has to win for the pairing to get flagged
+	const LOPSIDED_WIN_SHARE: f64 = 0.8;
+
+	/// Records a finished match, returning its id
+	pub fn record(&mut self, record: MatchRecord) -> usize
+	{
+		self.matches.push(record);
+		self.matches.len()
+	}
+
+	/// Records every pairing from a free-for-all or team game, tied together so they're treated as
+	/// one game. Returns their ids, in the same order
+	pub fn record_game(&mut self, records: impl IntoIterator<Item = MatchRecord>) -> Vec<usize>
+	{
+		let game_id = self.matches.len() + 1;
+		records
+			.into_iter()
+			.map(|record| {
+				self.record(MatchRecord {
+					game_id: Some(game_id),
+					..record
+				})
+			})
+			.collect()
+	}
+
+	pub fn matches(&self) -> &[MatchRecord]
+	{
+		&self.matches
+	}
+
+	/// The match with the given id. Ids count up from 1 in the order matches were played
+	pub fn get(&self, id: usize) -> Option<&MatchRecord>
+	{
+		self.matches.get(id.checked_sub(1)?)
+	}
+	pub fn get_mut(&mut self, id: usize) -> Option<&mut MatchRecord>
+	{
+		self.matches.get_mut(id.checked_sub(1)?)
+	}
+
+	/// The ids of every pairing from the same game as match `id`, which is just `id` itself for a
+	/// one on one match
+	pub fn game_of(&self, id: usize) -> Vec<usize>
+	{
+		match self.get(id).and_then(|record| record.game_id)
+		{
+			Some(game_id) => self
+				.matches
+				.iter()
+				.enumerate()
+				.skip(game_id - 1)
+				.take_while(|(_, record)| record.game_id == Some(game_id))
+				.map(|(i, _)| i + 1)
+				.collect(),
+			None => vec![id],
+		}
+	}
+
+	/// Every flagged match along with its id, oldest first
+	pub fn flagged(&self) -> impl DoubleEndedIterator<Item = (usize, &MatchRecord)>
+	{
+		self.matches
+			.iter()
+			.enumerate()
+			.filter(|(_, record)| record.flag.is_some())
+			.map(|(i, record)| (i + 1, record))
+	}
+
+	/// The ranked matches `player` and `rival` played against each other within the repeat
+	/// window before `now`
+	fn recent_ranked_matches(
+		&self,
+		player: UserId,
+		rival: UserId,
+		now: Timestamp,
+	) -> impl Iterator<Item = &MatchRecord>
+	{
+		self.matches_of(player)
+			.rev()
+			.take_while(move |record| {
+				now.unix_timestamp() - record.played_at.unix_timestamp() <= Self::REPEAT_WINDOW
+			})
+			.filter(move |record| record.ranked && record.opponent_of(player) == rival)
+	}
+
+	/// How much of the usual rating change a ranked match between `player` and `rival` should
+	/// count for. Every ranked match they've already played against each other recently counts
+	/// for less, so friends can't farm rating off of each other
+	#[allow(clippy::cast_possible_truncation, clippy::cast_possible_wrap)]
+	pub fn repeat_weight(&self, player: UserId, rival: UserId, now: Timestamp) -> f64
+	{
+		let repeats = self.recent_ranked_matches(player, rival, now).count();
+		Self::REPEAT_DAMPING
+			.powi(repeats.min(i32::MAX as usize) as i32)
+			.max(Self::MIN_REPEAT_WEIGHT)
+	}
+
+	/// Checks a finished match for signs of win trading before it's recorded, returning why it
+	/// looks suspicious if it does
+	pub fn suspicion(&self, record: &MatchRecord) -> Option<FlagReason>
+	{
+		if !record.ranked
+		{
+			return None;
+		}
+		if record.forfeited && record.rounds.is_empty()
+		{
+			return Some(FlagReason::InstantForfeit);
+		}
+
+		let loser = record.opponent_of(record.winner);
+		let (matches, wins) = self
+			.recent_ranked_matches(record.winner, loser, record.played_at)
+			.fold((1, 1), |(matches, wins), earlier| {
+				(matches + 1, wins + u32::from(earlier.winner == record.winner))
+			});
+
+		(matches >= Self::LOPSIDED_MIN_MATCHES
+			&& f64::from(wins) / f64::from(matches) >= Self::LOPSIDED_WIN_SHARE)
+			.then_some(FlagReason::LopsidedPairing { matches, wins })
+	}
+
+	/// Every match `player` took part in that hasn't been voided, oldest first
+	pub fn matches_of(&self, player: UserId) -> impl DoubleEndedIterator<Item = &MatchRecord>
+	{
+		self.matches
+			.iter()
+			.filter(move |record| !record.voided && record.involves(player))
+	}
+
+	/// Moves every match `alt` played over to `main`. Matches the two played against each other
+	/// are left alone, since a player can't have played themselves, so they should be voided first
+	pub fn replace_player(&mut self, alt: UserId, main: UserId)
+	{
+		let replace = |player: &mut UserId| {
+			if *player == alt
+			{
+				*player = main;
+			}
+		};
+
+		for record in self
+			.matches
+			.iter_mut()
+			.filter(|record| record.involves(alt) && !record.involves(main))
+		{
+			replace(&mut record.challenger);
+			replace(&mut record.opponent);
+			replace(&mut record.winner);
+		}
+	}
+
+	pub fn player_stats(&self, player: UserId) -> PlayerStats
+	{
+		let mut stats = PlayerStats::default();
+		let mut matches_against = HashMap::<UserId, u32>::new();
+		let mut losses_against = HashMap::<UserId, u32>::new();
+		// every pairing from a free-for-all or team game has the same throws, so each game's
+		// throws are only counted from its longest pairing
+		let mut game_throws = HashMap::<usize, &MatchRecord>::new();
+
+		for record in self.matches_of(player)
+		{
+			let opponent = record.opponent_of(player);
+			*matches_against.entry(opponent).or_default() += 1;
+			if record.winner != player
+			{
+				*losses_against.entry(opponent).or_default() += 1;
+			}
+
+			for (selection, opponent_selection) in record.selections_of(player)
+			{
+				match selection.against(opponent_selection)
+				{
+					Some(Outcome::Win) => stats.rounds_won += 1,
+					Some(Outcome::Loss) => stats.rounds_lost += 1,
+					None => stats.rounds_tied += 1,
+				}
+			}
+
+			match record.game_id
+			{
+				Some(game_id) =>
+				{
+					let longest = game_throws.entry(game_id).or_insert(record);
+					if record.rounds.len() > longest.rounds.len()
+					{
+						*longest = record;
+					}
+				}
+				None => stats.throws.count(record, player),
+			}
+		}
+		for record in game_throws.into_values()
+		{
+			stats.throws.count(record, player);
+		}
+
+		// a free-for-all or team game only counts as a win if every pairing in it was won
+		let games = self.matches_of(player).collect::<Vec<_>>();
+		stats.recent_form = games
+			.chunk_by(|a, b| a.game_id.is_some() && a.game_id == b.game_id)
+			.rev()
+			.take(PlayerStats::RECENT_FORM_LENGTH)
+			.map(|game| Outcome::from(game.iter().all(|record| record.winner == player)))
+			.collect();
+		stats.favorite_opponent = most_frequent(matches_against);
+		stats.nemesis = most_frequent(losses_against);
+
+		stats
+	}
+
+	pub fn rivalry(&self, player: UserId, rival: UserId) -> Rivalry<'_>
+	{
+		let mut rivalry = Rivalry {
+			player,
+			rival,
+			wins: 0,
+			losses: 0,
+			rounds_won: 0,
+			rounds_lost: 0,
+			net_rating: 0,
+			longest_streak: None,
+			recent_matches: Vec::new(),
+		};
+		let mut streak: Option<(UserId, u32)> = None;
+
+		for record in self
+			.matches_of(player)
+			.filter(|record| record.opponent_of(player) == rival)
+		{
+			match record.outcome_for(player)
+			{
+				Outcome::Win => rivalry.wins += 1,
+				Outcome::Loss => rivalry.losses += 1,
+			}
+
+			let (rounds_won, rounds_lost) = record.round_score_of(player);
+			rivalry.rounds_won += rounds_won;
+			rivalry.rounds_lost += rounds_lost;
+			rivalry.net_rating += record.rating_change_of(player).unwrap_or_default();
+
+			streak = match streak
+			{
+				Some((winner, length)) if winner == record.winner => Some((winner, length + 1)),
+				_ => Some((record.winner, 1)),
+			};
+			if streak.map(|(_, length)| length)
+				> rivalry.longest_streak.map(|(_, length)| length)
+			{
+				rivalry.longest_streak = streak;
+			}
+
+			rivalry.recent_matches.push(record);
+		}
+
+		rivalry.recent_matches.reverse();
+		rivalry
+			.recent_matches
+			.truncate(Rivalry::RECENT_MATCHES_LENGTH);
+		rivalry
+	}
+}
+
+/// The head to head record between two players, from the first player's point of view
+#[derive(Debug, Clone)]
+pub struct Rivalry<'a>
+{
+	pub player: UserId,
+	pub rival: UserId,
+	pub wins: u32,
+	pub losses: u32,
+	pub rounds_won: u32,
+	pub rounds_lost: u32,
+	/// rating gained from ranked matches against the rival, less rating lost to them
+	pub net_rating: i32,
+	/// the most matches in a row either player won against the other, and who won them
+	pub longest_streak: Option<(UserId, u32)>,
+	/// the most recent matches between the two, newest first
+	pub recent_matches: Vec<&'a MatchRecord>,
+}
+impl Rivalry<'_>
+{
+	pub const RECENT_MATCHES_LENGTH: usize = 5;
+
+	pub fn total_matches(&self) -> u32
+	{
+		self.wins + self.losses
+	}
+
+	/// Whoever has won more matches against the other, if anyone
+	pub fn leader(&self) -> Option<UserId>
+	{
+		match self.wins.cmp(&self.losses)
+		{
+			std::cmp::Ordering::Greater => Some(self.player),
+			std::cmp::Ordering::Less => Some(self.rival),
+			std::cmp::Ordering::Equal => None,
+		}
+	}
+}
+
+/// Picks the player with the highest count, breaking ties by id so the result doesn't change
+/// between calls
+fn most_frequent(counts: HashMap<UserId, u32>) -> Option<(UserId, u32)>
+{
+	counts
+		.into_iter()
+		.max_by_key(|(id, count)| (*count, std::cmp::Reverse(*id)))
+}
+
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
+pub struct MatchRecord
+{
+	pub challenger: UserId,
+	pub opponent: UserId,
+	pub winner: UserId,
+	pub forfeited: bool,
+	pub ranked: bool,
+	pub played_at: Timestamp,
+	/// the challenger's selection, then the opponent's, for every decided round
+	pub rounds: Vec<(Selection, Selection)>,
+	/// how much the challenger's rating changed, then the opponent's. `None` for casual matches
+	#[serde(default)]
+	pub rating_changes: Option<(i32, i32)>,
+	/// why the match was flagged for review, if it looked like win trading
+	#[serde(default)]
+	pub flag: Option<MatchFlag>,
+	/// whether a moderator took the match back. voided matches are left out of everyone's stats
+	#[serde(default)]
+	pub voided: bool,
+	/// for one pairing out of a free-for-all or team game, the id of the game's first pairing.
+	/// `None` for one on one matches
+	#[serde(default)]
+	pub game_id: Option<usize>,
+}
+impl MatchRecord
+{
+	pub fn involves(&self, player: UserId) -> bool
+	{
+		self.challenger == player || self.opponent == player
+	}
+
+	pub fn opponent_of(&self, player: UserId) -> UserId
+	{
+		if self.challenger == player
+		{
+			self.opponent
+		}
+		else
+		{
+			self.challenger
+		}
+	}
+
+	pub fn outcome_for(&self, player: UserId) -> Outcome
+	{
+		Outcome::from(self.winner == player)
+	}
+
+	/// How much `player`'s rating changed from this match, if it was ranked
+	pub fn rating_change_of(&self, player: UserId) -> Option<i32>
+	{
+		self.rating_changes.map(|(challenger, opponent)| {
+			if self.challenger == player
+			{
+				challenger
+			}
+			else
+			{
+				opponent
+			}
+		})
+	}
+
+	/// The rounds won by `player`, then by their opponent
+	pub fn round_score_of(&self, player: UserId) -> (u32, u32)
+	{
+		self.selections_of(player)
+			.fold((0, 0), |(won, lost), (selection, opponent_selection)| {
+				match selection.against(opponent_selection)
+				{
+					Some(Outcome::Win) => (won + 1, lost),
+					Some(Outcome::Loss) => (won, lost + 1),
+					None => (won, lost),
+				}
+			})
+	}
+
+	/// Every round's selections from `player`'s point of view, as their selection then their
+	/// opponent's
+	pub fn selections_of(&self, player: UserId) -> impl Iterator<Item = (Selection, Selection)> + '_
+	{
+		let is_challenger = self.challenger == player;
+		self.rounds.iter().map(move |&(challenger, opponent)| {
+			if is_challenger
+			{
+				(challenger, opponent)
+			}
+			else
+			{
+				(opponent, challenger)
+			}
+		})
+	}
+}
+
+#[derive(Debug, Clone, Copy, serde::Deserialize, serde::Serialize)]
+pub struct MatchFlag
+{
+	pub reason: FlagReason,
+	#[serde(default)]
+	pub status: FlagStatus,
+}
+impl MatchFlag
+{
+	pub fn new(reason: FlagReason) -> Self
+	{
+		Self {
+			reason,
+			status: FlagStatus::Open,
+		}
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub enum FlagReason
+{
+	/// the same player kept winning against the same opponent. `matches` counts every recent
+	/// ranked match between them, including the flagged one
+	LopsidedPairing { matches: u32, wins: u32 },
+	/// a ranked match was forfeited before a single round was decided
+	InstantForfeit,
+}
+impl Display for FlagReason
+{
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result
+	{
+		match self
+		{
+			Self::LopsidedPairing { matches, wins } =>
+			{
+				write!(
+					f,
+					"Won {wins} of {matches} ranked matches against the same opponent in a day"
+				)
+			}
+			Self::InstantForfeit =>
+			{
+				write!(f, "Forfeited a ranked match before any rounds were played")
+			}
+		}
+	}
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub enum FlagStatus
+{
+	/// waiting on a moderator to look at it
+	#[default]
+	Open,
+	/// looked at and left as is
+	Dismissed,
+	/// the match was voided, taking back everything it did to the leaderboard
+	Voided,
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct PlayerStats
+{
+	pub throws: ThrowCounts,
+	pub rounds_won: u32,
+	pub rounds_lost: u32,
+	pub rounds_tied: u32,
+	/// the opponent played the most, and how many times
+	pub favorite_opponent: Option<(UserId, u32)>,
+	/// the opponent lost to the most, and how many times
+	pub nemesis: Option<(UserId, u32)>,
+	/// outcomes of the most recent matches, newest first
+	pub recent_form: Vec<Outcome>,
+}
+impl PlayerStats
+{
+	pub const RECENT_FORM_LENGTH: usize = 10;
+
+	pub fn total_rounds(&self) -> u32
+	{
+		self.rounds_won + self.rounds_lost + self.rounds_tied
+	}
+
+	/// The share of decided rounds won. Ties aren't counted either way
+	pub fn round_win_rate(&self) -> f64
+	{
+		let decided_rounds = self.rounds_won + self.rounds_lost;
+		(decided_rounds != 0)
+			.then(|| f64::from(self.rounds_won) / f64::from(decided_rounds))
+			.unwrap_or_default()
+	}
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ThrowCounts
+{
+	rock: u32,
+	paper: u32,
+	scissors: u32,
+}
+impl ThrowCounts
+{
+	/// Adds up everything `player` threw in `record`
+	fn count(&mut self, record: &MatchRecord, player: UserId)
+	{
+		for (selection, _) in record.selections_of(player)
+		{
+			self[selection] += 1;
+		}
+	}
+
+	pub fn total(&self) -> u32
+	{
+		self.rock + self.paper + self.scissors
+	}
+
+	/// How often `selection` was thrown, from 0 to 1
+	pub fn share(&self, selection: Selection) -> f64
+	{
+		(self.total() != 0)
+			.then(|| f64::from(self[selection]) / f64::from(self.total()))
+			.unwrap_or_default()
+	}
+}
+impl std::ops::Index<Selection> for ThrowCounts
+{
+	type Output = u32;
+
+	fn index(&self, index: Selection) -> &Self::Output
+	{
+		match index
+		{
+			Selection::Rock => &self.rock,
+			Selection::Paper => &self.paper,
+			Selection::Scissors => &self.scissors,
+		}
+	}
+}
+impl std::ops::IndexMut<Selection> for ThrowCounts
+{
+	fn index_mut(&mut self, index: Selection) -> &mut Self::Output
+	{
+		match index
+		{
+			Selection::Rock => &mut self.rock,
+			Selection::Paper => &mut self.paper,
+			Selection::Scissors => &mut self.scissors,
+		}
+	}
+}
+
+#[derive(
+	Debug,
+	Clone,
+	Copy,
+	PartialEq,
+	Eq,
+	Hash,
+	IntoStaticStr,
+	EnumIter,
+	poise::ChoiceParameter,
+	serde::Deserialize,
+	serde::Serialize,
+)]
+pub enum Selection
+{
+	Rock,
+	Paper,
+	Scissors,
+}
+impl Selection
+{
+	pub fn map_all<T>(f: impl FnMut(Self) -> T) -> impl Iterator<Item = T>
+	{
+		Self::iter().map(f)
+	}
+
+	pub fn emoji(self) -> char
+	{
+		match self
+		{
+			Self::Rock => '\u{270a}',
+			Self::Paper => '\u{1f590}',
+			Self::Scissors => '\u{270c}',
+		}
+	}
+
+	pub fn as_str(self) -> &'static str
+	{
+		self.into()
+	}
+
+	/// The selection that beats this one
+	pub fn counter(self) -> Self
+	{
+		match self
+		{
+			Self::Rock => Self::Paper,
+			Self::Paper => Self::Scissors,
+			Self::Scissors => Self::Rock,
+		}
+	}
+
+	/// The outcome of throwing this against `other`, or `None` for a tie
+	pub fn against(self, other: Self) -> Option<Outcome>
+	{
+		if self == other
+		{
+			None
+		}
+		else
+		{
+			Some(Outcome::from(other.counter() == self))
+		}
+	}
+}
+impl rand::distributions::Distribution<Selection> for rand::distributions::Standard
+{
+	fn sample<R: rand::prelude::Rng + ?Sized>(&self, rng: &mut R) -> Selection
+	{
+		match rng.gen_range(0..3)
+		{
+			0 => Selection::Rock,
+			1 => Selection::Paper,
+			2 => Selection::Scissors,
+			_ => panic!("Rng generated an out-of-bounds value trying to create a Selection!"),
+		}
+	}
+}
+impl Display for Selection
+{
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result
+	{
+		write!(f, "{} {}", (*self).as_str(), (*self).emoji())
+	}
+}
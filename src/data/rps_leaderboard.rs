@@ -1,6 +1,8 @@
-use std::{cmp::Ordering, collections::HashMap, future::Future};
+use std::{cmp::Ordering, collections::HashMap};
 
-use poise::serenity_prelude::UserId;
+use poise::serenity_prelude::{Timestamp, UserId};
+
+use super::{Glicko2Rating, RatingSystem, SeasonReset};
 
 #[derive(Debug, Default, serde::Deserialize, serde::Serialize)]
 #[serde(transparent)]
@@ -19,37 +21,198 @@ impl Leaderboard
 		self.map.entry(player).or_default()
 	}
 
+	/// Every player with a score, in no particular order
+	pub fn players(&self) -> impl Iterator<Item = UserId> + '_
+	{
+		self.map.keys().copied()
+	}
+
+	pub fn remove(&mut self, player: UserId) -> Option<Score>
+	{
+		self.map.remove(&player)
+	}
+
+	/// Folds `alt`'s score into `main`'s and takes `alt` off the leaderboard. Returns whether
+	/// `alt` had a score to merge
+	pub fn merge(&mut self, alt: UserId, main: UserId) -> bool
+	{
+		let Some(alt_score) = self.map.remove(&alt)
+		else
+		{
+			return false;
+		};
+		self.score_mut(main).absorb(&alt_score);
+		true
+	}
+
+	/// Every player who has played at least one game, ranked from best to worst
 	pub fn ordered_scores(&self, limit: Option<usize>) -> Vec<LeaderboardEntry<'_>>
 	{
-		let mut unranked_vec = self
-			.map
-			.iter()
-			.take(limit.unwrap_or(usize::MAX))
-			.map(|(id, score)| (*id, score))
-			.collect::<Vec<_>>();
-
-		// unstable sorting by id then stable sorting by score should ensure ordered by score then
-		// by id -morgan 2024-05-19
-		unranked_vec.sort_unstable_by_key(|item| item.0);
-		unranked_vec.sort_by(|a, b| b.1.cmp(a.1));
-
-		// oh god this is a nightmare -morgan 2024-05-20
-		let mut ranked_vec = Vec::with_capacity(unranked_vec.len());
-		let mut unranked_vec_iter = unranked_vec.into_iter().peekable();
-		let mut rank = 1;
-		while let Some((id, score)) = unranked_vec_iter.next()
+		self.sorted_scores(LeaderboardSort::Elo, limit)
+	}
+
+	pub fn sorted_scores(
+		&self,
+		sort: LeaderboardSort,
+		limit: Option<usize>,
+	) -> Vec<LeaderboardEntry<'_>>
+	{
+		rank_scores(self.map.iter().map(|(id, score)| (*id, score)), sort, limit)
+	}
+
+	/// Starts everyone over for a new season. Players kept around by a soft reset have no games
+	/// played, so they stay off the rankings until they play again
+	#[allow(clippy::cast_possible_truncation, clippy::cast_possible_wrap)]
+	pub fn reset(&mut self, reset: SeasonReset)
+	{
+		match reset
 		{
-			ranked_vec.push(LeaderboardEntry::new(id, rank, score));
-			if let Some((_, next_score)) = unranked_vec_iter.peek()
+			SeasonReset::Hard => self.map.clear(),
+			SeasonReset::Soft if !self.map.is_empty() =>
 			{
-				if &score != next_score
+				let mean_elo = (self.map.values().map(|score| i64::from(score.elo)).sum::<i64>()
+					/ self.map.len() as i64) as i32;
+
+				for score in self.map.values_mut()
 				{
-					rank += 1;
+					let elo = mean_elo + (score.elo - mean_elo) / 2;
+					*score = Score {
+						elo,
+						glicko: score.glicko.map(|_| Glicko2Rating::from_elo(elo, 0)),
+						..Score::default()
+					};
 				}
 			}
+			SeasonReset::Soft => (),
+		}
+	}
+
+	/// Prepares every score for a change in rating system. Switching to Glicko-2 converts
+	/// everyone's current ELO into a fresh Glicko-2 rating; switching back to ELO just keeps
+	/// the current ratings as they are
+	pub fn convert_ratings(&mut self, rating_system: RatingSystem)
+	{
+		for score in self.map.values_mut()
+		{
+			score.glicko = match rating_system
+			{
+				RatingSystem::Elo => None,
+				RatingSystem::Glicko2 =>
+				{
+					Some(Glicko2Rating::from_elo(score.elo, score.total_games()))
+				}
+			};
 		}
+	}
+}
+
+/// Sorts scores from best to worst and assigns each one a rank. Equal scores share a rank.
+/// Scores that don't qualify for the sort are left out entirely
+pub fn rank_scores<'a>(
+	scores: impl Iterator<Item = (UserId, &'a Score)>,
+	sort: LeaderboardSort,
+	limit: Option<usize>,
+) -> Vec<LeaderboardEntry<'a>>
+{
+	let mut unranked_vec = scores
+		.filter(|(_, score)| sort.includes(score))
+		.collect::<Vec<_>>();
 
-		ranked_vec
+	// unstable sorting by id then stable sorting by score should ensure ordered by score then
+	// by id -morgan 2024-05-19
+	unranked_vec.sort_unstable_by_key(|item| item.0);
+	unranked_vec.sort_by(|a, b| sort.compare(b.1, a.1));
+	unranked_vec.truncate(limit.unwrap_or(usize::MAX));
+
+	// oh god this is a nightmare -morgan 2024-05-20
+	let mut ranked_vec = Vec::with_capacity(unranked_vec.len());
+	let mut unranked_vec_iter = unranked_vec.into_iter().peekable();
+	let mut rank = 1;
+	while let Some((id, score)) = unranked_vec_iter.next()
+	{
+		ranked_vec.push(LeaderboardEntry::new(id, rank, score));
+		if let Some((_, next_score)) = unranked_vec_iter.peek()
+		{
+			if sort.compare(score, next_score) != Ordering::Equal
+			{
+				rank += 1;
+			}
+		}
+	}
+
+	ranked_vec
+}
+
+/// The parts of a score moderators can change by hand
+#[derive(
+	Debug,
+	Clone,
+	Copy,
+	PartialEq,
+	Eq,
+	poise::ChoiceParameter,
+	serde::Deserialize,
+	serde::Serialize,
+)]
+pub enum ScoreStat
+{
+	#[name = "ELO"]
+	Elo,
+	Wins,
+	Losses,
+}
+impl std::fmt::Display for ScoreStat
+{
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result
+	{
+		f.write_str(match self
+		{
+			Self::Elo => "ELO",
+			Self::Wins => "wins",
+			Self::Losses => "losses",
+		})
+	}
+}
+
+/// The different ways the leaderboard can be ranked
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, poise::ChoiceParameter)]
+pub enum LeaderboardSort
+{
+	#[default]
+	#[name = "ELO"]
+	Elo,
+	Wins,
+	#[name = "Win Rate"]
+	WinRate,
+	#[name = "Games Played"]
+	GamesPlayed,
+}
+impl LeaderboardSort
+{
+	/// players need at least this many games to be ranked by win rate, otherwise anyone who won
+	/// their only game would sit at the top
+	pub const WIN_RATE_MIN_GAMES: u32 = 10;
+
+	pub fn includes(self, score: &Score) -> bool
+	{
+		match self
+		{
+			Self::WinRate => score.total_games() >= Self::WIN_RATE_MIN_GAMES,
+			_ => score.total_games() > 0,
+		}
+	}
+
+	/// Compares two scores by this sort, falling back to the regular ELO ordering for ties
+	pub fn compare(self, a: &Score, b: &Score) -> Ordering
+	{
+		match self
+		{
+			Self::Elo => Ordering::Equal,
+			Self::Wins => a.wins.cmp(&b.wins),
+			Self::WinRate => a.win_rate().total_cmp(&b.win_rate()),
+			Self::GamesPlayed => a.total_games().cmp(&b.total_games()),
+		}
+		.then_with(|| a.cmp(b))
 	}
 }
 
@@ -92,45 +255,36 @@ impl<'a, U> LeaderboardEntry<'a, U>
 		}
 	}
 }
-impl<'a, U, E> LeaderboardEntry<'a, Result<U, E>>
-{
-	pub fn transpose(self) -> Result<LeaderboardEntry<'a, U>, E>
-	{
-		match self.user
-		{
-			Ok(inner) => Ok(LeaderboardEntry {
-				user: inner,
-				rank: self.rank,
-				score: self.score,
-			}),
-			Err(error) => Err(error),
-		}
-	}
-}
-impl<'a, U: Future + Send + Sync> LeaderboardEntry<'a, U>
-{
-	pub async fn await_user(self) -> LeaderboardEntry<'a, U::Output>
-	{
-		LeaderboardEntry {
-			user: self.user.await,
-			rank: self.rank,
-			score: self.score,
-		}
-	}
-}
 
-#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
 pub struct Score
 {
 	pub wins: u32,
 	pub losses: u32,
+	/// the displayed rating. under glicko-2 this is kept in sync with the rounded glicko rating
 	pub elo: i32,
+	#[serde(default)]
+	pub glicko: Option<Glicko2Rating>,
+	#[serde(default)]
+	pub last_played: Option<Timestamp>,
+	/// the highest rating ever reached. `None` for scores from before peaks were tracked
+	#[serde(default)]
+	pub peak_elo: Option<i32>,
+	/// positive for a run of wins, negative for a run of losses
+	#[serde(default)]
+	pub streak: i32,
+	/// the most wins in a row
+	#[serde(default)]
+	pub longest_streak: u32,
 }
 impl Score
 {
 	pub const BASE_ELO: i32 = 1500;
 	/// sets the smoothness of the ELO change curve; lower values exaggerate ELO difference more
 	const ELO_SMOOTHING: f64 = 400.0;
+	/// how long a glicko-2 rating period lasts, in seconds. deviation grows once per period of
+	/// inactivity
+	const RATING_PERIOD: f64 = 7.0 * 24.0 * 60.0 * 60.0;
 
 	pub fn win_rate(&self) -> f64
 	{
@@ -139,7 +293,7 @@ impl Score
 			.unwrap_or_default()
 	}
 
-	fn total_games(&self) -> u32
+	pub fn total_games(&self) -> u32
 	{
 		self.wins + self.losses
 	}
@@ -147,21 +301,260 @@ impl Score
 	pub fn increment_wins(&mut self)
 	{
 		self.wins += 1;
+		self.streak = self.streak.max(0) + 1;
+		self.longest_streak = self.longest_streak.max(self.streak.unsigned_abs());
 	}
 	pub fn increment_losses(&mut self)
 	{
 		self.losses += 1;
+		self.streak = self.streak.min(0) - 1;
+	}
+
+	/// The highest rating ever reached, including the current one
+	pub fn peak_elo(&self) -> i32
+	{
+		self.peak_elo.map_or(self.elo, |peak| peak.max(self.elo))
 	}
 
 	pub fn update_elo(&mut self, opponent_elo: i32, outcome: Outcome) -> i32
 	{
 		self.elo += self.elo_change(opponent_elo, outcome);
+		self.peak_elo = Some(self.peak_elo());
 		self.elo
 	}
 
-	pub fn elo_change(&self, opponent_elo: i32, outcome: Outcome) -> i32
+	/// Updates the rating after a match against `opponent` using whichever rating system the
+	/// server has chosen, returning the new rating
+	pub fn update_rating(
+		&mut self,
+		opponent: &Self,
+		outcome: Outcome,
+		rating_system: RatingSystem,
+		now: Timestamp,
+	) -> i32
+	{
+		self.update_weighted_rating(opponent, outcome, rating_system, now, 1.0)
+	}
+
+	/// Like [`Self::update_rating`], but the rating only moves by `weight` times as much as it
+	/// usually would
+	pub fn update_weighted_rating(
+		&mut self,
+		opponent: &Self,
+		outcome: Outcome,
+		rating_system: RatingSystem,
+		now: Timestamp,
+		weight: f64,
+	) -> i32
+	{
+		self.update_rating_against_many(&[(opponent, outcome, weight)], rating_system, now);
+		self.elo
+	}
+
+	/// Updates the rating after one game against several opponents at once, like a free-for-all,
+	/// returning how much of the change came from each opponent, in the same order. Every opponent
+	/// is rated against this score as it was going into the game, so the order they're given in
+	/// doesn't matter. Each opponent comes with a weight, scaling how far the rating moves from
+	/// the game against them
+	#[allow(clippy::cast_possible_truncation)]
+	pub fn update_rating_against_many(
+		&mut self,
+		opponents: &[(&Self, Outcome, f64)],
+		rating_system: RatingSystem,
+		now: Timestamp,
+	) -> Vec<i32>
+	{
+		let changes = match rating_system
+		{
+			RatingSystem::Elo =>
+			{
+				let changes = opponents
+					.iter()
+					.map(|(opponent, outcome, weight)| {
+						let change = f64::from(self.elo_change(opponent.elo, *outcome));
+						(change * weight).round() as i32
+					})
+					.collect::<Vec<_>>();
+				self.elo += changes.iter().sum::<i32>();
+				changes
+			}
+			RatingSystem::Glicko2 =>
+			{
+				let old_rating = self.glicko_rating(now);
+				let games = opponents
+					.iter()
+					.map(|(opponent, outcome, _)| (opponent.glicko_rating(now), outcome.value()))
+					.collect::<Vec<_>>();
+				let (mut new_rating, game_changes) = old_rating.update_many(&games);
+
+				// weights only hold back how far the rating moves, so deviation and volatility
+				// change as they usually would. each game's share is rounded off of the running
+				// total, so the shares always add up to the change in the rounded rating
+				let mut running_rating = old_rating.rating;
+				let changes = game_changes
+					.into_iter()
+					.zip(opponents)
+					.map(|(change, (_, _, weight))| {
+						let before = running_rating.round() as i32;
+						running_rating = change.mul_add(*weight, running_rating);
+						running_rating.round() as i32 - before
+					})
+					.collect();
+				new_rating.rating = running_rating;
+
+				self.elo = new_rating.rating.round() as i32;
+				self.glicko = Some(new_rating);
+				changes
+			}
+		};
+
+		self.peak_elo = Some(self.peak_elo());
+		self.last_played = Some(now);
+		changes
+	}
+
+	/// What a match against `opponent` would be worth right now, without playing it
+	pub fn stakes(
+		&self,
+		opponent: &Self,
+		rating_system: RatingSystem,
+		now: Timestamp,
+		weight: f64,
+	) -> RatingStakes
+	{
+		let change = |outcome| {
+			self.clone()
+				.update_weighted_rating(opponent, outcome, rating_system, now, weight)
+				- self.elo
+		};
+
+		RatingStakes {
+			win_probability: match rating_system
+			{
+				RatingSystem::Elo => self.win_probability(opponent.elo),
+				RatingSystem::Glicko2 => self
+					.glicko_rating(now)
+					.expected_score(&opponent.glicko_rating(now)),
+			},
+			gain: change(Outcome::Win),
+			loss: change(Outcome::Loss),
+		}
+	}
+
+	/// Takes back a match that was rated, removing its win or loss and undoing the
+	/// `rating_change` it caused. Streaks and peaks are left as they are
+	pub fn revert_match(&mut self, outcome: Outcome, rating_change: i32)
+	{
+		match outcome
+		{
+			Outcome::Win => self.wins = self.wins.saturating_sub(1),
+			Outcome::Loss => self.losses = self.losses.saturating_sub(1),
+		}
+
+		self.shift_rating(-rating_change);
+	}
+
+	pub fn stat(&self, stat: ScoreStat) -> i32
+	{
+		match stat
+		{
+			ScoreStat::Elo => self.elo,
+			ScoreStat::Wins => i32::try_from(self.wins).unwrap_or(i32::MAX),
+			ScoreStat::Losses => i32::try_from(self.losses).unwrap_or(i32::MAX),
+		}
+	}
+
+	/// Overwrites one part of the score. Negative wins and losses are treated as 0
+	pub fn set_stat(&mut self, stat: ScoreStat, value: i32)
+	{
+		match stat
+		{
+			ScoreStat::Elo =>
+			{
+				self.shift_rating(value - self.elo);
+				self.peak_elo = Some(self.peak_elo());
+			}
+			ScoreStat::Wins => self.wins = value.max(0).unsigned_abs(),
+			ScoreStat::Losses => self.losses = value.max(0).unsigned_abs(),
+		}
+	}
+
+	/// Combines another account's score into this one. Wins and losses are added up, and the
+	/// rating of whichever account played more games is kept
+	pub fn absorb(&mut self, other: &Self)
+	{
+		if other.total_games() > self.total_games()
+		{
+			self.elo = other.elo;
+			self.glicko = other.glicko;
+		}
+		if other.last_played > self.last_played
+		{
+			self.last_played = other.last_played;
+			self.streak = other.streak;
+		}
+
+		self.wins += other.wins;
+		self.losses += other.losses;
+		self.peak_elo = Some(self.peak_elo().max(other.peak_elo()));
+		self.longest_streak = self.longest_streak.max(other.longest_streak);
+	}
+
+	/// Moves the rating by `change`, keeping the glicko rating in step
+	fn shift_rating(&mut self, change: i32)
+	{
+		self.elo += change;
+		if let Some(glicko) = &mut self.glicko
+		{
+			glicko.rating += f64::from(change);
+		}
+	}
+
+	/// The current Glicko-2 rating, with deviation grown for however long it's been since the
+	/// last match. Scores without a glicko rating yet are converted from their ELO
+	#[allow(clippy::cast_precision_loss)]
+	pub fn glicko_rating(&self, now: Timestamp) -> Glicko2Rating
+	{
+		let rating = self
+			.glicko
+			.unwrap_or_else(|| Glicko2Rating::from_elo(self.elo, self.total_games()));
+
+		match self.last_played
+		{
+			Some(last_played) =>
+			{
+				let seconds_inactive = now.unix_timestamp() - last_played.unix_timestamp();
+				rating.decayed(seconds_inactive.max(0) as f64 / Self::RATING_PERIOD)
+			}
+			None => rating,
+		}
+	}
+
+	pub fn is_provisional(&self, rating_system: RatingSystem) -> bool
+	{
+		rating_system == RatingSystem::Glicko2
+			&& self.glicko_rating(Timestamp::now()).is_provisional()
+	}
+
+	/// The rating as it should be shown to people, marked with a `?` if it's provisional
+	pub fn rating_string(&self, rating_system: RatingSystem) -> String
+	{
+		format!(
+			"{}{}",
+			self.elo,
+			if self.is_provisional(rating_system) { "?" } else { "" }
+		)
+	}
+
+	/// The chance of beating a player rated `opponent_elo`, going by the difference in ratings
+	pub fn win_probability(&self, opponent_elo: i32) -> f64
 	{
 		let elo_difference = f64::from(opponent_elo - self.elo);
+		(1.0 + f64::powf(10.0, elo_difference / Self::ELO_SMOOTHING)).recip()
+	}
+
+	pub fn elo_change(&self, opponent_elo: i32, outcome: Outcome) -> i32
+	{
 		let sensitivity = match self.elo
 		{
 			..=2100 => 40.0,
@@ -169,9 +562,7 @@ impl Score
 			2401.. => 15.0,
 		};
 
-		let float_change = sensitivity
-			* (outcome.value()
-				- (1.0 + f64::powf(10.0, elo_difference / Self::ELO_SMOOTHING)).recip());
+		let float_change = sensitivity * (outcome.value() - self.win_probability(opponent_elo));
 
 		float_change.floor() as i32
 	}
@@ -184,9 +575,23 @@ impl Default for Score
 			wins: 0,
 			losses: 0,
 			elo: Self::BASE_ELO,
+			glicko: None,
+			last_played: None,
+			peak_elo: None,
+			streak: 0,
+			longest_streak: 0,
 		}
 	}
 }
+// only the parts of the score used for ranking are compared, so equal scores always share a rank
+impl PartialEq for Score
+{
+	fn eq(&self, other: &Self) -> bool
+	{
+		self.cmp(other) == Ordering::Equal
+	}
+}
+impl Eq for Score {}
 impl PartialOrd for Score
 {
 	fn partial_cmp(&self, other: &Self) -> Option<Ordering>
@@ -214,6 +619,16 @@ impl Ord for Score
 	}
 }
 
+/// How much one player's rating stands to change in a match
+#[derive(Debug, Clone, Copy)]
+pub struct RatingStakes
+{
+	pub win_probability: f64,
+	pub gain: i32,
+	/// how much the rating would change by after a loss, which is negative
+	pub loss: i32,
+}
+
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
 pub enum Outcome
 {
@@ -0,0 +1,152 @@
+use std::collections::HashMap;
+
+use poise::serenity_prelude::{RoleId, UserId};
+
+use super::Leaderboard;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub enum RankRequirement
+{
+	/// held by everyone ranked this high or higher
+	Top(u32),
+	/// held by everyone rated at least this much
+	Rating(i32),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub struct RankRole
+{
+	pub role: RoleId,
+	pub requirement: RankRequirement,
+}
+
+/// Roles handed out for placing on the leaderboard. Players only hold the most exclusive
+/// position role and the highest rating role they qualify for, so tiers don't stack up
+#[derive(Debug, Default, Clone, serde::Deserialize, serde::Serialize)]
+#[serde(transparent)]
+pub struct RankRoles
+{
+	roles: Vec<RankRole>,
+}
+impl RankRoles
+{
+	pub fn iter(&self) -> impl Iterator<Item = &RankRole>
+	{
+		self.roles.iter()
+	}
+
+	pub fn is_empty(&self) -> bool
+	{
+		self.roles.is_empty()
+	}
+
+	pub fn role_ids(&self) -> Vec<RoleId>
+	{
+		self.roles.iter().map(|rank_role| rank_role.role).collect()
+	}
+
+	/// Adds a rank role, replacing the requirement if the role was already a rank role
+	pub fn set(&mut self, role: RoleId, requirement: RankRequirement)
+	{
+		self.remove(role);
+		self.roles.push(RankRole { role, requirement });
+	}
+
+	/// Removes a rank role, returning whether it was one
+	pub fn remove(&mut self, role: RoleId) -> bool
+	{
+		let len = self.roles.len();
+		self.roles.retain(|rank_role| rank_role.role != role);
+		self.roles.len() != len
+	}
+
+	pub fn position_role(&self, rank: u32) -> Option<RoleId>
+	{
+		self.roles
+			.iter()
+			.filter_map(|rank_role| match rank_role.requirement
+			{
+				RankRequirement::Top(top) if rank <= top => Some((top, rank_role.role)),
+				_ => None,
+			})
+			.min_by_key(|(top, _)| *top)
+			.map(|(_, role)| role)
+	}
+
+	/// The highest rating role `elo` qualifies for, along with the rating it requires
+	pub fn rating_tier(&self, elo: i32) -> Option<(i32, RoleId)>
+	{
+		self.roles
+			.iter()
+			.filter_map(|rank_role| match rank_role.requirement
+			{
+				RankRequirement::Rating(min_rating) if elo >= min_rating =>
+				{
+					Some((min_rating, rank_role.role))
+				}
+				_ => None,
+			})
+			.max_by_key(|(min_rating, _)| *min_rating)
+	}
+
+	/// Takes note of who should hold which rank roles, so it can be compared against after the
+	/// leaderboard changes
+	pub fn standing(&self, leaderboard: &Leaderboard) -> RankStanding
+	{
+		let entries = leaderboard.ordered_scores(None);
+
+		RankStanding {
+			leader: entries
+				.first()
+				.filter(|entry| entry.rank() == 1)
+				.map(|entry| *entry.user()),
+			tiers: entries
+				.iter()
+				.filter_map(|entry| Some((*entry.user(), self.rating_tier(entry.score().elo)?)))
+				.collect(),
+			earned_roles: entries
+				.iter()
+				.map(|entry| {
+					let roles = self
+						.position_role(entry.rank())
+						.into_iter()
+						.chain(self.rating_tier(entry.score().elo).map(|(_, role)| role))
+						.collect();
+					(*entry.user(), roles)
+				})
+				.collect(),
+		}
+	}
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct RankStanding
+{
+	/// the player alone at the top of the leaderboard, if anyone
+	pub leader: Option<UserId>,
+	/// the rating tier of everyone who has one, along with the rating it requires
+	pub tiers: HashMap<UserId, (i32, RoleId)>,
+	pub earned_roles: HashMap<UserId, Vec<RoleId>>,
+}
+impl RankStanding
+{
+	pub fn earned_roles(&self, player: UserId) -> &[RoleId]
+	{
+		self.earned_roles.get(&player).map_or(&[], Vec::as_slice)
+	}
+
+	/// Everyone whose rank roles differ between the two standings
+	pub fn changed_players(&self, other: &Self) -> Vec<UserId>
+	{
+		let mut changed = self
+			.earned_roles
+			.keys()
+			.chain(other.earned_roles.keys())
+			.filter(|player| self.earned_roles(**player) != other.earned_roles(**player))
+			.copied()
+			.collect::<Vec<_>>();
+		changed.sort_unstable();
+		changed.dedup();
+		changed
+	}
+}
@@ -0,0 +1,57 @@
+use std::collections::HashMap;
+
+use poise::serenity_prelude::{Timestamp, UserId};
+
+/// Every player's rating after each of their ranked matches, oldest first, so it can be graphed
+#[derive(Debug, Default, serde::Deserialize, serde::Serialize)]
+#[serde(transparent)]
+pub struct RatingHistory
+{
+	map: HashMap<UserId, Vec<RatingPoint>>,
+}
+impl RatingHistory
+{
+	/// Notes down everyone's new rating at the same moment, usually right after a match
+	pub fn record(&mut self, ratings: impl IntoIterator<Item = (UserId, i32)>)
+	{
+		let at = Timestamp::now();
+		for (player, rating) in ratings
+		{
+			self.map
+				.entry(player)
+				.or_default()
+				.push(RatingPoint { rating, at });
+		}
+	}
+
+	pub fn of(&self, player: UserId) -> &[RatingPoint]
+	{
+		self.map.get(&player).map_or(&[], Vec::as_slice)
+	}
+
+	pub fn remove(&mut self, player: UserId)
+	{
+		self.map.remove(&player);
+	}
+
+	/// Folds `alt`'s history into `main`'s, keeping it in order
+	pub fn merge(&mut self, alt: UserId, main: UserId)
+	{
+		let Some(alt_points) = self.map.remove(&alt)
+		else
+		{
+			return;
+		};
+
+		let points = self.map.entry(main).or_default();
+		points.extend(alt_points);
+		points.sort_by_key(|point| point.at);
+	}
+}
+
+#[derive(Debug, Clone, Copy, serde::Deserialize, serde::Serialize)]
+pub struct RatingPoint
+{
+	pub rating: i32,
+	pub at: Timestamp,
+}
@@ -0,0 +1,70 @@
+use poise::serenity_prelude::{Timestamp, UserId};
+
+use super::{LeaderboardEntry, Score};
+
+/// The final standings of a finished leaderboard season
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
+pub struct Season
+{
+	pub name: String,
+	pub ended_at: Timestamp,
+	pub standings: Vec<ArchivedStanding>,
+}
+impl Season
+{
+	pub fn new(name: String, standings: Vec<ArchivedStanding>) -> Self
+	{
+		Self {
+			name,
+			ended_at: Timestamp::now(),
+			standings,
+		}
+	}
+}
+
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
+pub struct ArchivedStanding
+{
+	pub user: UserId,
+	pub rank: u32,
+	pub elo: i32,
+	pub wins: u32,
+	pub losses: u32,
+}
+impl ArchivedStanding
+{
+	/// Rebuilds a score out of the archived values so old standings can be displayed the same way
+	/// as the live leaderboard
+	pub fn score(&self) -> Score
+	{
+		Score {
+			wins: self.wins,
+			losses: self.losses,
+			elo: self.elo,
+			..Score::default()
+		}
+	}
+}
+impl From<LeaderboardEntry<'_>> for ArchivedStanding
+{
+	fn from(entry: LeaderboardEntry<'_>) -> Self
+	{
+		Self {
+			user: *entry.user(),
+			rank: entry.rank(),
+			elo: entry.score().elo,
+			wins: entry.score().wins,
+			losses: entry.score().losses,
+		}
+	}
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, poise::ChoiceParameter)]
+pub enum SeasonReset
+{
+	/// everyone starts over from the base rating
+	#[default]
+	Hard,
+	/// everyone's rating is pulled halfway towards the server average
+	Soft,
+}
@@ -0,0 +1,191 @@
+use std::time::Duration;
+
+use poise::serenity_prelude::ChannelId;
+
+use super::RankRoles;
+
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
+pub struct RpsSettings
+{
+	/// seconds a challenged player has to accept or decline
+	#[serde(default = "RpsSettings::default_timeout")]
+	accept_timeout: u64,
+	/// seconds players have to make their selection each round
+	#[serde(default = "RpsSettings::default_timeout")]
+	round_timeout: u64,
+	#[serde(default)]
+	rating_system: RatingSystem,
+	/// whether ranked matches in this server are kept off of the global ladder
+	#[serde(default)]
+	global_ladder_opt_out: bool,
+	#[serde(default)]
+	rank_roles: RankRoles,
+	/// where to announce new leaders and players changing rating tiers
+	#[serde(default)]
+	announcement_channel: Option<ChannelId>,
+	/// whether challenges are played in a thread off of the challenge message by default
+	#[serde(default)]
+	match_threads: bool,
+	/// where to report matches that look like win trading
+	#[serde(default)]
+	moderation_channel: Option<ChannelId>,
+	#[serde(default)]
+	departed_players: DepartedPlayers,
+}
+impl Default for RpsSettings
+{
+	fn default() -> Self
+	{
+		Self {
+			accept_timeout: Self::default_timeout(),
+			round_timeout: Self::default_timeout(),
+			rating_system: RatingSystem::default(),
+			global_ladder_opt_out: false,
+			rank_roles: RankRoles::default(),
+			announcement_channel: None,
+			match_threads: false,
+			moderation_channel: None,
+			departed_players: DepartedPlayers::default(),
+		}
+	}
+}
+impl RpsSettings
+{
+	pub fn accept_timeout(&self) -> Duration
+	{
+		Duration::from_secs(self.accept_timeout)
+	}
+	pub fn set_accept_timeout(&mut self, timeout: Duration)
+	{
+		self.accept_timeout = timeout.as_secs();
+	}
+
+	pub fn round_timeout(&self) -> Duration
+	{
+		Duration::from_secs(self.round_timeout)
+	}
+	pub fn set_round_timeout(&mut self, timeout: Duration)
+	{
+		self.round_timeout = timeout.as_secs();
+	}
+
+	pub fn rating_system(&self) -> RatingSystem
+	{
+		self.rating_system
+	}
+	pub fn set_rating_system(&mut self, rating_system: RatingSystem)
+	{
+		self.rating_system = rating_system;
+	}
+
+	pub fn global_ladder_enabled(&self) -> bool
+	{
+		!self.global_ladder_opt_out
+	}
+	pub fn set_global_ladder_enabled(&mut self, enabled: bool)
+	{
+		self.global_ladder_opt_out = !enabled;
+	}
+
+	pub fn rank_roles(&self) -> &RankRoles
+	{
+		&self.rank_roles
+	}
+	pub fn rank_roles_mut(&mut self) -> &mut RankRoles
+	{
+		&mut self.rank_roles
+	}
+
+	pub fn announcement_channel(&self) -> Option<ChannelId>
+	{
+		self.announcement_channel
+	}
+	pub fn set_announcement_channel(&mut self, channel: Option<ChannelId>)
+	{
+		self.announcement_channel = channel;
+	}
+
+	pub fn match_threads(&self) -> bool
+	{
+		self.match_threads
+	}
+	pub fn set_match_threads(&mut self, enabled: bool)
+	{
+		self.match_threads = enabled;
+	}
+
+	pub fn moderation_channel(&self) -> Option<ChannelId>
+	{
+		self.moderation_channel
+	}
+	pub fn set_moderation_channel(&mut self, channel: Option<ChannelId>)
+	{
+		self.moderation_channel = channel;
+	}
+
+	pub fn departed_players(&self) -> DepartedPlayers
+	{
+		self.departed_players
+	}
+	pub fn set_departed_players(&mut self, departed_players: DepartedPlayers)
+	{
+		self.departed_players = departed_players;
+	}
+
+	fn default_timeout() -> u64
+	{
+		3600
+	}
+}
+
+#[derive(
+	Debug,
+	Default,
+	Clone,
+	Copy,
+	PartialEq,
+	Eq,
+	poise::ChoiceParameter,
+	serde::Deserialize,
+	serde::Serialize,
+)]
+pub enum RatingSystem
+{
+	#[default]
+	#[name = "ELO"]
+	Elo,
+	#[name = "Glicko-2"]
+	Glicko2,
+}
+
+/// What happens to players on the leaderboard once they leave the server
+#[derive(
+	Debug,
+	Default,
+	Clone,
+	Copy,
+	PartialEq,
+	Eq,
+	poise::ChoiceParameter,
+	serde::Deserialize,
+	serde::Serialize,
+)]
+pub enum DepartedPlayers
+{
+	/// they keep their place, with a note that they left
+	#[default]
+	Marked,
+	/// they're left off of the leaderboard, but keep their score in case they come back
+	Hidden,
+	/// their score is deleted as soon as they leave
+	Removed,
+}
+impl DepartedPlayers
+{
+	/// Whether players who left should be left off of the leaderboard. Anyone who left before
+	/// removal was turned on is still around, so they're hidden too
+	pub fn hidden(self) -> bool
+	{
+		self != Self::Marked
+	}
+}
@@ -28,6 +28,10 @@ pub async fn handle(
 		{
 			add_autorole(ctx, new_member, data).await?;
 		}
+		FullEvent::GuildMemberRemoval { guild_id, user, .. } =>
+		{
+			crate::command::remove_departed(ctx, data, *guild_id, user.id).await?;
+		}
 		_ => (),
 	}
 
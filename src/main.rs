@@ -5,6 +5,7 @@
 mod command;
 mod data;
 mod events;
+mod render;
 mod respond;
 
 pub use respond::*;
@@ -39,6 +40,12 @@ async fn main()
 		log::error!("No token specified!");
 		panic!("No token specified!");
 	}
+	let fonts = render::Fonts::load(&config.fonts);
+	if fonts.is_none()
+	{
+		log::warn!("No fonts loaded! Leaderboard images will fall back to text");
+	}
+
 	let intents = GatewayIntents::all();
 
 	let framework = poise::Framework::builder()
@@ -78,6 +85,7 @@ async fn main()
 				Ok(Data::new(
 					config.status,
 					DataManager::load_or_create_from_dir(config.data_directory),
+					fonts,
 				))
 			})
 		})
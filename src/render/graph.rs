@@ -0,0 +1,200 @@
+// every size here is small enough that converting between pixel types can't go wrong, and
+// timestamps lose nothing that would show up at this resolution
+#![allow(
+	clippy::cast_possible_truncation,
+	clippy::cast_possible_wrap,
+	clippy::cast_precision_loss,
+	clippy::cast_sign_loss
+)]
+
+use image::{Rgba, RgbaImage};
+use imageproc::{drawing, rect::Rect};
+
+use super::{Fonts, BACKGROUND, BACKGROUND_ALT, TEXT, TEXT_DIM};
+
+/// the colors each player's line is drawn in, in order
+pub const GRAPH_LINE_COLORS: [Rgba<u8>; 4] = [
+	Rgba([0x58, 0x65, 0xf2, 0xff]),
+	Rgba([0x57, 0xf2, 0x87, 0xff]),
+	Rgba([0xfe, 0xe7, 0x5c, 0xff]),
+	Rgba([0xeb, 0x45, 0x9e, 0xff]),
+];
+
+const GRAPH_WIDTH: u32 = 800;
+const GRAPH_HEIGHT: u32 = 440;
+const PADDING: u32 = 20;
+const LEGEND_HEIGHT: u32 = 40;
+/// room left of the plot for the rating labels
+const AXIS_LABEL_WIDTH: u32 = 56;
+/// room under the plot for the date labels
+const AXIS_LABEL_HEIGHT: u32 = 28;
+/// the most ratings shown on the side of the graph
+const MAX_GRIDLINES: i32 = 6;
+/// lines with at most this many points get a dot on every match, so short histories are readable
+const MAX_MARKED_POINTS: usize = 30;
+
+pub struct GraphLine
+{
+	pub name: String,
+	pub color: Rgba<u8>,
+	/// unix timestamps and the rating at each of them, oldest first
+	pub points: Vec<(i64, i32)>,
+}
+
+/// Draws everyone's rating over time on the same axes. `time_labels` are written under the start
+/// and end of the time axis
+pub fn render_rating_graph(
+	fonts: &Fonts,
+	lines: &[GraphLine],
+	time_labels: (&str, &str),
+) -> RgbaImage
+{
+	let mut canvas = RgbaImage::from_pixel(GRAPH_WIDTH, GRAPH_HEIGHT, BACKGROUND);
+	let plot = Rect::at((PADDING + AXIS_LABEL_WIDTH) as i32, (PADDING + LEGEND_HEIGHT) as i32)
+		.of_size(
+			GRAPH_WIDTH - PADDING * 2 - AXIS_LABEL_WIDTH,
+			GRAPH_HEIGHT - PADDING * 2 - LEGEND_HEIGHT - AXIS_LABEL_HEIGHT,
+		);
+
+	draw_legend(fonts, &mut canvas, lines);
+
+	let (low, high, step) = rating_axis(lines.iter().flat_map(|line| &line.points));
+	let mut rating = low;
+	while rating <= high
+	{
+		let y = plot_y(rating, low, high, plot);
+		drawing::draw_line_segment_mut(
+			&mut canvas,
+			(plot.left() as f32, y),
+			(plot.right() as f32, y),
+			BACKGROUND_ALT,
+		);
+		fonts.draw_text_right(
+			&mut canvas,
+			TEXT_DIM,
+			(plot.left() - 8, y as i32 - 9),
+			16.0,
+			&rating.to_string(),
+		);
+		rating += step;
+	}
+
+	let (start, end) = time_axis(lines.iter().flat_map(|line| &line.points));
+	let label_y = plot.bottom() + 8;
+	fonts.draw_text(&mut canvas, TEXT_DIM, (plot.left(), label_y), 16.0, time_labels.0);
+	fonts.draw_text_right(&mut canvas, TEXT_DIM, (plot.right(), label_y), 16.0, time_labels.1);
+
+	for line in lines
+	{
+		let points = line
+			.points
+			.iter()
+			.map(|(at, rating)| (plot_x(*at, start, end, plot), plot_y(*rating, low, high, plot)))
+			.collect::<Vec<_>>();
+
+		for pair in points.windows(2)
+		{
+			// a single pixel line gets lost, so it's drawn a few times over to thicken it
+			for (dx, dy) in [(0.0, 0.0), (-1.0, 0.0), (1.0, 0.0), (0.0, -1.0), (0.0, 1.0)]
+			{
+				drawing::draw_line_segment_mut(
+					&mut canvas,
+					(pair[0].0 + dx, pair[0].1 + dy),
+					(pair[1].0 + dx, pair[1].1 + dy),
+					line.color,
+				);
+			}
+		}
+
+		let marked = if points.len() <= MAX_MARKED_POINTS
+		{
+			&points[..]
+		}
+		else
+		{
+			&points[points.len() - 1..]
+		};
+		for (x, y) in marked
+		{
+			drawing::draw_filled_circle_mut(&mut canvas, (*x as i32, *y as i32), 4, line.color);
+		}
+	}
+
+	canvas
+}
+
+/// A colored key for every line along the top, with each player's current rating
+fn draw_legend(fonts: &Fonts, canvas: &mut RgbaImage, lines: &[GraphLine])
+{
+	let entry_width = (GRAPH_WIDTH - PADDING * 2) / lines.len().max(1) as u32;
+	for (i, line) in lines.iter().enumerate()
+	{
+		let x = (PADDING + entry_width * i as u32) as i32;
+		drawing::draw_filled_rect_mut(
+			canvas,
+			Rect::at(x, PADDING as i32 + 4).of_size(14, 14),
+			line.color,
+		);
+
+		let label = match line.points.last()
+		{
+			Some((_, rating)) => format!("{} ({rating})", line.name),
+			None => line.name.clone(),
+		};
+		fonts.draw_text(
+			canvas,
+			TEXT,
+			(x + 22, PADDING as i32),
+			20.0,
+			&fonts.fit_text(&label, 20.0, entry_width.saturating_sub(32)),
+		);
+	}
+}
+
+/// The lowest and highest ratings on the side of the graph, and the gap between each gridline.
+/// Both ends are rounded out to a multiple of the gap so the labels come out as round numbers
+fn rating_axis<'a>(points: impl Iterator<Item = &'a (i64, i32)>) -> (i32, i32, i32)
+{
+	let ratings = points.map(|(_, rating)| *rating).collect::<Vec<_>>();
+	let lowest = ratings.iter().min().copied().unwrap_or_default();
+	let highest = ratings.iter().max().copied().unwrap_or_default();
+
+	let step = [10, 25, 50, 100, 200, 250, 500, 1000]
+		.into_iter()
+		.find(|step| (highest - lowest) / step < MAX_GRIDLINES - 1)
+		.unwrap_or(1000);
+	let low = lowest.div_euclid(step) * step;
+	let high = (highest.div_euclid(step) + 1) * step;
+
+	(low, high, step)
+}
+
+/// The earliest and latest times on the graph, spread out a little if they're the same moment so
+/// a single point lands in the middle instead of dividing by zero
+fn time_axis<'a>(points: impl Iterator<Item = &'a (i64, i32)>) -> (i64, i64)
+{
+	let times = points.map(|(at, _)| *at).collect::<Vec<_>>();
+	let start = times.iter().min().copied().unwrap_or_default();
+	let end = times.iter().max().copied().unwrap_or_default();
+
+	if start == end
+	{
+		(start - 3600, end + 3600)
+	}
+	else
+	{
+		(start, end)
+	}
+}
+
+fn plot_x(at: i64, start: i64, end: i64, plot: Rect) -> f32
+{
+	let progress = (at - start) as f64 / (end - start) as f64;
+	plot.left() as f32 + (progress * f64::from(plot.width())) as f32
+}
+
+fn plot_y(rating: i32, low: i32, high: i32, plot: Rect) -> f32
+{
+	let progress = f64::from(rating - low) / f64::from(high - low);
+	plot.bottom() as f32 - (progress * f64::from(plot.height())) as f32
+}
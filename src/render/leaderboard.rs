@@ -0,0 +1,291 @@
+// every size here is small enough that converting between pixel types can't go wrong
+#![allow(
+	clippy::cast_possible_truncation,
+	clippy::cast_possible_wrap,
+	clippy::cast_sign_loss
+)]
+
+use std::ops::RangeInclusive;
+
+use image::{imageops, Rgba, RgbaImage};
+use imageproc::{drawing, rect::Rect};
+
+use super::{Fonts, BACKGROUND, BACKGROUND_ALT, BAR_EMPTY, TEXT, TEXT_DIM};
+
+const GOLD: Rgba<u8> = Rgba([0xd4, 0xaf, 0x37, 0xff]);
+const SILVER: Rgba<u8> = Rgba([0xb4, 0xb8, 0xbc, 0xff]);
+const BRONZE: Rgba<u8> = Rgba([0xcd, 0x7f, 0x32, 0xff]);
+
+pub const LEADERBOARD_AVATAR_SIZE: u32 = 40;
+pub const PROFILE_AVATAR_SIZE: u32 = 160;
+
+const LEADERBOARD_WIDTH: u32 = 760;
+const PADDING: u32 = 20;
+const HEADER_HEIGHT: u32 = 36;
+const ROW_HEIGHT: u32 = 52;
+
+// x positions of each column. numbers are right aligned, so theirs are where they end
+const MEDAL_CENTER: i32 = 44;
+const AVATAR_X: i32 = 76;
+const NAME_X: i32 = 128;
+const NAME_WIDTH: u32 = 232;
+const BAR_X: i32 = 372;
+const BAR_WIDTH: u32 = 128;
+const RATING_RIGHT: i32 = 580;
+const RECORD_RIGHT: i32 = 660;
+const WIN_RATE_RIGHT: i32 = 740;
+
+const PROFILE_WIDTH: u32 = 640;
+const PROFILE_HEIGHT: u32 = 220;
+
+pub struct LeaderboardRow
+{
+	pub rank: u32,
+	pub name: String,
+	pub avatar: Option<RgbaImage>,
+	/// the rating as it should be displayed, which may be marked as provisional
+	pub rating: String,
+	pub elo: i32,
+	pub wins: u32,
+	pub losses: u32,
+	pub win_rate: f64,
+}
+
+pub struct ProfileCard
+{
+	pub name: String,
+	pub avatar: Option<RgbaImage>,
+	pub rank: String,
+	pub rating: String,
+	pub elo: i32,
+	pub wins: u32,
+	pub losses: u32,
+	pub win_rate: f64,
+	pub accent: Rgba<u8>,
+}
+
+/// Draws a page of the leaderboard. `elo_range` is the lowest and highest rating on the whole
+/// leaderboard, which the rating bars are scaled to
+pub fn render_leaderboard(
+	fonts: &Fonts,
+	rows: &[LeaderboardRow],
+	elo_range: &RangeInclusive<i32>,
+) -> RgbaImage
+{
+	let height = PADDING * 2 + HEADER_HEIGHT + ROW_HEIGHT * rows.len() as u32;
+	let mut canvas = RgbaImage::from_pixel(LEADERBOARD_WIDTH, height, BACKGROUND);
+
+	let header_y = PADDING as i32 + 8;
+	fonts.draw_text(&mut canvas, TEXT_DIM, (MEDAL_CENTER - 8, header_y), 16.0, "#");
+	fonts.draw_text(&mut canvas, TEXT_DIM, (NAME_X, header_y), 16.0, "USER");
+	fonts.draw_text_right(&mut canvas, TEXT_DIM, (RATING_RIGHT, header_y), 16.0, "ELO");
+	fonts.draw_text_right(&mut canvas, TEXT_DIM, (RECORD_RIGHT, header_y), 16.0, "W-L");
+	fonts.draw_text_right(&mut canvas, TEXT_DIM, (WIN_RATE_RIGHT, header_y), 16.0, "WRATE");
+
+	for (i, row) in rows.iter().enumerate()
+	{
+		let top = (PADDING + HEADER_HEIGHT + ROW_HEIGHT * i as u32) as i32;
+		let middle = top + ROW_HEIGHT as i32 / 2;
+		let text_y = middle - 12;
+
+		if i % 2 == 0
+		{
+			drawing::draw_filled_rect_mut(
+				&mut canvas,
+				Rect::at(PADDING as i32, top).of_size(LEADERBOARD_WIDTH - PADDING * 2, ROW_HEIGHT),
+				BACKGROUND_ALT,
+			);
+		}
+
+		draw_rank(fonts, &mut canvas, row.rank, (MEDAL_CENTER, middle));
+
+		if let Some(avatar) = &row.avatar
+		{
+			imageops::overlay(
+				&mut canvas,
+				avatar,
+				i64::from(AVATAR_X),
+				i64::from(middle - LEADERBOARD_AVATAR_SIZE as i32 / 2),
+			);
+		}
+
+		fonts.draw_text(
+			&mut canvas,
+			TEXT,
+			(NAME_X, text_y),
+			22.0,
+			&fonts.fit_text(&row.name, 22.0, NAME_WIDTH),
+		);
+
+		draw_bar(
+			&mut canvas,
+			Rect::at(BAR_X, middle - 5).of_size(BAR_WIDTH, 10),
+			bar_fill(row.elo, elo_range),
+			medal_color(row.rank).unwrap_or(TEXT_DIM),
+		);
+
+		fonts.draw_text_right(&mut canvas, TEXT, (RATING_RIGHT, text_y), 22.0, &row.rating);
+		fonts.draw_text_right(
+			&mut canvas,
+			TEXT,
+			(RECORD_RIGHT, text_y),
+			22.0,
+			&format!("{}-{}", row.wins, row.losses),
+		);
+		fonts.draw_text_right(
+			&mut canvas,
+			TEXT_DIM,
+			(WIN_RATE_RIGHT, text_y),
+			22.0,
+			format!("{:.4}", row.win_rate).trim_start_matches('0'),
+		);
+	}
+
+	canvas
+}
+
+pub fn render_profile_card(
+	fonts: &Fonts,
+	card: &ProfileCard,
+	elo_range: &RangeInclusive<i32>,
+) -> RgbaImage
+{
+	let mut canvas = RgbaImage::from_pixel(PROFILE_WIDTH, PROFILE_HEIGHT, BACKGROUND);
+	drawing::draw_filled_rect_mut(
+		&mut canvas,
+		Rect::at(0, 0).of_size(8, PROFILE_HEIGHT),
+		card.accent,
+	);
+
+	let avatar_y = (PROFILE_HEIGHT - PROFILE_AVATAR_SIZE) as i32 / 2;
+	match &card.avatar
+	{
+		Some(avatar) => imageops::overlay(&mut canvas, avatar, 32, i64::from(avatar_y)),
+		None => drawing::draw_filled_circle_mut(
+			&mut canvas,
+			(
+				32 + PROFILE_AVATAR_SIZE as i32 / 2,
+				avatar_y + PROFILE_AVATAR_SIZE as i32 / 2,
+			),
+			PROFILE_AVATAR_SIZE as i32 / 2,
+			BACKGROUND_ALT,
+		),
+	}
+
+	let text_x = 224;
+	let text_width = PROFILE_WIDTH - text_x as u32 - PADDING;
+
+	fonts.draw_text(
+		&mut canvas,
+		TEXT,
+		(text_x, 24),
+		36.0,
+		&fonts.fit_text(&card.name, 36.0, text_width),
+	);
+	fonts.draw_text(
+		&mut canvas,
+		TEXT_DIM,
+		(text_x, 70),
+		20.0,
+		&fonts.fit_text(&card.rank, 20.0, text_width),
+	);
+
+	fonts.draw_text(&mut canvas, TEXT, (text_x, 102), 30.0, &card.rating);
+	let rating_width = fonts.text_width(&card.rating, 30.0) as i32;
+	fonts.draw_text(
+		&mut canvas,
+		TEXT_DIM,
+		(text_x + rating_width + 8, 110),
+		20.0,
+		"ELO",
+	);
+
+	draw_bar(
+		&mut canvas,
+		Rect::at(text_x, 144).of_size(text_width, 12),
+		bar_fill(card.elo, elo_range),
+		card.accent,
+	);
+
+	fonts.draw_text(
+		&mut canvas,
+		TEXT,
+		(text_x, 170),
+		22.0,
+		&format!(
+			"{} W  ·  {} L  ·  {:.2}%",
+			card.wins,
+			card.losses,
+			card.win_rate * 100.0
+		),
+	);
+
+	canvas
+}
+
+fn draw_rank(fonts: &Fonts, canvas: &mut RgbaImage, rank: u32, (center_x, center_y): (i32, i32))
+{
+	let rank_string = rank.to_string();
+	let text_width = fonts.text_width(&rank_string, 20.0) as i32;
+
+	if let Some(color) = medal_color(rank)
+	{
+		drawing::draw_filled_circle_mut(canvas, (center_x, center_y), 15, color);
+		fonts.draw_text(
+			canvas,
+			BACKGROUND,
+			(center_x - text_width / 2, center_y - 11),
+			20.0,
+			&rank_string,
+		);
+	}
+	else
+	{
+		fonts.draw_text(
+			canvas,
+			TEXT_DIM,
+			(center_x - text_width / 2, center_y - 11),
+			20.0,
+			&rank_string,
+		);
+	}
+}
+
+fn medal_color(rank: u32) -> Option<Rgba<u8>>
+{
+	match rank
+	{
+		1 => Some(GOLD),
+		2 => Some(SILVER),
+		3 => Some(BRONZE),
+		_ => None,
+	}
+}
+
+/// How much of a rating bar should be filled in, from 0 to 1. Everyone gets at least a sliver so
+/// the lowest rated player doesn't look like they have no rating at all
+fn bar_fill(elo: i32, elo_range: &RangeInclusive<i32>) -> f64
+{
+	let span = elo_range.end() - elo_range.start();
+	if span <= 0
+	{
+		return 1.0;
+	}
+
+	(f64::from(elo - elo_range.start()) / f64::from(span)).clamp(0.05, 1.0)
+}
+
+fn draw_bar(canvas: &mut RgbaImage, rect: Rect, fill: f64, color: Rgba<u8>)
+{
+	drawing::draw_filled_rect_mut(canvas, rect, BAR_EMPTY);
+
+	let filled_width = (f64::from(rect.width()) * fill).round() as u32;
+	if filled_width > 0
+	{
+		drawing::draw_filled_rect_mut(
+			canvas,
+			Rect::at(rect.left(), rect.top()).of_size(filled_width, rect.height()),
+			color,
+		);
+	}
+}
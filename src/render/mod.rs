@@ -0,0 +1,191 @@
+mod graph;
+mod leaderboard;
+
+pub use graph::*;
+pub use leaderboard::*;
+
+use std::{io::Cursor, path::PathBuf};
+
+use ab_glyph::{Font, FontArc};
+use image::{imageops::FilterType, ImageFormat, Rgba, RgbaImage};
+use imageproc::drawing;
+use poise::serenity_prelude::Color;
+
+use crate::Error;
+
+const BACKGROUND: Rgba<u8> = Rgba([0x2b, 0x2d, 0x31, 0xff]);
+const BACKGROUND_ALT: Rgba<u8> = Rgba([0x31, 0x33, 0x38, 0xff]);
+const TEXT: Rgba<u8> = Rgba([0xf2, 0xf3, 0xf5, 0xff]);
+const TEXT_DIM: Rgba<u8> = Rgba([0x94, 0x9b, 0xa4, 0xff]);
+const BAR_EMPTY: Rgba<u8> = Rgba([0x1e, 0x1f, 0x22, 0xff]);
+
+/// The fonts used to draw text, in order of preference. Characters missing from the first font
+/// are drawn with the next font that has them, so names in other scripts still come out right
+pub struct Fonts(Vec<FontArc>);
+impl Fonts
+{
+	/// Loads every font file that can be read, logging the ones that can't. Returns `None` if no
+	/// fonts could be loaded at all, since nothing could be drawn without one
+	pub fn load(paths: &[PathBuf]) -> Option<Self>
+	{
+		let fonts = paths
+			.iter()
+			.filter_map(|path| {
+				match std::fs::read(path)
+					.map_err(Error::from)
+					.and_then(|bytes| FontArc::try_from_vec(bytes).map_err(Error::from))
+				{
+					Ok(font) => Some(font),
+					Err(err) =>
+					{
+						log::error!("Could not load font {}! {err}", path.display());
+						None
+					}
+				}
+			})
+			.collect::<Vec<_>>();
+
+		(!fonts.is_empty()).then_some(Self(fonts))
+	}
+
+	pub fn text_width(&self, text: &str, scale: f32) -> u32
+	{
+		self.runs(text)
+			.into_iter()
+			.map(|(font, run)| drawing::text_size(scale, font, run).0)
+			.sum()
+	}
+
+	#[allow(clippy::cast_possible_wrap)]
+	pub fn draw_text(
+		&self,
+		canvas: &mut RgbaImage,
+		color: Rgba<u8>,
+		(x, y): (i32, i32),
+		scale: f32,
+		text: &str,
+	)
+	{
+		let mut x = x;
+		for (font, run) in self.runs(text)
+		{
+			drawing::draw_text_mut(canvas, color, x, y, scale, font, run);
+			x += drawing::text_size(scale, font, run).0 as i32;
+		}
+	}
+
+	/// Draws text so that it ends at `right`, for lining numbers up in columns
+	#[allow(clippy::cast_possible_wrap)]
+	pub fn draw_text_right(
+		&self,
+		canvas: &mut RgbaImage,
+		color: Rgba<u8>,
+		(right, y): (i32, i32),
+		scale: f32,
+		text: &str,
+	)
+	{
+		let x = right - self.text_width(text, scale) as i32;
+		self.draw_text(canvas, color, (x, y), scale, text);
+	}
+
+	/// Cuts text down to fit within `max_width` pixels, ending it with an ellipsis if anything had
+	/// to be cut off
+	pub fn fit_text(&self, text: &str, scale: f32, max_width: u32) -> String
+	{
+		if self.text_width(text, scale) <= max_width
+		{
+			return text.to_owned();
+		}
+
+		let mut fitted = text.to_owned();
+		while fitted.pop().is_some()
+		{
+			let candidate = format!("{}…", fitted.trim_end());
+			if self.text_width(&candidate, scale) <= max_width
+			{
+				return candidate;
+			}
+		}
+
+		String::from("…")
+	}
+
+	fn font_for(&self, c: char) -> &FontArc
+	{
+		self.0
+			.iter()
+			.find(|font| font.glyph_id(c).0 != 0)
+			.unwrap_or(&self.0[0])
+	}
+
+	/// Splits text up into runs of characters that are all drawn with the same font
+	fn runs<'a>(&'a self, text: &'a str) -> Vec<(&'a FontArc, &'a str)>
+	{
+		let mut runs = Vec::new();
+		let mut run_start = 0;
+		let mut run_font: Option<&FontArc> = None;
+
+		for (i, c) in text.char_indices()
+		{
+			let font = self.font_for(c);
+			match run_font
+			{
+				Some(current) if std::ptr::eq(current, font) => (),
+				Some(current) =>
+				{
+					runs.push((current, &text[run_start..i]));
+					run_start = i;
+					run_font = Some(font);
+				}
+				None => run_font = Some(font),
+			}
+		}
+
+		if let Some(font) = run_font
+		{
+			runs.push((font, &text[run_start..]));
+		}
+
+		runs
+	}
+}
+
+/// Downloads an image (usually an avatar) and crops it into a circle `diameter` pixels across
+pub async fn fetch_circle(url: &str, diameter: u32) -> Result<RgbaImage, Error>
+{
+	let bytes = reqwest::get(url)
+		.await?
+		.error_for_status()?
+		.bytes()
+		.await?;
+
+	let mut image = image::load_from_memory(&bytes)?
+		.resize_to_fill(diameter, diameter, FilterType::Triangle)
+		.into_rgba8();
+
+	let radius = f64::from(diameter) / 2.0;
+	for (x, y, pixel) in image.enumerate_pixels_mut()
+	{
+		let distance = (f64::from(x) + 0.5 - radius).hypot(f64::from(y) + 0.5 - radius);
+		if distance > radius
+		{
+			pixel[3] = 0;
+		}
+	}
+
+	Ok(image)
+}
+
+pub fn encode_png(image: &RgbaImage) -> Result<Vec<u8>, Error>
+{
+	let mut bytes = Vec::new();
+	image.write_to(&mut Cursor::new(&mut bytes), ImageFormat::Png)?;
+	Ok(bytes)
+}
+
+pub fn rgba(color: Color) -> Rgba<u8>
+{
+	let (r, g, b) = color.tuple();
+	Rgba([r, g, b, 0xff])
+}